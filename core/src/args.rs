@@ -0,0 +1,177 @@
+// The launcher's command-line flags. They can come in any order, unlike
+// the subcommands (doctor, ctl, resolve), which come first and take the
+// rest of the line.
+
+use print::{self, Field};
+
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Args {
+    // `--query TEXT` starts with the entry filled in, e.g. inside a submenu
+    pub query: Option<String>,
+    // `--menu PATH` makes a submenu the root, e.g. for a power menu binding
+    pub menu: Option<String>,
+    // `--layout top-bar` overrides the layout in the config
+    pub layout: Option<String>,
+    // `--daemon` stays resident with the window hidden until toggled
+    pub daemon: bool,
+    // `--print` writes the chosen entry's command and `--print=FIELD`
+    // another field of it, instead of running it
+    pub print: Option<Field>,
+    // `--toggle`, `--show [QUERY]` or `--hide`, with an optional
+    // `--output NAME`, as the command for a running daemon
+    pub remote: Option<String>,
+}
+
+const VALUES: &[&str] = &["--query", "--menu", "--layout", "--output"];
+
+fn set(slot: &mut Option<String>, flag: &str, value: String)
+    -> Result<(), String> {
+    if slot.is_some() {
+        return Err(format!("{} is given twice", flag));
+    }
+
+    *slot = Some(value);
+    Ok(())
+}
+
+pub fn parse(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut output = None;
+    let mut remote: Option<&str> = None;
+    let mut words = vec![];
+    // The flag that decides what this run does, for conflicts
+    let mut mode: Option<&str> = None;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let arg = arg.as_str();
+
+        if VALUES.contains(&arg) {
+            let value = match args.next() {
+                Some(v) => v.clone(),
+                None    => return Err(format!("{} needs a value", arg)),
+            };
+
+            let slot = match arg {
+                "--query"  => &mut parsed.query,
+                "--menu"   => &mut parsed.menu,
+                "--layout" => &mut parsed.layout,
+                _          => &mut output,
+            };
+
+            set(slot, arg, value)?;
+            continue;
+        }
+
+        let (name, field) = match arg.split_once('=') {
+            Some((n, f)) => (n, Some(f)),
+            None         => (arg, None),
+        };
+
+        match name {
+            "--daemon" | "--print" | "--toggle" | "--show" | "--hide" => {
+                if let Some(m) = mode {
+                    return Err(format!("{} can't be used with {}", name, m));
+                }
+
+                mode = Some(name);
+            },
+            _ if arg.starts_with('-') => {
+                return Err(format!("Unknown option {}", arg));
+            },
+            _ => {
+                words.push(arg);
+                continue;
+            },
+        };
+
+        if field.is_some() && name != "--print" {
+            return Err(format!("{} doesn't take a value", name));
+        }
+
+        match name {
+            "--daemon" => parsed.daemon = true,
+            "--print"  => parsed.print = Some(print::parse_field(
+                field.unwrap_or(""))?),
+            _          => remote = Some(&name[2..]),
+        };
+    }
+
+    if output.is_some() && remote.is_none() {
+        return Err("--output only goes with --toggle, --show or --hide".
+            to_string());
+    }
+
+    if !words.is_empty() && remote != Some("show") {
+        return Err(format!("Unexpected argument {}", words[0]));
+    }
+
+    if let Some(command) = remote {
+        let mut line = command.to_string();
+
+        if let Some(o) = output {
+            line.push_str(&format!(" --output {}", o));
+        }
+
+        let query = if words.is_empty() {
+            parsed.query.clone()
+        } else {
+            Some(words.join(" "))
+        };
+
+        if let Some(q) = query.filter(|_| command == "show") {
+            line.push_str(&format!(" {}", q));
+        }
+
+        parsed.remote = Some(line);
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use print::Field;
+    use super::parse;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn it_parses_flags_in_any_order() {
+        let parsed = parse(&args("--query web --print=title")).unwrap();
+        assert_eq!(parsed.query, Some("web".to_string()));
+        assert_eq!(parsed.print, Some(Field::Title));
+
+        let parsed = parse(&args("--menu p --daemon --layout top-bar")).
+            unwrap();
+        assert!(parsed.daemon);
+        assert_eq!(parsed.menu, Some("p".to_string()));
+        assert_eq!(parsed.layout, Some("top-bar".to_string()));
+    }
+
+    #[test]
+    fn it_builds_daemon_commands() {
+        assert_eq!(parse(&args("--output DP-1 --show web search")).unwrap().
+                   remote, Some("show --output DP-1 web search".to_string()));
+        assert_eq!(parse(&args("--query web --show")).unwrap().remote,
+                   Some("show web".to_string()));
+        assert_eq!(parse(&args("--hide")).unwrap().remote,
+                   Some("hide".to_string()));
+    }
+
+    #[test]
+    fn it_rejects_bad_combinations() {
+        assert!(parse(&args("--print --daemon")).is_err());
+        assert!(parse(&args("--toggle --show")).is_err());
+        assert!(parse(&args("--output DP-1")).is_err());
+        assert!(parse(&args("--query")).is_err());
+        assert!(parse(&args("--query a --query b")).is_err());
+        assert!(parse(&args("--explode")).is_err());
+        assert!(parse(&args("--daemon stray")).is_err());
+        assert!(parse(&args("--print=colour")).is_err());
+        assert!(parse(&args("--daemon=yes")).is_err());
+    }
+}
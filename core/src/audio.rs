@@ -0,0 +1,95 @@
+// Audio outputs through pactl, which talks to both PulseAudio and
+// PipeWire's pulse server.
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["pactl"]
+    }
+}
+
+fn default_prefix() -> String {
+    "audio".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Sink {
+    pub name: String,
+    pub description: String,
+}
+
+// The Name and Description of each sink in `pactl list sinks`.
+pub fn parse_sinks(output: &str) -> Vec<Sink> {
+    let mut sinks: Vec<Sink> = vec![];
+
+    for line in output.lines() {
+        let line = line.trim();
+
+        if let Some(name) = line.strip_prefix("Name:") {
+            sinks.push(Sink {
+                name: name.trim().to_string(),
+                description: name.trim().to_string(),
+            });
+        } else if let Some(description) = line.strip_prefix("Description:") {
+            if let Some(sink) = sinks.last_mut() {
+                sink.description = description.trim().to_string();
+            }
+        }
+    }
+
+    sinks
+}
+
+pub fn sinks() -> Result<Vec<Sink>, String> {
+    cli::output("pactl", &["list", "sinks"]).map(|o| parse_sinks(&o))
+}
+
+pub fn default_sink() -> Option<String> {
+    cli::output("pactl", &["get-default-sink"]).ok().
+        map(|o| o.trim().to_string())
+}
+
+// Makes the sink the default and moves everything already playing to it.
+pub fn select_command(sink: &str) -> String {
+    let sink = shell_quote(sink);
+
+    format!("pactl set-default-sink {0} && \
+             pactl list short sink-inputs | cut -f1 | \
+             xargs -r -I{{}} pactl move-sink-input {{}} {0}", sink)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sink, parse_sinks};
+
+    #[test]
+    fn it_parses_sinks() {
+        let output = "Sink #48\n\
+                      \tState: RUNNING\n\
+                      \tName: alsa_output.pci.analog-stereo\n\
+                      \tDescription: Built-in Audio Analog Stereo\n\
+                      \n\
+                      Sink #52\n\
+                      \tName: bluez_output.00_11\n";
+
+        assert_eq!(parse_sinks(output), vec![
+            Sink {
+                name: "alsa_output.pci.analog-stereo".to_string(),
+                description: "Built-in Audio Analog Stereo".to_string(),
+            },
+            Sink {
+                name: "bluez_output.00_11".to_string(),
+                description: "bluez_output.00_11".to_string(),
+            },
+        ]);
+    }
+}
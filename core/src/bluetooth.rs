@@ -0,0 +1,91 @@
+// Paired Bluetooth devices through bluetoothctl.
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["bluetoothctl"]
+    }
+}
+
+fn default_prefix() -> String {
+    "bt".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Device {
+    pub address: String,
+    pub name: String,
+    pub connected: bool,
+}
+
+// "Device <address> <name>" lines, as `bluetoothctl devices` prints them.
+pub fn parse_devices(output: &str) -> Vec<(String, String)> {
+    output.lines().filter_map(|line| {
+        let mut parts = line.trim().splitn(3, ' ');
+
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some("Device"), Some(address), name) => Some((
+                address.to_string(),
+                name.unwrap_or(address).to_string())),
+            _ => None,
+        }
+    }).collect()
+}
+
+fn bluetoothctl(args: &[&str]) -> Result<String, String> {
+    cli::output("bluetoothctl", args)
+}
+
+pub fn devices() -> Result<Vec<Device>, String> {
+    // Older bluez only has paired-devices, newer only the filter argument
+    let paired = bluetoothctl(&["devices", "Paired"]).
+        or_else(|_| bluetoothctl(&["paired-devices"]))?;
+    let connected = bluetoothctl(&["devices", "Connected"]).
+        map(|o| parse_devices(&o)).unwrap_or_default();
+
+    Ok(parse_devices(&paired).into_iter().map(|(address, name)| Device {
+        connected: connected.iter().any(|(a, _)| *a == address),
+        address,
+        name,
+    }).collect())
+}
+
+pub fn powered() -> bool {
+    bluetoothctl(&["show"]).
+        is_ok_and(|o| o.lines().any(|l| l.trim() == "Powered: yes"))
+}
+
+pub fn connect_command(device: &Device) -> String {
+    let verb = if device.connected { "disconnect" } else { "connect" };
+    format!("bluetoothctl {} {}", verb, shell_quote(&device.address))
+}
+
+pub fn power_command(on: bool) -> String {
+    format!("bluetoothctl power {}", if on { "on" } else { "off" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_devices;
+
+    #[test]
+    fn it_parses_devices() {
+        let output = "Device 00:11:22:33:44:55 WH-1000XM4\n\
+                      [CHG] Controller 66:77 Discovering: no\n\
+                      Device AA:BB:CC:DD:EE:FF\n";
+
+        assert_eq!(parse_devices(output), vec![
+            ("00:11:22:33:44:55".to_string(), "WH-1000XM4".to_string()),
+            ("AA:BB:CC:DD:EE:FF".to_string(), "AA:BB:CC:DD:EE:FF".to_string()),
+        ]);
+    }
+}
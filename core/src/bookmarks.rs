@@ -0,0 +1,226 @@
+// Bookmarks from Firefox and Chromium-based browsers, read without touching
+// the browsers' own files: Chromium keeps them as JSON, and Firefox's
+// sqlite database is copied first since the browser holds a lock on it.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use serde_json::{self, Value};
+
+use config::Tools;
+use paths::expand_home;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["sqlite3"]
+    }
+}
+
+fn default_prefix() -> String {
+    "bm".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Bookmark {
+    pub title: String,
+    pub url: String,
+}
+
+const CHROMIUM_DIRS: &[&str] = &[
+    "~/.config/chromium",
+    "~/.config/google-chrome",
+    "~/.config/BraveSoftware/Brave-Browser",
+    "~/.config/vivaldi",
+];
+
+const FIREFOX_DIR: &str = "~/.mozilla/firefox";
+
+// Chromium's Bookmarks file has a tree under each of "roots", with folders
+// holding "children" and bookmarks having a "url".
+pub fn parse_chromium(json: &str) -> Vec<Bookmark> {
+    fn collect(node: &Value, found: &mut Vec<Bookmark>) {
+        if let (Some(name), Some(url)) = (node["name"].as_str(),
+                                          node["url"].as_str()) {
+            found.push(Bookmark {
+                title: name.to_string(),
+                url: url.to_string(),
+            });
+        }
+
+        if let Some(children) = node["children"].as_array() {
+            for c in children {
+                collect(c, found);
+            }
+        }
+    }
+
+    let value: Value = match serde_json::from_str(json) {
+        Ok (v) => v,
+        Err(_) => return vec![],
+    };
+
+    let mut found = vec![];
+
+    if let Some(roots) = value["roots"].as_object() {
+        for root in roots.values() {
+            collect(root, &mut found);
+        }
+    }
+
+    found
+}
+
+// sqlite3 output as "title\turl" lines. Untitled bookmarks go by their URL.
+pub fn parse_firefox(output: &str) -> Vec<Bookmark> {
+    output.lines().filter_map(|line| {
+        let (title, url) = line.split_once('\t')?;
+
+        if url.is_empty() || url.starts_with("place:") {
+            return None;
+        }
+
+        Some(Bookmark {
+            title: if title.is_empty() { url } else { title }.to_string(),
+            url: url.to_string(),
+        })
+    }).collect()
+}
+
+fn read_firefox(places: &Path) -> Vec<Bookmark> {
+    let copy = env::temp_dir().join("blaunch-places.sqlite");
+
+    if fs::copy(places, &copy).is_err() {
+        return vec![];
+    }
+
+    let output = Command::new("sqlite3").
+        args(["-readonly", "-separator", "\t"]).arg(&copy).
+        arg("SELECT coalesce(b.title, ''), p.url FROM moz_bookmarks b \
+             JOIN moz_places p ON b.fk = p.id WHERE b.type = 1").
+        output();
+
+    let _ = fs::remove_file(&copy);
+
+    match output {
+        Ok (ref o) if o.status.success() =>
+            parse_firefox(&String::from_utf8_lossy(&o.stdout)),
+        _ => vec![],
+    }
+}
+
+// Every profile's bookmark store that exists. Chromium profiles are
+// "Default" and "Profile N"; Firefox ones have random names.
+pub fn sources() -> Vec<PathBuf> {
+    let mut found = vec![];
+
+    let profiles = |dir: &str| -> Vec<PathBuf> {
+        fs::read_dir(expand_home(dir)).map(|e| {
+            e.filter_map(|e| e.ok()).map(|e| e.path()).collect()
+        }).unwrap_or_default()
+    };
+
+    for dir in CHROMIUM_DIRS {
+        found.extend(profiles(dir).into_iter().map(|p| p.join("Bookmarks")));
+    }
+
+    found.extend(profiles(FIREFOX_DIR).into_iter().
+        map(|p| p.join("places.sqlite")));
+
+    found.retain(|p| p.is_file());
+    found.sort();
+    found
+}
+
+pub fn read(source: &Path) -> Vec<Bookmark> {
+    if source.file_name().is_some_and(|n| n == "places.sqlite") {
+        return read_firefox(source);
+    }
+
+    fs::read_to_string(source).map(|j| parse_chromium(&j)).
+        unwrap_or_default()
+}
+
+// Bookmarks from every browser, re-read whenever a store changes on disk.
+#[derive(Default)]
+pub struct Store {
+    read: Vec<(PathBuf, Option<SystemTime>)>,
+    bookmarks: Vec<Bookmark>,
+}
+
+impl Store {
+    pub fn new() -> Store {
+        Store::default()
+    }
+
+    pub fn bookmarks(&mut self) -> &[Bookmark] {
+        let current: Vec<(PathBuf, Option<SystemTime>)> = sources().
+            into_iter().map(|p| {
+                let modified = fs::metadata(&p).and_then(|m| m.modified()).
+                    ok();
+                (p, modified)
+            }).collect();
+
+        if current != self.read {
+            let mut bookmarks: Vec<Bookmark> = current.iter().
+                flat_map(|(p, _)| read(p)).collect();
+
+            // The same page is often bookmarked in more than one browser
+            bookmarks.sort_by(|a, b| a.url.cmp(&b.url));
+            bookmarks.dedup_by(|a, b| a.url == b.url);
+
+            self.bookmarks = bookmarks;
+            self.read = current;
+        }
+
+        &self.bookmarks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bookmark, parse_chromium, parse_firefox};
+
+    fn bookmark(title: &str, url: &str) -> Bookmark {
+        Bookmark { title: title.to_string(), url: url.to_string() }
+    }
+
+    #[test]
+    fn it_parses_chromium_bookmarks() {
+        let json = r#"{"roots": {
+            "bookmark_bar": {"name": "Bar", "type": "folder", "children": [
+                {"name": "Rust", "type": "url", "url": "https://rust-lang.org"},
+                {"name": "Docs", "type": "folder", "children": [
+                    {"name": "GTK", "type": "url", "url": "https://gtk.org"}
+                ]}
+            ]},
+            "other": {"name": "Other", "type": "folder", "children": []}
+        }}"#;
+
+        assert_eq!(parse_chromium(json), vec![
+            bookmark("Rust", "https://rust-lang.org"),
+            bookmark("GTK", "https://gtk.org"),
+        ]);
+        assert_eq!(parse_chromium("{"), vec![]);
+    }
+
+    #[test]
+    fn it_parses_firefox_rows() {
+        let output = "Rust\thttps://rust-lang.org\n\
+                      \thttps://gtk.org\n\
+                      Recent\tplace:sort=8\n";
+
+        assert_eq!(parse_firefox(output), vec![
+            bookmark("Rust", "https://rust-lang.org"),
+            bookmark("https://gtk.org", "https://gtk.org"),
+        ]);
+    }
+}
@@ -0,0 +1,54 @@
+use std::io;
+
+use desktop;
+use node::Node;
+use session;
+use timers;
+
+pub fn run(builtin: &str) -> io::Result<()> {
+    // Timer builtins carry arguments
+    if let Some(result) = timers::run(builtin) {
+        return result;
+    }
+
+    match builtin {
+        session::LOCK        => session::lock(),
+        session::CAFFEINE    => session::toggle_caffeine(),
+        session::LOGOUT      => session::logout(),
+        session::SUSPEND | session::HIBERNATE | session::REBOOT |
+        session::POWEROFF    => session::power(builtin),
+        desktop::NIGHT_LIGHT => desktop::toggle_night_light(),
+        _                    => Err(io::Error::new(io::ErrorKind::NotFound,
+                                    format!("Unknown builtin {}", builtin))),
+    }
+}
+
+// Whether a builtin can run, without running it: one we know, with
+// arguments it understands. Prompt answers are checked with this.
+pub fn check(builtin: &str) -> Result<(), String> {
+    if let Some(result) = timers::check(builtin) {
+        return result;
+    }
+
+    match builtin {
+        session::LOCK | session::CAFFEINE | session::LOGOUT |
+        session::SUSPEND | session::HIBERNATE | session::REBOOT |
+        session::POWEROFF | desktop::NIGHT_LIGHT => Ok(()),
+        _ => Err(format!("Unknown builtin {}", builtin)),
+    }
+}
+
+// Built-ins with state show it next to their description.
+pub fn describe(node: &Node) -> String {
+    let active = match node.builtin.as_deref() {
+        Some(session::CAFFEINE)    => session::caffeine_active(),
+        Some(desktop::NIGHT_LIGHT) => desktop::night_light_active(),
+        _                          => false,
+    };
+
+    if active {
+        format!("{} (active)", node.description)
+    } else {
+        node.description.clone()
+    }
+}
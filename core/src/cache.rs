@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// Results keyed by normalized query, each kept for a fixed time-to-live. A
+// zero TTL disables caching entirely, for sources like the window list that
+// must always be fresh.
+pub struct Cache<V> {
+    ttl: Duration,
+    entries: HashMap<String, (Instant, V)>,
+}
+
+// Typing "foo" and "foo " should hit the same entry. Case is kept, since
+// some sources care about it (unit symbols, paths, translations).
+pub fn normalize(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+impl<V: Clone> Cache<V> {
+    pub fn new(ttl: Duration) -> Cache<V> {
+        Cache {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, query: &str) -> Option<V> {
+        let key = normalize(query);
+
+        let expired = match self.entries.get(&key) {
+            Some(&(stored, _)) => stored.elapsed() >= self.ttl,
+            None               => return None,
+        };
+
+        if expired {
+            self.entries.remove(&key);
+            return None;
+        }
+
+        self.entries.get(&key).map(|e| e.1.clone())
+    }
+
+    pub fn insert(&mut self, query: &str, value: V) {
+        if self.ttl == Duration::from_secs(0) {
+            return;
+        }
+
+        let ttl = self.ttl;
+        self.entries.retain(|_, e| e.0.elapsed() < ttl);
+        self.entries.insert(normalize(query), (Instant::now(), value));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::Cache;
+
+    #[test]
+    fn it_hits_on_normalized_query() {
+        let mut cache = Cache::new(Duration::from_secs(60));
+        cache.insert("Foo  bar", 1);
+
+        assert_eq!(cache.get(" Foo bar "), Some(1));
+        assert_eq!(cache.get("foo bar"), None);
+        assert_eq!(cache.get("Foo"), None);
+    }
+
+    #[test]
+    fn it_never_caches_with_zero_ttl() {
+        let mut cache = Cache::new(Duration::from_secs(0));
+        cache.insert("foo", 1);
+
+        assert_eq!(cache.get("foo"), None);
+    }
+}
@@ -0,0 +1,177 @@
+// A small arithmetic evaluator for the calculator: + - * / % ^, brackets,
+// unary minus and decimals, with the usual precedence. ^ binds tightest and
+// is right associative.
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    at: usize,
+    expr: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.get(self.at).is_some_and(|c| c.is_whitespace()) {
+            self.at += 1;
+        }
+
+        self.chars.get(self.at).cloned()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.at += 1;
+            return true;
+        }
+
+        false
+    }
+
+    fn error(&self) -> String {
+        format!("Can't evaluate {}", self.expr)
+    }
+
+    fn sum(&mut self) -> Result<f64, String> {
+        let mut value = self.product()?;
+
+        loop {
+            if self.eat('+') {
+                value += self.product()?;
+            } else if self.eat('-') {
+                value -= self.product()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+
+        loop {
+            if self.eat('*') {
+                value *= self.unary()?;
+            } else if self.eat('/') {
+                value /= self.unary()?;
+            } else if self.eat('%') {
+                value %= self.unary()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64, String> {
+        if self.eat('-') {
+            return self.unary().map(|v| -v);
+        }
+
+        if self.eat('+') {
+            return self.unary();
+        }
+
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.atom()?;
+
+        if self.eat('^') {
+            return self.unary().map(|e| base.powf(e));
+        }
+
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<f64, String> {
+        if self.eat('(') {
+            let value = self.sum()?;
+
+            if !self.eat(')') {
+                return Err(self.error());
+            }
+
+            return Ok(value);
+        }
+
+        self.peek();
+        let start = self.at;
+
+        while self.chars.get(self.at).
+                is_some_and(|c| c.is_ascii_digit() || *c == '.') {
+            self.at += 1;
+        }
+
+        let number: String = self.chars[start..self.at].iter().collect();
+        number.parse().map_err(|_| self.error())
+    }
+}
+
+pub fn eval(expr: &str) -> Result<f64, String> {
+    let mut parser = Parser { chars: expr.chars().collect(), at: 0, expr };
+    let value = parser.sum()?;
+
+    if parser.peek().is_some() {
+        return Err(parser.error());
+    }
+
+    if !value.is_finite() {
+        return Err(format!("{} isn't a number", expr));
+    }
+
+    Ok(value)
+}
+
+// Whether a query is meant for the calculator without the "=" prefix:
+// numbers with at least one operator between them, and nothing else.
+pub fn looks_like_math(input: &str) -> bool {
+    let input = input.trim();
+
+    input.chars().any(|c| c.is_ascii_digit()) &&
+    input.chars().skip(1).any(|c| "+-*/%^".contains(c)) &&
+    input.chars().all(|c| c.is_ascii_digit() || c.is_whitespace() ||
+                          "+-*/%^().".contains(c))
+}
+
+// Up to 10 decimal places, without trailing zeroes.
+pub fn format(value: f64) -> String {
+    let text = format!("{:.10}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+
+    match text {
+        "-0" => "0".to_string(),
+        t    => t.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eval, format, looks_like_math};
+
+    #[test]
+    fn it_evaluates_with_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(eval("-2 ^ 2"), Ok(-4.0));
+        assert_eq!(eval("2 ^ 3 ^ 2"), Ok(512.0));
+        assert_eq!(eval("10 % 4 / 4"), Ok(0.5));
+        assert!(eval("1 +").is_err());
+        assert!(eval("(1").is_err());
+        assert!(eval("1 / 0").is_err());
+    }
+
+    #[test]
+    fn it_spots_math() {
+        assert!(looks_like_math("12*4"));
+        assert!(looks_like_math("(3 + 4) / 2"));
+        assert!(!looks_like_math("-5"));
+        assert!(!looks_like_math("wf"));
+        assert!(!looks_like_math("!ls -l"));
+    }
+
+    #[test]
+    fn it_formats_results() {
+        assert_eq!(format(7.0), "7");
+        assert_eq!(format(0.1 + 0.2), "0.3");
+        assert_eq!(format(-0.0), "0");
+    }
+}
@@ -0,0 +1,67 @@
+// Helpers for providers built on command-line tools: running them for their
+// output, reading the JSON many of them print, and opening commands in a
+// terminal.
+
+use std::process::Command;
+
+use serde_json::Value;
+
+// A tool's standard output, or what it printed to standard error when it
+// failed.
+pub fn output(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program).args(args).output().
+        map_err(|e| format!("Can't run {}: {}", program, e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().
+            to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Output with one JSON object per line, like `docker ps --format json`.
+// Lines that don't parse are skipped.
+pub fn json_lines(output: &str) -> Vec<Value> {
+    output.lines().filter(|l| !l.trim().is_empty()).
+        filter_map(|l| serde_json::from_str(l).ok()).collect()
+}
+
+// The first of the keys an object has, as a string. Arrays give their
+// first element, so the same lookup works across tools that disagree on
+// whether a field is a list.
+pub fn field(value: &Value, keys: &[&str]) -> Option<String> {
+    let found = keys.iter().filter_map(|k| value.get(k)).next()?;
+
+    match *found {
+        Value::String(ref s) => Some(s.clone()),
+        Value::Array(ref a)  => a.first().and_then(|v| v.as_str()).
+            map(|s| s.to_string()),
+        Value::Number(ref n) => Some(n.to_string()),
+        Value::Bool(b)       => Some(b.to_string()),
+        _                    => None,
+    }
+}
+
+// A command line that runs the command in a new terminal window.
+pub fn in_terminal(terminal: &str, command: &str) -> String {
+    format!("{} -e {}", terminal, command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{field, json_lines};
+
+    #[test]
+    fn it_reads_json_lines() {
+        let values = json_lines("{\"ID\":\"ab12\",\"Names\":\"web\"}\n\
+                                 not json\n\
+                                 {\"Id\":\"cd34\",\"Names\":[\"db\"]}\n");
+
+        assert_eq!(values.len(), 2);
+        assert_eq!(field(&values[0], &["ID", "Id"]), Some("ab12".to_string()));
+        assert_eq!(field(&values[1], &["ID", "Id"]), Some("cd34".to_string()));
+        assert_eq!(field(&values[1], &["Names"]), Some("db".to_string()));
+        assert_eq!(field(&values[1], &["State"]), None);
+    }
+}
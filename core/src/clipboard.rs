@@ -0,0 +1,125 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+
+use serde_json;
+
+use paths::expand_home;
+
+// Opt-in history of copied text, recorded by the daemon. Entries only live
+// in the runtime directory, gone at logout, unless `persist` is set.
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_entries")]
+    pub entries: usize,
+    // Bigger copies aren't recorded
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: usize,
+    // Keep the history across logins, in ~/.local/share
+    #[serde(default)]
+    pub persist: bool,
+}
+
+fn default_prefix() -> String {
+    "clip".to_string()
+}
+
+fn default_entries() -> usize {
+    50
+}
+
+fn default_max_bytes() -> usize {
+    16 * 1024
+}
+
+const PERSIST_FILE: &str = "~/.local/share/blaunch/clipboard.json";
+
+// Where the history is kept. Without a runtime directory (or persist)
+// there's nowhere private enough, so nothing is recorded.
+pub fn path(config: &Config) -> Option<PathBuf> {
+    if config.persist {
+        return Some(PathBuf::from(expand_home(PERSIST_FILE)));
+    }
+
+    env::var_os("XDG_RUNTIME_DIR").
+        map(|d| PathBuf::from(d).join("blaunch-clipboard.json"))
+}
+
+// Newest first.
+pub fn load(config: &Config) -> Vec<String> {
+    path(config).and_then(|p| fs::read_to_string(p).ok()).
+        and_then(|c| serde_json::from_str(&c).ok()).unwrap_or_default()
+}
+
+// The history after copying `text`: moved or added to the front, and
+// trimmed to size.
+pub fn add(mut entries: Vec<String>, text: &str, limit: usize)
+    -> Vec<String> {
+    entries.retain(|e| e != text);
+    entries.insert(0, text.to_string());
+    entries.truncate(limit);
+    entries
+}
+
+fn save(config: &Config, entries: &[String]) -> io::Result<()> {
+    let path = match path(config) {
+        Some(p) => p,
+        None    => return Ok(()),
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let json = serde_json::to_string(entries)?;
+
+    // Only readable by us, since copies include passwords now and then
+    let mut file = OpenOptions::new().create(true).write(true).truncate(true).
+        mode(0o600).open(&path)?;
+
+    file.write_all(json.as_bytes())
+}
+
+// Watches what the frontend reads off the clipboard, recording each new
+// copy once. The config is passed in each time so a reload applies.
+#[derive(Default)]
+pub struct Recorder {
+    last: Option<String>,
+}
+
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder::default()
+    }
+
+    pub fn seen(&mut self, config: &Config, text: &str) -> io::Result<()> {
+        if self.last.as_deref() == Some(text) {
+            return Ok(());
+        }
+
+        self.last = Some(text.to_string());
+
+        if text.trim().is_empty() || text.len() > config.max_bytes {
+            return Ok(());
+        }
+
+        save(config, &add(load(config), text, config.entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::add;
+
+    #[test]
+    fn it_moves_copies_to_the_front() {
+        let entries = vec!["b".to_string(), "a".to_string(), "c".to_string()];
+
+        assert_eq!(add(entries.clone(), "a", 5), vec!["a", "b", "c"]);
+        assert_eq!(add(entries, "d", 3), vec!["d", "b", "a"]);
+    }
+}
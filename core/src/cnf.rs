@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+
+use node::on_path;
+use provider::{Action, Item};
+
+// Packages for a few commands whose package is named differently, for
+// systems without a command-not-found database.
+const BUNDLED: &[(&str, &str)] = &[
+    ("rg", "ripgrep"),
+    ("nvim", "neovim"),
+    ("http", "httpie"),
+    ("convert", "imagemagick"),
+    ("ffprobe", "ffmpeg"),
+];
+
+// Things sh runs that never show up on PATH.
+const SHELL_WORDS: &[&str] = &[
+    "cd", "export", "unset", "alias", "source", ".", "exec", "eval", "set",
+    "if", "for", "while", "case", "until", "{", "(", "!", "true", "false",
+    "test", "[", "echo", "printf", "read", "wait", "kill", "ulimit", "umask",
+];
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Suggestion {
+    pub package: String,
+    // Command line that installs the package, if the package manager is
+    // known
+    pub install: Option<String>,
+}
+
+// The program a shell command line would run, if it isn't on PATH. Leading
+// variable assignments like `FOO=1 cmd` are skipped.
+pub fn missing_binary(command: &str) -> Option<String> {
+    let binary = command.split_whitespace().
+        find(|w| !w.contains('=') || w.starts_with('='))?;
+
+    if SHELL_WORDS.contains(&binary) {
+        return None;
+    }
+
+    let found = if binary.contains('/') {
+        Path::new(binary).exists()
+    } else {
+        on_path(binary)
+    };
+
+    if found {
+        None
+    } else {
+        Some(binary.to_string())
+    }
+}
+
+// Ubuntu and Debian's command-not-found prints lines like
+// "sudo apt install ripgrep", possibly next to snap alternatives.
+pub fn parse_debian(output: &str) -> Vec<String> {
+    output.lines().filter(|l| l.contains("apt ")).filter_map(|l| {
+        let mut words = l.split_whitespace().skip_while(|w| *w != "install");
+        words.next();
+        words.next().map(|p| p.to_string())
+    }).collect()
+}
+
+// Arch's `pkgfile -b` prints "repository/package" per match.
+pub fn parse_pkgfile(output: &str) -> Vec<String> {
+    output.lines().filter_map(|l| l.trim().rsplit('/').next()).
+        filter(|p| !p.is_empty()).map(|p| p.to_string()).collect()
+}
+
+fn run(program: &str, args: &[&str]) -> String {
+    match Command::new(program).args(args).output() {
+        Ok (o) => format!("{}{}", String::from_utf8_lossy(&o.stdout),
+                          String::from_utf8_lossy(&o.stderr)),
+        Err(_) => String::new(),
+    }
+}
+
+// Install command for the package manager found on this system.
+pub fn install_command(package: &str) -> Option<String> {
+    let managers = [
+        ("apt", "pkexec apt install -y"),
+        ("pacman", "pkexec pacman -S --noconfirm"),
+        ("dnf", "pkexec dnf install -y"),
+        ("zypper", "pkexec zypper install -y"),
+    ];
+
+    managers.iter().find(|m| on_path(m.0)).
+        map(|m| format!("{} {}", m.1, package))
+}
+
+// Asks the distribution which packages provide the binary, falling back to
+// the bundled list.
+pub fn lookup(binary: &str) -> Vec<Suggestion> {
+    let mut packages = vec![];
+
+    if Path::new("/usr/lib/command-not-found").exists() {
+        packages = parse_debian(&run("/usr/lib/command-not-found",
+                                     &["--", binary]));
+    }
+
+    if packages.is_empty() && on_path("pkgfile") {
+        packages = parse_pkgfile(&run("pkgfile", &["-b", "--", binary]));
+    }
+
+    if packages.is_empty() {
+        packages = BUNDLED.iter().filter(|b| b.0 == binary).
+            map(|b| b.1.to_string()).collect();
+    }
+
+    packages.into_iter().map(|p| Suggestion {
+        install: install_command(&p),
+        package: p,
+    }).collect()
+}
+
+// Looks binaries up on worker threads, since the databases take a moment,
+// and remembers the answers. The UI polls it like the other background work.
+pub struct Finder {
+    found: HashMap<String, Option<Vec<Suggestion>>>,
+    sender: Sender<(String, Vec<Suggestion>)>,
+    receiver: Receiver<(String, Vec<Suggestion>)>,
+}
+
+impl Default for Finder {
+    fn default() -> Finder {
+        Finder::new()
+    }
+}
+
+impl Finder {
+    pub fn new() -> Finder {
+        let (sender, receiver) = channel();
+
+        Finder {
+            found: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    // The packages for a binary, or None while they're being looked up.
+    pub fn lookup(&mut self, binary: &str) -> Option<&[Suggestion]> {
+        if !self.found.contains_key(binary) {
+            self.found.insert(binary.to_string(), None);
+
+            let binary = binary.to_string();
+            let sender = self.sender.clone();
+
+            thread::spawn(move || {
+                let suggestions = lookup(&binary);
+                let _ = sender.send((binary, suggestions));
+            });
+        }
+
+        self.found[binary].as_deref()
+    }
+
+    // Collects finished lookups, returning true if any finished.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+
+        for (binary, suggestions) in self.receiver.try_iter() {
+            self.found.insert(binary, Some(suggestions));
+            changed = true;
+        }
+
+        changed
+    }
+
+    // Rows for a binary that isn't installed. Installing runs as a job so
+    // its progress shows in the launcher.
+    pub fn items(&mut self, binary: &str) -> Vec<Item> {
+        let mut items = vec![Item::message(
+            &format!("{}: command not found", binary))];
+
+        let suggestions = match self.lookup(binary) {
+            Some(s) => s,
+            None    => {
+                items.push(Item::loading(
+                    &format!("Looking for a package with {}..", binary)));
+                return items;
+            },
+        };
+
+        items.extend(suggestions.iter().map(|s| Item {
+            title: format!("Install {}", s.package),
+            description: format!("to get {}", binary),
+            action: s.install.clone().map(Action::Spawn),
+            icon: Some("system-software-install".to_string()),
+            wait: true,
+            ..Default::default()
+        }));
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{missing_binary, parse_debian, parse_pkgfile};
+
+    #[test]
+    fn it_finds_missing_commands() {
+        assert_eq!(missing_binary("LANG=C sh -c true"), None);
+        assert_eq!(missing_binary("cd /tmp"), None);
+        assert_eq!(missing_binary("FOO=1 blaunch-no-such-tool --x"),
+                   Some("blaunch-no-such-tool".to_string()));
+        assert_eq!(missing_binary(""), None);
+
+        let debian = "Command 'rg' not found, but can be installed with:\n\
+                      sudo snap install ripgrep  # version 13\n\
+                      sudo apt  install ripgrep  # version 13\n";
+        assert_eq!(parse_debian(debian), vec!["ripgrep"]);
+        assert_eq!(parse_pkgfile("extra/ripgrep\n"), vec!["ripgrep"]);
+    }
+}
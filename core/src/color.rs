@@ -0,0 +1,250 @@
+// Colors typed as hex, "rgb(...)" or "hsl(...)", converted between those
+// notations.
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub fn rgba(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    // Short forms repeat each digit, so "#f80" is "#ff8800"
+    let digits: String = match hex.len() {
+        3 | 4 => hex.chars().flat_map(|c| vec![c, c]).collect(),
+        6 | 8 => hex.to_string(),
+        _     => return None,
+    };
+
+    let byte = |i: usize| u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).
+        ok();
+
+    Some(Color {
+        r: byte(0)?,
+        g: byte(1)?,
+        b: byte(2)?,
+        a: if digits.len() == 8 { byte(3)? } else { 255 },
+    })
+}
+
+// The numbers inside "name(...)", separated by commas or spaces, with an
+// optional "/ alpha" at the end.
+fn arguments<'a>(input: &'a str, names: &[&str]) -> Option<Vec<&'a str>> {
+    let open = input.find('(')?;
+
+    if !names.contains(&input[..open].trim()) || !input.ends_with(')') {
+        return None;
+    }
+
+    Some(input[open + 1..input.len() - 1].
+        split(|c: char| c == ',' || c == '/' || c.is_whitespace()).
+        filter(|a| !a.is_empty()).collect())
+}
+
+// "50%" of 255, or a plain number as is.
+fn channel(value: &str) -> Option<u8> {
+    let number = match value.strip_suffix('%') {
+        Some(p) => p.parse::<f64>().ok()? * 2.55,
+        None    => value.parse::<f64>().ok()?,
+    };
+
+    if !(0.0..=255.0).contains(&number) {
+        return None;
+    }
+
+    Some(number.round() as u8)
+}
+
+// Alpha as 0-1 or a percentage.
+fn alpha(value: Option<&&str>) -> Option<u8> {
+    let value = match value {
+        Some(v) => v,
+        None    => return Some(255),
+    };
+
+    let fraction = match value.strip_suffix('%') {
+        Some(p) => p.parse::<f64>().ok()? / 100.0,
+        None    => value.parse::<f64>().ok()?,
+    };
+
+    if !(0.0..=1.0).contains(&fraction) {
+        return None;
+    }
+
+    Some((fraction * 255.0).round() as u8)
+}
+
+fn percent(value: &str) -> Option<f64> {
+    let number: f64 = value.trim_end_matches('%').parse().ok()?;
+
+    if !(0.0..=100.0).contains(&number) {
+        return None;
+    }
+
+    Some(number / 100.0)
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = (h.rem_euclid(360.0)) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    let byte = |v: f64| ((v + m) * 255.0).round() as u8;
+    (byte(r), byte(g), byte(b))
+}
+
+fn rgb_to_hsl(color: &Color) -> (f64, f64, f64) {
+    let r = color.r as f64 / 255.0;
+    let g = color.g as f64 / 255.0;
+    let b = color.b as f64 / 255.0;
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+
+    if d == 0.0 {
+        return (0.0, 0.0, l);
+    }
+
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+
+    let h = if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+
+    (h, s, l)
+}
+
+pub fn parse(input: &str) -> Option<Color> {
+    let input = input.trim().to_lowercase();
+
+    if let Some(hex) = input.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+
+    if let Some(a) = arguments(&input, &["rgb", "rgba"]) {
+        if a.len() != 3 && a.len() != 4 {
+            return None;
+        }
+
+        return Some(Color {
+            r: channel(a[0])?,
+            g: channel(a[1])?,
+            b: channel(a[2])?,
+            a: alpha(a.get(3))?,
+        });
+    }
+
+    if let Some(a) = arguments(&input, &["hsl", "hsla"]) {
+        if a.len() != 3 && a.len() != 4 {
+            return None;
+        }
+
+        let hue: f64 = a[0].trim_end_matches("deg").parse().ok()?;
+        let (r, g, b) = hsl_to_rgb(hue, percent(a[1])?, percent(a[2])?);
+
+        return Some(Color {
+            r,
+            g,
+            b,
+            a: alpha(a.get(3))?,
+        });
+    }
+
+    None
+}
+
+fn alpha_text(a: u8) -> String {
+    let text = format!("{:.2}", a as f64 / 255.0);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// The color in each notation, with the notation's name. Alpha is only
+// written out when the color isn't opaque.
+pub fn formats(color: &Color) -> Vec<(&'static str, String)> {
+    let opaque = color.a == 255;
+    let (h, s, l) = rgb_to_hsl(color);
+
+    let hex = if opaque {
+        format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", color.r, color.g, color.b,
+                color.a)
+    };
+
+    let rgb = if opaque {
+        format!("rgb({}, {}, {})", color.r, color.g, color.b)
+    } else {
+        format!("rgba({}, {}, {}, {})", color.r, color.g, color.b,
+                alpha_text(color.a))
+    };
+
+    let hsl = format!("{}, {}%, {}%", h.round(), (s * 100.0).round(),
+                      (l * 100.0).round());
+
+    let hsl = if opaque {
+        format!("hsl({})", hsl)
+    } else {
+        format!("hsla({}, {})", hsl, alpha_text(color.a))
+    };
+
+    vec![("hex", hex), ("rgb", rgb), ("hsl", hsl)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, formats, parse};
+
+    fn color(r: u8, g: u8, b: u8, a: u8) -> Option<Color> {
+        Some(Color { r, g, b, a })
+    }
+
+    #[test]
+    fn it_parses_colors() {
+        assert_eq!(parse("#F80"), color(255, 136, 0, 255));
+        assert_eq!(parse("#3366cc80"), color(51, 102, 204, 128));
+        assert_eq!(parse("rgb(51, 102, 204)"), color(51, 102, 204, 255));
+        assert_eq!(parse("rgba(51 102 204 / 50%)"), color(51, 102, 204, 128));
+        assert_eq!(parse("hsl(220, 60%, 50%)"), color(51, 102, 204, 255));
+        assert_eq!(parse("#12345"), None);
+        assert_eq!(parse("rgb(300, 0, 0)"), None);
+        assert_eq!(parse("rgb 1 2 3"), None);
+    }
+
+    #[test]
+    fn it_formats_colors() {
+        let formats = formats(&Color { r: 51, g: 102, b: 204, a: 128 });
+
+        assert_eq!(formats, vec![
+            ("hex", "#3366cc80".to_string()),
+            ("rgb", "rgba(51, 102, 204, 0.5)".to_string()),
+            ("hsl", "hsla(220, 60%, 50%, 0.5)".to_string()),
+        ]);
+    }
+}
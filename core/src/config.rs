@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::fs::File;
+
+use serde_json;
+
+use audio;
+use bluetooth;
+use bookmarks;
+use clipboard;
+use containers;
+use convert;
+use desktop;
+use dictionary;
+use displays;
+use drives;
+use emoji;
+use fallback;
+use files;
+use history;
+use keyboard;
+use keys;
+use kube;
+use macros;
+use node::{self, Node};
+use man;
+use notes;
+use pass;
+use pins;
+use processes;
+use projects::Project;
+use providers::{apps, run};
+use qr;
+use quick;
+use recent_files;
+use repos;
+use screenshot;
+use search;
+use session;
+use snippets;
+use ssh;
+use steam;
+use theme;
+use timers;
+use tmux;
+use todo;
+use translate;
+use trash;
+use units;
+use update;
+use vms;
+use vpn;
+use weather;
+use wifi;
+use window;
+use windows;
+
+// What a feature shells out to, for the doctor to look for. Any one of the
+// tools is enough; none means it doesn't need anything.
+pub trait Tools {
+    fn tools(&self) -> Vec<&str>;
+}
+
+// The program a configured command line runs.
+pub fn program(command: &str) -> &str {
+    command.split_whitespace().next().unwrap_or(command)
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct Config {
+    pub shell_prefix: String,
+    pub menu: Vec<Node>,
+    pub notes: Option<notes::Config>,
+    pub quick_settings: Option<quick::Config>,
+    pub wallpapers: Option<desktop::WallpaperConfig>,
+    pub applications: Option<apps::Config>,
+    pub run: Option<run::Config>,
+    pub history: Option<history::Config>,
+    pub macros: Option<macros::Config>,
+    pub fallback: Option<fallback::Config>,
+    // Checks for new releases while the daemon runs
+    pub update: Option<update::Config>,
+    // Emoji picker, on ":" unless another prefix is given
+    pub emoji: Option<emoji::Config>,
+    pub search: Option<search::Config>,
+    // Window switcher, listing open windows through wmctrl
+    pub windows: Option<windows::Config>,
+    // Hosts from the ssh config and known_hosts
+    pub ssh: Option<ssh::Config>,
+    // Recent copies, recorded while the daemon runs
+    pub clipboard: Option<clipboard::Config>,
+    // File search, through a command like plocate or by walking ~
+    pub files: Option<files::Config>,
+    // Process killer, listing what's running from /proc
+    pub processes: Option<processes::Config>,
+    // Lock, log out, suspend, reboot and so on
+    pub power: Option<session::PowerConfig>,
+    // systemd units, with start, stop, restart and status
+    pub units: Option<units::Config>,
+    // Firefox and Chromium bookmarks
+    pub bookmarks: Option<bookmarks::Config>,
+    // Entries from pass, copied or typed without showing them
+    pub pass: Option<pass::Config>,
+    // Wi-Fi networks and NetworkManager connections
+    pub wifi: Option<wifi::Config>,
+    // Audio outputs through pactl
+    pub audio: Option<audio::Config>,
+    // Paired Bluetooth devices
+    pub bluetooth: Option<bluetooth::Config>,
+    // tmux sessions and windows
+    pub tmux: Option<tmux::Config>,
+    // Git repositories and workspace files under root directories
+    pub repos: Option<repos::Config>,
+    // Installed Steam games
+    pub steam: Option<steam::Config>,
+    // libvirt virtual machines
+    pub vms: Option<vms::Config>,
+    // Docker or Podman containers and images
+    pub containers: Option<containers::Config>,
+    // Removable drives, mounted through udisks
+    pub drives: Option<drives::Config>,
+    // Saved display layouts from autorandr and arandr
+    pub displays: Option<displays::Config>,
+    // Keyboard layouts
+    pub keyboard: Option<keyboard::Config>,
+    // Unit and currency conversions like "5 mi to km"
+    pub convert: Option<convert::Config>,
+    // Word definitions from dict or sdcv
+    pub dictionary: Option<dictionary::Config>,
+    // Translates "tr <lang> <text>"
+    pub translate: Option<translate::Config>,
+    // Tasks from a todo.txt file
+    pub todo: Option<todo::Config>,
+    // Text snippets by name, copied or typed out
+    pub snippets: Option<snippets::Config>,
+    // GTK's recently used files
+    pub recent_files: Option<recent_files::Config>,
+    // Manual pages from the apropos index
+    pub man: Option<man::Config>,
+    // Kubernetes contexts and namespaces
+    pub kube: Option<kube::Config>,
+    // NetworkManager VPNs and wg-quick tunnels
+    pub vpn: Option<vpn::Config>,
+    // Text as a QR code
+    pub qr: Option<qr::Config>,
+    // Countdown timers and stopwatches, sent by the daemon
+    pub timers: Option<timers::Config>,
+    // Current weather from a wttr.in style endpoint
+    pub weather: Option<weather::Config>,
+    // Files in the XDG trash, restored or emptied
+    pub trash: Option<trash::Config>,
+    #[serde(default)]
+    pub projects: Vec<Project>,
+    // Where the screenshot builtin saves and which tool it uses
+    #[serde(default)]
+    pub screenshot: screenshot::Config,
+    #[serde(default)]
+    pub window: window::Config,
+    #[serde(default)]
+    pub theme: theme::Config,
+    #[serde(default)]
+    pub pins: pins::Config,
+    // Global shortcut that toggles the daemon, e.g. "Super+Space". Only
+    // grabbed on X11 when built with the x11-hotkey feature.
+    pub hotkey: Option<String>,
+    // Use a wrapping multi-line box for the query, where Shift+Enter adds a
+    // line to shell commands
+    #[serde(default)]
+    pub multiline_entry: bool,
+    // Quit on the first Escape instead of clearing the entry first
+    #[serde(default)]
+    pub escape_quits: bool,
+    // Chords for the launcher's keys by name, e.g. {"complete": "Ctrl+l"}
+    #[serde(default)]
+    pub keys: HashMap<String, String>,
+    // The keys with the defaults filled in, parsed at load
+    #[serde(skip_deserializing)]
+    pub keymap: Vec<keys::Binding>,
+    // Shortcut path of the submenu used as the root of the menu (--menu)
+    #[serde(skip_deserializing)]
+    pub root: Option<String>,
+    // Why the real config couldn't be used, when running the fallback
+    #[serde(skip_deserializing)]
+    pub error: Option<String>,
+}
+
+// A configured feature's tools, when it needs any.
+fn feature<'a, T: Tools>(name: &'a str, config: &'a Option<T>)
+    -> Option<(&'a str, Vec<&'a str>)> {
+    let tools = config.as_ref()?.tools();
+
+    if tools.is_empty() {
+        return None;
+    }
+
+    Some((name, tools))
+}
+
+impl Config {
+    // The tools of every configured feature, by feature name.
+    pub fn tools(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut tools: Vec<(&str, Vec<&str>)> = vec![
+            feature("power", &self.power),
+            feature("wallpaper", &self.wallpapers),
+            feature("update", &self.update),
+            feature("windows", &self.windows),
+            feature("files", &self.files),
+            feature("units", &self.units),
+            feature("bookmarks", &self.bookmarks),
+            feature("pass", &self.pass),
+            feature("wifi", &self.wifi),
+            feature("audio", &self.audio),
+            feature("bluetooth", &self.bluetooth),
+            feature("tmux", &self.tmux),
+            feature("repos", &self.repos),
+            feature("steam", &self.steam),
+            feature("vms", &self.vms),
+            feature("containers", &self.containers),
+            feature("drives", &self.drives),
+            feature("displays", &self.displays),
+            feature("keyboard", &self.keyboard),
+            feature("convert", &self.convert),
+            feature("dictionary", &self.dictionary),
+            feature("translate", &self.translate),
+            feature("man", &self.man),
+            feature("kube", &self.kube),
+            feature("vpn", &self.vpn),
+            feature("qr", &self.qr),
+            feature("timers", &self.timers),
+            feature("weather", &self.weather),
+            feature("notes", &self.notes),
+            feature("snippets", &self.snippets),
+            feature("emoji", &self.emoji),
+            feature("search", &self.search),
+        ].into_iter().flatten().collect();
+
+        // Besides pass itself, passwords are typed or copied by another tool
+        if let Some(ref p) = self.pass {
+            tools.push(("pass", vec![if p.type_it {
+                desktop::typing_tool()
+            } else {
+                desktop::clipboard_tool()
+            }]));
+        }
+
+        // Suggestions are fetched on top of opening the search
+        if self.search.as_ref().is_some_and(|s| s.suggest.is_some()) {
+            tools.push(("suggest", vec!["curl"]));
+        }
+
+        tools
+    }
+}
+
+// Reads and parses the config, then drops nodes that don't apply to this
+// machine and fills in generated submenus.
+pub fn load(path: &str) -> Result<Config, String> {
+    let file = File::open(path).
+        map_err(|e| format!("Can't open {}: {}", path, e))?;
+
+    let mut config: Config = serde_json::from_reader(file).
+        map_err(|e| format!("Can't parse {}: {}", path, e))?;
+
+    config.keymap = keys::keymap(&config.keys).
+        map_err(|e| format!("Can't parse {}: {}", path, e))?;
+
+    let host = node::hostname();
+    config.menu = node::prune_nodes(config.menu,
+                                    &|n| node::node_available(n, &host));
+
+    if let Some(ref w) = config.wallpapers {
+        node::fill_builtin_children(&mut config.menu, desktop::WALLPAPER,
+                                    &desktop::wallpaper_nodes(w));
+    }
+
+    node::fill_builtin_children(&mut config.menu, screenshot::SCREENSHOT,
+                                &screenshot::nodes(&config.screenshot));
+
+    Ok(config)
+}
+
+// Safe mode: a minimal menu to fix a config that doesn't load, so a typo
+// never locks anyone out of their launcher.
+pub fn fallback(path: &str, error: &str) -> Config {
+    let node = |shortcut: &str, description: &str, command: &str| Node {
+        shortcut: shortcut.to_string(),
+        description: description.to_string(),
+        command: Some(command.to_string()),
+        shell: true,
+        ..Default::default()
+    };
+
+    Config {
+        shell_prefix: "!".to_string(),
+        menu: vec![
+            node("e", &format!("edit {}", path), &format!("xdg-open {}",
+                 desktop::shell_quote(path))),
+            node("t", "terminal", "x-terminal-emulator || xterm"),
+            node("q", "quit", "true"),
+        ],
+        keymap: keys::keymap(&HashMap::new()).unwrap_or_default(),
+        error: Some(error.to_string()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fallback, load};
+
+    #[test]
+    fn it_falls_back_when_config_is_broken() {
+        let error = match load("/nonexistent/blaunch.json") {
+            Ok (_) => panic!("Loaded a missing config"),
+            Err(e) => e,
+        };
+
+        let config = fallback("/nonexistent/blaunch.json", &error);
+
+        assert_eq!(config.menu.len(), 3);
+        assert_eq!(config.menu[0].command,
+                   Some("xdg-open '/nonexistent/blaunch.json'".to_string()));
+        assert!(!config.keymap.is_empty());
+        assert!(config.error.unwrap().starts_with("Can't open"));
+    }
+}
@@ -0,0 +1,178 @@
+// Docker or Podman containers and images, read from the CLI's JSON output.
+
+use serde_json::Value;
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+use node::on_path;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // "docker" or "podman"; whichever is installed when not set
+    pub engine: Option<String>,
+    #[serde(default = "default_terminal")]
+    pub terminal: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        match self.engine {
+            Some(ref e) => vec![e.as_str()],
+            None        => vec!["docker", "podman"],
+        }
+    }
+}
+
+fn default_prefix() -> String {
+    "dk".to_string()
+}
+
+fn default_terminal() -> String {
+    "xterm".to_string()
+}
+
+pub fn engine(config: &Config) -> String {
+    match config.engine {
+        Some(ref e) => e.clone(),
+        None if on_path("docker") => "docker".to_string(),
+        None => "podman".to_string(),
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Container {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    // "running", "exited" and so on
+    pub state: String,
+    // Like "Up 2 hours"
+    pub status: String,
+}
+
+impl Container {
+    pub fn running(&self) -> bool {
+        self.state == "running"
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Image {
+    pub id: String,
+    pub name: String,
+}
+
+// Docker and Podman name a few fields differently and Podman gives lists
+// where Docker gives strings, which cli::field smooths over.
+fn container(value: &Value) -> Option<Container> {
+    Some(Container {
+        id: cli::field(value, &["ID", "Id"])?,
+        name: cli::field(value, &["Names", "Name"])?,
+        image: cli::field(value, &["Image"]).unwrap_or_default(),
+        state: cli::field(value, &["State"]).unwrap_or_default().
+            to_lowercase(),
+        status: cli::field(value, &["Status"]).unwrap_or_default(),
+    })
+}
+
+fn image(value: &Value) -> Option<Image> {
+    let id = cli::field(value, &["ID", "Id"])?;
+    let repository = cli::field(value, &["Repository"])?;
+    let tag = cli::field(value, &["Tag"]).unwrap_or_default();
+
+    // Dangling layers have no name to run them by
+    if repository == "<none>" {
+        return None;
+    }
+
+    let name = if tag.is_empty() || tag == "<none>" {
+        repository
+    } else {
+        format!("{}:{}", repository, tag)
+    };
+
+    Some(Image { id, name })
+}
+
+pub fn parse_containers(output: &str) -> Vec<Container> {
+    cli::json_lines(output).iter().filter_map(container).collect()
+}
+
+pub fn parse_images(output: &str) -> Vec<Image> {
+    cli::json_lines(output).iter().filter_map(image).collect()
+}
+
+pub fn containers(engine: &str) -> Result<Vec<Container>, String> {
+    cli::output(engine, &["ps", "--all", "--format", "{{json .}}"]).
+        map(|o| parse_containers(&o))
+}
+
+pub fn images(engine: &str) -> Result<Vec<Image>, String> {
+    cli::output(engine, &["images", "--format", "{{json .}}"]).
+        map(|o| parse_images(&o))
+}
+
+// The command line for start or stop.
+pub fn command(engine: &str, container: &Container, verb: &str) -> String {
+    format!("{} {} {}", engine, verb, shell_quote(&container.name))
+}
+
+// bash where the image has it, sh otherwise
+const SHELL: &str = "command -v bash >/dev/null && exec bash || exec sh";
+
+pub fn shell_command(config: &Config, container: &Container) -> String {
+    cli::in_terminal(&config.terminal, &format!(
+        "{} exec -it {} sh -c {}", engine(config),
+        shell_quote(&container.name), shell_quote(SHELL)))
+}
+
+// A throwaway container from the image with a shell in it.
+pub fn run_command(config: &Config, image: &Image) -> String {
+    cli::in_terminal(&config.terminal, &format!(
+        "{} run --rm -it --entrypoint sh {} -c {}", engine(config),
+        shell_quote(&image.name), shell_quote(SHELL)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Container, Image, parse_containers, parse_images};
+
+    #[test]
+    fn it_parses_docker_and_podman_containers() {
+        let docker = "{\"ID\":\"ab12\",\"Names\":\"web\",\
+                      \"Image\":\"nginx\",\"State\":\"running\",\
+                      \"Status\":\"Up 2 hours\"}";
+        let podman = "{\"Id\":\"cd34\",\"Names\":[\"db\"],\
+                      \"Image\":\"postgres\",\"State\":\"exited\",\
+                      \"Status\":\"Exited (0) 3 days ago\"}";
+
+        let containers = parse_containers(&format!("{}\n{}\n", docker,
+                                                   podman));
+
+        assert_eq!(containers[0], Container {
+            id: "ab12".to_string(),
+            name: "web".to_string(),
+            image: "nginx".to_string(),
+            state: "running".to_string(),
+            status: "Up 2 hours".to_string(),
+        });
+        assert_eq!(containers[1].name, "db");
+        assert!(!containers[1].running());
+    }
+
+    #[test]
+    fn it_parses_images() {
+        let output = "{\"ID\":\"ef56\",\"Repository\":\"nginx\",\
+                      \"Tag\":\"latest\"}\n\
+                      {\"ID\":\"0000\",\"Repository\":\"<none>\",\
+                      \"Tag\":\"<none>\"}\n";
+
+        assert_eq!(parse_images(output), vec![Image {
+            id: "ef56".to_string(),
+            name: "nginx:latest".to_string(),
+        }]);
+    }
+}
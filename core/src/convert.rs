@@ -0,0 +1,387 @@
+// Conversions typed as "<amount> <unit> to <unit>", like "5 mi to km" or
+// "100 usd in eur". Units are converted offline; currencies use rates
+// fetched from a configurable URL and cached on disk.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use serde_json;
+
+use calc;
+use config::Tools;
+use network;
+use paths::expand_home;
+
+const RATES_FILE: &str = "~/.cache/blaunch/rates.json";
+const FETCH_TIMEOUT_SECS: &str = "5";
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    // Returns JSON with a "rates" object keyed by currency code, relative
+    // to "base" or "base_code"
+    #[serde(default = "default_rates_url")]
+    pub rates_url: String,
+    // How old the cached rates can get before they're fetched again
+    #[serde(default = "default_max_age_hours")]
+    pub max_age_hours: u64,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["curl"]
+    }
+}
+
+fn default_rates_url() -> String {
+    "https://open.er-api.com/v6/latest/USD".to_string()
+}
+
+fn default_max_age_hours() -> u64 {
+    12
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Conversion {
+    pub amount: f64,
+    pub from: String,
+    pub to: String,
+}
+
+// "5 mi to km", "5mi in km" and "100 USD to eur".
+pub fn parse(input: &str) -> Option<Conversion> {
+    let input = input.trim().to_lowercase();
+    let (left, to) = input.split_once(" to ").
+        or_else(|| input.split_once(" in "))?;
+
+    let left = left.trim();
+    let split = left.find(|c: char| !c.is_ascii_digit() && c != '.' &&
+                                    c != ',' && c != '-')?;
+    let amount = left[..split].replace(',', "").parse().ok()?;
+    let from = left[split..].trim();
+    let to = to.trim();
+
+    if from.is_empty() || to.is_empty() || to.contains(' ') {
+        return None;
+    }
+
+    Some(Conversion {
+        amount,
+        from: from.to_string(),
+        to: to.to_string(),
+    })
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Time,
+    Data,
+    Speed,
+    Area,
+}
+
+// Names for each unit and its size in the dimension's base unit: meters,
+// grams, liters, seconds, bytes, meters per second and square meters.
+const UNITS: &[(&[&str], Dimension, f64)] = &[
+    (&["mm", "millimeter", "millimeters"], Dimension::Length, 0.001),
+    (&["cm", "centimeter", "centimeters"], Dimension::Length, 0.01),
+    (&["m", "meter", "meters", "metre", "metres"], Dimension::Length, 1.0),
+    (&["km", "kilometer", "kilometers"], Dimension::Length, 1000.0),
+    (&["in", "inch", "inches"], Dimension::Length, 0.0254),
+    (&["ft", "foot", "feet"], Dimension::Length, 0.3048),
+    (&["yd", "yard", "yards"], Dimension::Length, 0.9144),
+    (&["mi", "mile", "miles"], Dimension::Length, 1609.344),
+    (&["nmi"], Dimension::Length, 1852.0),
+    (&["mg", "milligram", "milligrams"], Dimension::Mass, 0.001),
+    (&["g", "gram", "grams"], Dimension::Mass, 1.0),
+    (&["kg", "kilogram", "kilograms"], Dimension::Mass, 1000.0),
+    (&["t", "tonne", "tonnes"], Dimension::Mass, 1_000_000.0),
+    (&["oz", "ounce", "ounces"], Dimension::Mass, 28.349523125),
+    (&["lb", "lbs", "pound", "pounds"], Dimension::Mass, 453.59237),
+    (&["st", "stone"], Dimension::Mass, 6350.29318),
+    (&["ml", "milliliter", "milliliters"], Dimension::Volume, 0.001),
+    (&["l", "liter", "liters", "litre", "litres"], Dimension::Volume, 1.0),
+    (&["tsp", "teaspoon", "teaspoons"], Dimension::Volume, 0.00492892159),
+    (&["tbsp", "tablespoon", "tablespoons"], Dimension::Volume,
+     0.0147867648),
+    (&["floz"], Dimension::Volume, 0.0295735296),
+    (&["cup", "cups"], Dimension::Volume, 0.2365882365),
+    (&["pt", "pint", "pints"], Dimension::Volume, 0.473176473),
+    (&["qt", "quart", "quarts"], Dimension::Volume, 0.946352946),
+    (&["gal", "gallon", "gallons"], Dimension::Volume, 3.785411784),
+    (&["ms", "millisecond", "milliseconds"], Dimension::Time, 0.001),
+    (&["s", "sec", "second", "seconds"], Dimension::Time, 1.0),
+    (&["min", "minute", "minutes"], Dimension::Time, 60.0),
+    (&["h", "hr", "hour", "hours"], Dimension::Time, 3600.0),
+    (&["d", "day", "days"], Dimension::Time, 86400.0),
+    (&["wk", "week", "weeks"], Dimension::Time, 604800.0),
+    (&["yr", "year", "years"], Dimension::Time, 31557600.0),
+    (&["b", "byte", "bytes"], Dimension::Data, 1.0),
+    (&["kb"], Dimension::Data, 1e3),
+    (&["mb"], Dimension::Data, 1e6),
+    (&["gb"], Dimension::Data, 1e9),
+    (&["tb"], Dimension::Data, 1e12),
+    (&["kib"], Dimension::Data, 1024.0),
+    (&["mib"], Dimension::Data, 1048576.0),
+    (&["gib"], Dimension::Data, 1073741824.0),
+    (&["tib"], Dimension::Data, 1099511627776.0),
+    (&["m/s", "mps"], Dimension::Speed, 1.0),
+    (&["km/h", "kmh", "kph"], Dimension::Speed, 1.0 / 3.6),
+    (&["mph"], Dimension::Speed, 0.44704),
+    (&["kn", "knot", "knots"], Dimension::Speed, 0.514444444),
+    (&["m2", "sqm"], Dimension::Area, 1.0),
+    (&["km2"], Dimension::Area, 1e6),
+    (&["ft2", "sqft"], Dimension::Area, 0.09290304),
+    (&["acre", "acres"], Dimension::Area, 4046.8564224),
+    (&["ha", "hectare", "hectares"], Dimension::Area, 10000.0),
+];
+
+fn unit(name: &str) -> Option<(Dimension, f64)> {
+    UNITS.iter().find(|&&(names, _, _)| names.contains(&name)).
+        map(|&(_, dimension, factor)| (dimension, factor))
+}
+
+// Temperatures have offsets, so they go through Kelvin.
+fn to_kelvin(value: f64, unit: &str) -> Option<f64> {
+    match unit {
+        "c" | "celsius"    => Some(value + 273.15),
+        "f" | "fahrenheit" => Some((value - 32.0) * 5.0 / 9.0 + 273.15),
+        "k" | "kelvin"     => Some(value),
+        _                  => None,
+    }
+}
+
+fn from_kelvin(value: f64, unit: &str) -> Option<f64> {
+    match unit {
+        "c" | "celsius"    => Some(value - 273.15),
+        "f" | "fahrenheit" => Some((value - 273.15) * 9.0 / 5.0 + 32.0),
+        "k" | "kelvin"     => Some(value),
+        _                  => None,
+    }
+}
+
+// None when either unit isn't one this knows, so the input can still be a
+// currency conversion or nothing at all.
+pub fn convert_units(c: &Conversion) -> Option<Result<f64, String>> {
+    if let Some(k) = to_kelvin(c.amount, &c.from) {
+        return from_kelvin(k, &c.to).map(Ok);
+    }
+
+    let (from, from_factor) = unit(&c.from)?;
+    let (to, to_factor) = unit(&c.to)?;
+
+    if from != to {
+        return Some(Err(format!("Can't convert {} to {}", c.from, c.to)));
+    }
+
+    Some(Ok(c.amount * from_factor / to_factor))
+}
+
+// Enough digits for the result to be useful without float noise.
+pub fn format(value: f64, places: i32) -> String {
+    let scale = 10f64.powi(places);
+    calc::format((value * scale).round() / scale)
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Rates {
+    // Units of each currency per one of the base currency
+    pub rates: HashMap<String, f64>,
+}
+
+impl Rates {
+    pub fn convert(&self, c: &Conversion) -> Option<f64> {
+        let from = self.rates.get(&c.from.to_uppercase())?;
+        let to = self.rates.get(&c.to.to_uppercase())?;
+
+        Some(c.amount / from * to)
+    }
+}
+
+pub fn parse_rates(body: &str) -> Result<Rates, String> {
+    let value: serde_json::Value = serde_json::from_str(body).
+        map_err(|e| format!("Can't parse exchange rates: {}", e))?;
+
+    let rates = value.get("rates").and_then(|r| r.as_object()).
+        ok_or("No rates in the exchange rate response")?;
+
+    let mut rates: HashMap<String, f64> = rates.iter().
+        filter_map(|(code, rate)| rate.as_f64().map(|r| (code.clone(), r))).
+        collect();
+
+    let base = value.get("base_code").or_else(|| value.get("base")).
+        and_then(|b| b.as_str());
+
+    if let Some(b) = base {
+        rates.insert(b.to_uppercase(), 1.0);
+    }
+
+    Ok(Rates { rates })
+}
+
+fn fetch(url: &str) -> Result<String, String> {
+    let output = Command::new("curl").
+        args(["-s", "-f", "--max-time", FETCH_TIMEOUT_SECS, url]).output().
+        map_err(|e| format!("Can't run curl: {}", e))?;
+
+    if !output.status.success() {
+        return Err("Can't fetch exchange rates".to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn age(path: &Path) -> Option<Duration> {
+    fs::metadata(path).and_then(|m| m.modified()).ok().
+        and_then(|t| SystemTime::now().duration_since(t).ok())
+}
+
+// Exchange rates from the cache file, fetched again in the background once
+// they're older than the configured age. Old rates keep being used until
+// new ones arrive.
+pub struct Exchange {
+    config: Config,
+    rates: Option<Rates>,
+    error: Option<String>,
+    fetching: bool,
+    monitor: network::Monitor,
+    sender: Sender<Result<String, String>>,
+    receiver: Receiver<Result<String, String>>,
+}
+
+impl Exchange {
+    pub fn new(config: Config) -> Exchange {
+        let (sender, receiver) = channel();
+        let rates = fs::read_to_string(expand_home(RATES_FILE)).ok().
+            and_then(|b| parse_rates(&b).ok());
+
+        Exchange {
+            config,
+            rates,
+            error: None,
+            fetching: false,
+            monitor: network::Monitor::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    fn stale(&self) -> bool {
+        let max_age = Duration::from_secs(self.config.max_age_hours * 3600);
+
+        self.rates.is_none() ||
+            age(Path::new(&expand_home(RATES_FILE))).
+                is_none_or(|a| a > max_age)
+    }
+
+    // Nothing is fetched while offline; old rates are still used then.
+    pub fn rates(&mut self) -> Result<Option<&Rates>, String> {
+        let due = self.stale() && !self.fetching && self.error.is_none();
+
+        if due && !self.monitor.is_online() {
+            return match self.rates.as_ref() {
+                Some(r) => Ok(Some(r)),
+                None    => Err(network::offline_description(
+                    "Can't fetch exchange rates")),
+            };
+        }
+
+        if due {
+            self.fetching = true;
+            let url = self.config.rates_url.clone();
+            let sender = self.sender.clone();
+
+            thread::spawn(move || {
+                let _ = sender.send(fetch(&url));
+            });
+        }
+
+        match (self.rates.as_ref(), self.error.as_ref()) {
+            (Some(r), _)    => Ok(Some(r)),
+            (None, Some(e)) => Err(e.clone()),
+            (None, None)    => Ok(None),
+        }
+    }
+
+    // Saves newly fetched rates. A failed fetch isn't retried until the
+    // launcher starts again, so an offline laptop doesn't keep trying.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+
+        for body in self.receiver.try_iter() {
+            self.fetching = false;
+            changed = true;
+
+            match body.and_then(|b| parse_rates(&b).map(|r| (b, r))) {
+                Ok ((body, rates)) => {
+                    let path = expand_home(RATES_FILE);
+
+                    if let Some(dir) = Path::new(&path).parent() {
+                        let _ = fs::create_dir_all(dir);
+                    }
+
+                    let _ = fs::write(&path, body);
+                    self.rates = Some(rates);
+                },
+                Err(e) => self.error = Some(e),
+            };
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Conversion, convert_units, format, parse, parse_rates};
+
+    fn conversion(amount: f64, from: &str, to: &str) -> Conversion {
+        Conversion {
+            amount,
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    #[test]
+    fn it_parses_conversions() {
+        assert_eq!(parse("5 mi to km"), Some(conversion(5.0, "mi", "km")));
+        assert_eq!(parse("1,000USD in eur"),
+                   Some(conversion(1000.0, "usd", "eur")));
+        assert_eq!(parse("go to bed"), None);
+        assert_eq!(parse("5 mi to"), None);
+    }
+
+    #[test]
+    fn it_converts_units() {
+        let km = convert_units(&conversion(5.0, "mi", "km")).unwrap();
+        assert_eq!(format(km.unwrap(), 4), "8.0467");
+
+        let f = convert_units(&conversion(100.0, "c", "f")).unwrap();
+        assert_eq!(format(f.unwrap(), 4), "212");
+
+        assert!(convert_units(&conversion(1.0, "kg", "km")).unwrap().
+            is_err());
+        assert_eq!(convert_units(&conversion(1.0, "usd", "eur")), None);
+    }
+
+    #[test]
+    fn it_converts_currencies() {
+        let rates = parse_rates("{\"base_code\": \"USD\", \
+                                 \"rates\": {\"EUR\": 0.5, \"GBP\": 0.25}}").
+            unwrap();
+
+        assert_eq!(rates.convert(&conversion(10.0, "usd", "eur")),
+                   Some(5.0));
+        assert_eq!(rates.convert(&conversion(10.0, "eur", "gbp")),
+                   Some(5.0));
+        assert_eq!(rates.convert(&conversion(10.0, "eur", "xyz")), None);
+    }
+}
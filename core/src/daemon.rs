@@ -0,0 +1,246 @@
+use std::env;
+use std::fs::{self, DirBuilder};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use processes;
+
+pub const TOGGLE: &str = "toggle";
+
+// Things a running daemon can be asked to do, over the socket or D-Bus. The
+// output names the monitor (e.g. "DP-1") whose window a request is for; each
+// output gets its own window with its own state, and None is the default one.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Request {
+    Toggle { output: Option<String> },
+    // Show the window, optionally with the entry filled in
+    Show { output: Option<String>, query: Option<String> },
+    Hide { output: Option<String> },
+    // Re-read the config file
+    Reload,
+    // Re-apply the theme, switching to another stylesheet if one is given
+    ReloadTheme(Option<String>),
+}
+
+fn split_word(line: &str) -> (&str, Option<&str>) {
+    match line.find(' ') {
+        Some(i) => (&line[..i], Some(&line[i + 1..])),
+        None    => (line, None),
+    }
+}
+
+// Socket commands are one line each: "toggle", "show [query]", "hide",
+// "reload" or "reload-theme [path]". Toggle, show and hide can be aimed at
+// one output with "--output NAME" right after the command, the same as on
+// the command line.
+pub fn parse_request(line: &str) -> Option<Request> {
+    let line = line.trim_end_matches('\n');
+
+    let (command, mut argument) = split_word(line);
+    let mut output = None;
+
+    if let Some(a) = argument.and_then(|a| a.strip_prefix("--output ")) {
+        let (name, rest) = split_word(a.trim_start());
+        output = Some(name.to_string());
+        argument = rest;
+    }
+
+    match command {
+        TOGGLE         => Some(Request::Toggle { output }),
+        "show"         => Some(Request::Show {
+            output,
+            query: argument.map(|a| a.to_string()),
+        }),
+        "hide"         => Some(Request::Hide { output }),
+        "reload"       => Some(Request::Reload),
+        "reload-theme" => Some(Request::ReloadTheme(
+            argument.map(|a| a.to_string()))),
+        _              => None,
+    }
+}
+
+// In the runtime dir, which only we can use. Without one it goes in a
+// directory of our own under the temp dir, named for our uid, since a
+// shared path there could be taken by another user first.
+pub fn socket_path() -> PathBuf {
+    let dir = match env::var_os("XDG_RUNTIME_DIR") {
+        Some(d) if !d.is_empty() => PathBuf::from(d),
+        _                        => env::temp_dir().join(format!(
+            "blaunch-{}", processes::current_uid().unwrap_or(0))),
+    };
+
+    dir.join("blaunch.sock")
+}
+
+// The socket's directory has to be ours and closed to everyone else, or
+// someone else's socket could be answering in place of the daemon.
+pub fn check_dir(dir: &Path) -> io::Result<()> {
+    let meta = fs::symlink_metadata(dir)?;
+
+    if !meta.is_dir() || Some(meta.uid()) != processes::current_uid() ||
+       meta.mode() & 0o077 != 0 {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied,
+            format!("{} should be a directory only we can use (mode 700)",
+                    dir.display())));
+    }
+
+    Ok(())
+}
+
+fn check_parent(path: &Path) -> io::Result<()> {
+    check_dir(path.parent().unwrap_or(Path::new("/")))
+}
+
+// Whether a daemon is listening, for features that only work while one
+// runs.
+pub fn running() -> bool {
+    let path = socket_path();
+    check_parent(&path).is_ok() && UnixStream::connect(path).is_ok()
+}
+
+// Sends one command to a running daemon.
+pub fn send(path: &Path, command: &str) -> io::Result<()> {
+    check_parent(path)?;
+    let mut stream = UnixStream::connect(path)?;
+    writeln!(stream, "{}", command)
+}
+
+// The daemon's end of the socket. It never blocks: the frontend polls it
+// from a timer alongside everything else.
+pub struct Listener {
+    path: PathBuf,
+    listener: UnixListener,
+}
+
+impl Listener {
+    pub fn bind(path: &Path) -> io::Result<Listener> {
+        if let Some(dir) = path.parent().filter(|d| !d.exists()) {
+            DirBuilder::new().mode(0o700).create(dir)?;
+        }
+
+        check_parent(path)?;
+
+        // A socket left behind by a daemon that died would make bind fail.
+        // If nothing answers on it, it's safe to replace.
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(io::ErrorKind::AddrInUse,
+                    format!("A daemon is already listening on {}",
+                            path.display())));
+            }
+
+            fs::remove_file(path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Listener {
+            path: path.to_path_buf(),
+            listener,
+        })
+    }
+
+    // Requests received since the last poll, one per connection. Lines that
+    // aren't a known command are dropped.
+    pub fn poll(&self) -> Vec<Request> {
+        let mut ret = vec![];
+
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(false).is_err() ||
+               stream.set_read_timeout(Some(Duration::from_millis(100))).
+                   is_err() {
+                continue;
+            }
+
+            let mut line = String::new();
+            if BufReader::new(stream).read_line(&mut line).is_err() {
+                continue;
+            }
+
+            if let Some(r) = parse_request(&line) {
+                ret.push(r);
+            }
+        }
+
+        ret
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process;
+    use super::{Listener, Request, TOGGLE, check_dir, parse_request, send};
+
+    #[test]
+    fn it_parses_requests() {
+        assert_eq!(parse_request("toggle\n"),
+                   Some(Request::Toggle { output: None }));
+        assert_eq!(parse_request("show"),
+                   Some(Request::Show { output: None, query: None }));
+        assert_eq!(parse_request("show web  search"), Some(Request::Show {
+            output: None,
+            query: Some("web  search".to_string()),
+        }));
+        assert_eq!(parse_request("show --output DP-1 web"), Some(Request::Show {
+            output: Some("DP-1".to_string()),
+            query: Some("web".to_string()),
+        }));
+        assert_eq!(parse_request("hide --output HDMI-2"),
+                   Some(Request::Hide { output: Some("HDMI-2".to_string()) }));
+        assert_eq!(parse_request("reload"), Some(Request::Reload));
+        assert_eq!(parse_request("reload-theme ~/dark.css"),
+                   Some(Request::ReloadTheme(Some("~/dark.css".to_string()))));
+        assert_eq!(parse_request("explode"), None);
+    }
+
+    #[test]
+    fn it_receives_commands() {
+        let dir = env::temp_dir().
+            join(format!("blaunch-daemon-test.{}", process::id()));
+        let path = dir.join("blaunch.sock");
+
+        let listener = Listener::bind(&path).unwrap();
+        assert!(listener.poll().is_empty());
+
+        send(&path, TOGGLE).unwrap();
+        assert_eq!(listener.poll(),
+                   vec![Request::Toggle { output: None }]);
+
+        // A second daemon can't take over a live socket
+        assert!(Listener::bind(&path).is_err());
+
+        drop(listener);
+        assert!(!path.exists());
+
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_refuses_shared_socket_dirs() {
+        let dir = env::temp_dir().
+            join(format!("blaunch-daemon-shared.{}", process::id()));
+        fs::create_dir(&dir).unwrap();
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(check_dir(&dir).is_err());
+        assert!(send(&dir.join("blaunch.sock"), TOGGLE).is_err());
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700)).unwrap();
+        assert!(check_dir(&dir).is_ok());
+
+        fs::remove_dir(&dir).unwrap();
+    }
+}
@@ -0,0 +1,289 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use config::{Tools, program};
+use node::Node;
+use paths::expand_home;
+
+pub const NIGHT_LIGHT: &str = "nightlight";
+pub const WALLPAPER: &str = "wallpaper";
+
+// Tried in order when turning night light on.
+const NIGHT_LIGHT_TOOLS: [&str; 2] = ["gammastep", "redshift"];
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "webp", "bmp"];
+
+#[derive(Deserialize, Clone)]
+pub struct WallpaperConfig {
+    pub directory: String,
+    // Lists the wallpapers after this prefix too, e.g. "wall beach"
+    #[serde(default = "default_wallpaper_prefix")]
+    pub prefix: String,
+    // Sets the wallpaper instead of the desktop's own way, where {path} is
+    // the image, e.g. "feh --bg-fill {path}"
+    pub command: Option<String>,
+    // Show a thumbnail of each wallpaper
+    #[serde(default = "default_true")]
+    pub previews: bool,
+}
+
+impl Tools for WallpaperConfig {
+    fn tools(&self) -> Vec<&str> {
+        match (self.command.as_ref(), detect_environment()) {
+            (Some(c), _)               => vec![program(c)],
+            (None, Environment::Sway)  => vec!["swaymsg"],
+            (None, Environment::Gnome) => vec!["gsettings"],
+            (None, Environment::Other) => vec!["feh"],
+        }
+    }
+}
+
+fn default_wallpaper_prefix() -> String {
+    "wall".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Environment {
+    Sway,
+    Gnome,
+    Other,
+}
+
+fn running(process: &str) -> bool {
+    Command::new("pgrep").arg("-x").arg(process).stdout(Stdio::null()).
+        status().map(|s| s.success()).unwrap_or(false)
+}
+
+pub fn night_light_active() -> bool {
+    NIGHT_LIGHT_TOOLS.iter().any(|t| running(t))
+}
+
+// Killing gammastep/redshift restores normal gamma, so toggling off is just
+// stopping whichever one is running.
+pub fn toggle_night_light() -> io::Result<()> {
+    let active: Vec<&&str> = NIGHT_LIGHT_TOOLS.iter().
+        filter(|t| running(t)).collect();
+
+    if !active.is_empty() {
+        for tool in active {
+            Command::new("pkill").arg("-x").arg(tool).status()?;
+        }
+
+        return Ok(());
+    }
+
+    for tool in NIGHT_LIGHT_TOOLS.iter() {
+        match Command::new(tool).stdout(Stdio::null()).
+            stderr(Stdio::null()).spawn() {
+            Ok (_) => return Ok(()),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+    }
+
+    Err(io::Error::new(io::ErrorKind::NotFound,
+                       "Neither gammastep nor redshift is installed"))
+}
+
+pub fn detect_environment() -> Environment {
+    if env::var_os("SWAYSOCK").is_some() {
+        return Environment::Sway;
+    }
+
+    let desktop = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    if desktop.split(':').any(|d| d == "GNOME") {
+        return Environment::Gnome;
+    }
+
+    Environment::Other
+}
+
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+// The command that types its standard input, or the given text, into the
+// focused window: wtype on Wayland and xdotool on X11.
+fn typist(stdin: bool) -> &'static str {
+    match (env::var_os("WAYLAND_DISPLAY").is_some(), stdin) {
+        (true, true)   => "wtype -",
+        (true, false)  => "wtype --",
+        (false, true)  => "xdotool type --clearmodifiers --file -",
+        (false, false) => "xdotool type --clearmodifiers --",
+    }
+}
+
+// The program typist uses in this session, for the doctor to look for.
+pub fn typing_tool() -> &'static str {
+    program(typist(false))
+}
+
+// What pass and the like copy with in this session.
+pub fn clipboard_tool() -> &'static str {
+    if env::var_os("WAYLAND_DISPLAY").is_some() { "wl-copy" } else { "xclip" }
+}
+
+// Types text into the window that had focus. It waits a moment so focus
+// has gone back from the launcher first.
+pub fn type_command(text: &str) -> String {
+    format!("sleep 0.2; {} {}", typist(false), shell_quote(text))
+}
+
+// Types what a command prints, so it never shows up in a command line.
+pub fn type_output_command(command: &str) -> String {
+    format!("sleep 0.2; {} | {}", command, typist(true))
+}
+
+pub fn wallpaper_command(environment: Environment, path: &Path) -> String {
+    let path = path.to_string_lossy();
+
+    match environment {
+        Environment::Sway  =>
+            format!("swaymsg output '*' bg {} fill", shell_quote(&path)),
+        Environment::Gnome =>
+            format!("gsettings set org.gnome.desktop.background \
+                     picture-uri {0} && \
+                     gsettings set org.gnome.desktop.background \
+                     picture-uri-dark {0}",
+                    shell_quote(&format!("file://{}", path))),
+        Environment::Other =>
+            format!("feh --bg-fill {}", shell_quote(&path)),
+    }
+}
+
+fn is_image(path: &Path) -> bool {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(e) => IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()),
+        None    => false,
+    }
+}
+
+pub fn wallpapers(directory: &str) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(expand_home(directory)) {
+        Ok (e) => e,
+        Err(_) => return vec![],
+    };
+
+    let mut ret: Vec<PathBuf> = entries.filter_map(|e| e.ok()).
+        map(|e| e.path()).filter(|p| is_image(p)).collect();
+
+    ret.sort();
+    ret
+}
+
+// The configured setter with the path filled in, or the desktop's own.
+pub fn set_wallpaper_command(config: &WallpaperConfig,
+                             environment: Environment, path: &Path)
+    -> String {
+    match config.command {
+        Some(ref c) => c.replace("{path}",
+                                 &shell_quote(&path.to_string_lossy())),
+        None        => wallpaper_command(environment, path),
+    }
+}
+
+pub fn wallpaper_name(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().into_owned()).
+        unwrap_or_default()
+}
+
+// Shortcuts that are another's prefix (or the same) could never be typed
+// on their own, so those get the wallpaper's position in the list: in
+// "beach", "sunset", "sunset2" the second becomes "sunset.2". Numbers have
+// the same width, so numbered shortcuts can't be each other's prefix.
+pub fn unique_shortcuts(mut shortcuts: Vec<String>) -> Vec<String> {
+    let width = shortcuts.len().to_string().len();
+
+    loop {
+        let clashing: Vec<usize> = (0..shortcuts.len()).filter(|&i| {
+            shortcuts.iter().enumerate().
+                any(|(j, s)| j != i && s.starts_with(&shortcuts[i]))
+        }).collect();
+
+        if clashing.is_empty() {
+            return shortcuts;
+        }
+
+        for i in clashing {
+            shortcuts[i] = format!("{}.{:02$}", shortcuts[i], i + 1, width);
+        }
+    }
+}
+
+pub fn wallpaper_nodes(config: &WallpaperConfig) -> Vec<Node> {
+    let environment = detect_environment();
+    let paths = wallpapers(&config.directory);
+
+    let shortcuts = unique_shortcuts(paths.iter().
+        map(|p| wallpaper_name(p).to_lowercase().replace(' ', "")).collect());
+
+    paths.iter().zip(shortcuts).map(|(path, shortcut)| {
+        let name = wallpaper_name(path);
+
+        Node {
+            shortcut,
+            description: name,
+            command: Some(set_wallpaper_command(config, environment, path)),
+            shell: true,
+            preview: if config.previews {
+                Some(path.to_string_lossy().into_owned())
+            } else {
+                None
+            },
+            ..Default::default()
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::{Environment, WallpaperConfig, set_wallpaper_command,
+                unique_shortcuts, wallpaper_command};
+
+    #[test]
+    fn it_builds_wallpaper_commands() {
+        let path = Path::new("/home/me/walls/it's.png");
+
+        assert_eq!(wallpaper_command(Environment::Other, path),
+                   "feh --bg-fill '/home/me/walls/it'\\''s.png'");
+        assert_eq!(wallpaper_command(Environment::Sway, path),
+                   "swaymsg output '*' bg '/home/me/walls/it'\\''s.png' fill");
+
+        let config = WallpaperConfig {
+            directory: "~/walls".to_string(),
+            prefix: "wall".to_string(),
+            command: Some("swww img {path}".to_string()),
+            previews: true,
+        };
+
+        assert_eq!(set_wallpaper_command(&config, Environment::Sway, path),
+                   "swww img '/home/me/walls/it'\\''s.png'");
+    }
+
+    #[test]
+    fn it_keeps_wallpaper_shortcuts_reachable() {
+        let shortcuts = |s: &[&str]| unique_shortcuts(
+            s.iter().map(|s| s.to_string()).collect());
+
+        assert_eq!(shortcuts(&["beach", "sunset", "sunset2"]),
+                   vec!["beach", "sunset.2", "sunset2"]);
+        assert_eq!(shortcuts(&["sunset", "sunset"]),
+                   vec!["sunset.1", "sunset.2"]);
+
+        let many: Vec<&str> = vec!["a"; 12];
+        let numbered = shortcuts(&many);
+        assert_eq!(numbered[0], "a.01");
+        assert!(numbered.iter().enumerate().all(|(i, a)| {
+            numbered.iter().enumerate().all(|(j, b)| i == j ||
+                                                     !b.starts_with(a.as_str()))
+        }));
+    }
+}
@@ -0,0 +1,197 @@
+// Word definitions from a dict server through the dict client, or from
+// StarDict dictionaries through sdcv.
+
+use std::process::Command;
+
+use config::{Tools, program};
+use desktop::shell_quote;
+use node::on_path;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // dict server to ask, e.g. "dict.org"; dict's own default when not set
+    pub server: Option<String>,
+    // Prints definitions for {query} instead of dict or sdcv, in dict's
+    // output format
+    pub command: Option<String>,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        match self.command {
+            Some(ref c) => vec![program(c)],
+            None        => vec!["sdcv", "dict"],
+        }
+    }
+}
+
+fn default_prefix() -> String {
+    "def".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Definition {
+    pub word: String,
+    // The dictionary it came from, like "WordNet (r) 3.0 (2006)"
+    pub source: String,
+    pub text: String,
+}
+
+// Drops the common indentation, keeping the relative indentation of
+// numbered senses and examples.
+fn dedent(lines: &[&str]) -> String {
+    let indent = lines.iter().filter(|l| !l.trim().is_empty()).
+        map(|l| l.len() - l.trim_start().len()).min().unwrap_or(0);
+
+    lines.iter().map(|l| l.get(indent..).unwrap_or("").trim_end()).
+        collect::<Vec<&str>>().join("\n").trim().to_string()
+}
+
+// dict's output: a count line, then a "From <database>:" header before
+// each definition.
+pub fn parse_dict(word: &str, output: &str) -> Vec<Definition> {
+    let mut definitions = vec![];
+    let mut source: Option<String> = None;
+    let mut lines: Vec<&str> = vec![];
+
+    for line in output.lines().chain(std::iter::once("From ")) {
+        if let Some(header) = line.strip_prefix("From ") {
+            if let Some(s) = source.take() {
+                definitions.push(Definition {
+                    word: word.to_string(),
+                    source: s,
+                    text: dedent(&lines),
+                });
+            }
+
+            source = Some(header.trim_end_matches(':').to_string());
+            lines.clear();
+        } else if source.is_some() {
+            lines.push(line);
+        }
+    }
+
+    definitions.retain(|d| !d.text.is_empty());
+    definitions
+}
+
+// sdcv's non-interactive output: "-->" lines with the dictionary and the
+// word before each definition.
+pub fn parse_sdcv(output: &str) -> Vec<Definition> {
+    let mut definitions = vec![];
+    let mut lines = output.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let source = match line.strip_prefix("-->") {
+            Some(s) => s.trim().to_string(),
+            None    => continue,
+        };
+
+        let word = match lines.next().and_then(|l| l.strip_prefix("-->")) {
+            Some(w) => w.trim().to_string(),
+            None    => continue,
+        };
+
+        let mut text = vec![];
+
+        while lines.peek().is_some_and(|l| !l.starts_with("-->")) {
+            text.extend(lines.next());
+        }
+
+        definitions.push(Definition {
+            word,
+            source,
+            text: dedent(&text),
+        });
+    }
+
+    definitions
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    // dict exits non-zero when nothing matches, which isn't an error here
+    Command::new(program).args(args).output().
+        map(|o| String::from_utf8_lossy(&o.stdout).into_owned()).
+        map_err(|e| format!("Can't run {}: {}", program, e))
+}
+
+// Whether lookups go to a dict server rather than local dictionaries or a
+// configured command.
+pub fn remote(config: &Config) -> bool {
+    config.command.is_none() && (config.server.is_some() || !on_path("sdcv"))
+}
+
+pub fn define(config: &Config, word: &str) -> Result<Vec<Definition>, String> {
+    if let Some(ref c) = config.command {
+        let command = c.replace("{query}", &shell_quote(word));
+        return run("sh", &["-c", &command]).map(|o| parse_dict(word, &o));
+    }
+
+    if !remote(config) {
+        return run("sdcv", &["-n", "--utf8-output", word]).
+            map(|o| parse_sdcv(&o));
+    }
+
+    let mut args = vec![];
+
+    if let Some(ref s) = config.server {
+        args.extend(["-h", s.as_str()]);
+    }
+
+    args.push(word);
+    run("dict", &args).map(|o| parse_dict(word, &o))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Definition, parse_dict, parse_sdcv};
+
+    #[test]
+    fn it_parses_dict_output() {
+        let output = concat!(
+            "2 definitions found\n",
+            "\n",
+            "From WordNet (r) 3.0 (2006) [wn]:\n",
+            "\n",
+            "  launch\n",
+            "      n 1: a motorboat\n",
+            "      v 1: set up or found\n",
+            "\n",
+            "From The Free On-line Dictionary of Computing [foldoc]:\n",
+            "\n",
+            "  launch\n");
+
+        assert_eq!(parse_dict("launch", output), vec![
+            Definition {
+                word: "launch".to_string(),
+                source: "WordNet (r) 3.0 (2006) [wn]".to_string(),
+                text: "launch\n    n 1: a motorboat\n    v 1: set up or found".
+                    to_string(),
+            },
+            Definition {
+                word: "launch".to_string(),
+                source: "The Free On-line Dictionary of Computing [foldoc]".
+                    to_string(),
+                text: "launch".to_string(),
+            },
+        ]);
+    }
+
+    #[test]
+    fn it_parses_sdcv_output() {
+        let output = "Found 1 items, similar to launch.\n\
+                      -->Collins\n\
+                      -->launch\n\
+                      \n\
+                      to put a boat into the water\n\
+                      \n";
+
+        assert_eq!(parse_sdcv(output), vec![Definition {
+            word: "launch".to_string(),
+            source: "Collins".to_string(),
+            text: "to put a boat into the water".to_string(),
+        }]);
+    }
+}
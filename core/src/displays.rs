@@ -0,0 +1,107 @@
+// Saved display layouts: autorandr profiles and arandr's scripts in
+// ~/.screenlayout.
+
+use std::fs;
+use std::path::Path;
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+use node::on_path;
+use paths::expand_home;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_scripts")]
+    pub scripts: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["autorandr", "xrandr"]
+    }
+}
+
+fn default_prefix() -> String {
+    "disp".to_string()
+}
+
+fn default_scripts() -> String {
+    "~/.screenlayout".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Layout {
+    pub name: String,
+    pub command: String,
+    // autorandr recognizes the connected screens as this profile
+    pub detected: bool,
+    pub current: bool,
+}
+
+// Lines of bare `autorandr`, like "docked (detected) (current)".
+pub fn parse_autorandr(output: &str) -> Vec<Layout> {
+    output.lines().filter_map(|line| {
+        let mut words = line.split_whitespace();
+        let name = words.next()?;
+        let tags: Vec<&str> = words.collect();
+
+        Some(Layout {
+            name: name.to_string(),
+            command: format!("autorandr --load {}", shell_quote(name)),
+            detected: tags.contains(&"(detected)"),
+            current: tags.contains(&"(current)"),
+        })
+    }).collect()
+}
+
+pub fn scripts(dir: &Path) -> Vec<Layout> {
+    let entries = match fs::read_dir(dir) {
+        Ok (e) => e,
+        Err(_) => return vec![],
+    };
+
+    let mut layouts: Vec<Layout> = entries.filter_map(|e| e.ok()).
+        map(|e| e.path()).
+        filter(|p| p.extension().is_some_and(|e| e == "sh")).
+        filter_map(|p| Some(Layout {
+            name: p.file_stem()?.to_string_lossy().into_owned(),
+            command: format!("sh {}", shell_quote(&p.to_string_lossy())),
+            detected: false,
+            current: false,
+        })).collect();
+
+    layouts.sort_by(|a, b| a.name.cmp(&b.name));
+    layouts
+}
+
+pub fn layouts(config: &Config) -> Vec<Layout> {
+    let mut layouts = if on_path("autorandr") {
+        cli::output("autorandr", &[]).map(|o| parse_autorandr(&o)).
+            unwrap_or_default()
+    } else {
+        vec![]
+    };
+
+    layouts.extend(scripts(Path::new(&expand_home(&config.scripts))));
+    layouts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_autorandr;
+
+    #[test]
+    fn it_parses_autorandr_profiles() {
+        let layouts = parse_autorandr("docked (detected) (current)\n\
+                                       mobile\n\
+                                       projector (detected)\n");
+
+        assert_eq!(layouts.len(), 3);
+        assert!(layouts[0].detected && layouts[0].current);
+        assert!(!layouts[1].detected && !layouts[1].current);
+        assert_eq!(layouts[2].command, "autorandr --load 'projector'");
+    }
+}
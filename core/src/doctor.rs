@@ -0,0 +1,347 @@
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use config::{self, Config};
+use daemon;
+use desktop;
+use node::{self, Node};
+use paths;
+use screenshot;
+use session;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Status {
+    Ok,
+    Warning,
+    Failure,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl Check {
+    pub fn new(name: &str, status: Status, detail: &str) -> Check {
+        Check {
+            name: name.to_string(),
+            status,
+            detail: detail.to_string(),
+        }
+    }
+}
+
+// Looks over the environment blaunch runs in and explains anything that
+// would make a feature misbehave. The GTK check lives in the frontend.
+pub fn run(config_path: &str) -> Vec<Check> {
+    let mut checks = vec![
+        display_check(env::var("WAYLAND_DISPLAY").ok(),
+                      env::var("DISPLAY").ok()),
+        icon_theme_check(),
+        runtime_dir_check(env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from)),
+        socket_check(&daemon::socket_path()),
+    ];
+
+    match config::load(config_path) {
+        Ok (c) => {
+            checks.push(Check::new("config", Status::Ok,
+                &format!("{} loaded, {} top-level entries", config_path,
+                         c.menu.len())));
+            checks.extend(tool_checks(&c, &node::on_path));
+        },
+        Err(e) => checks.push(Check::new("config", Status::Failure, &e)),
+    };
+
+    checks
+}
+
+pub fn failed(checks: &[Check]) -> bool {
+    checks.iter().any(|c| c.status == Status::Failure)
+}
+
+pub fn report(checks: &[Check]) -> String {
+    checks.iter().map(|c| {
+        let label = match c.status {
+            Status::Ok      => "ok",
+            Status::Warning => "warn",
+            Status::Failure => "FAIL",
+        };
+
+        format!("[{:4}] {}: {}\n", label, c.name, c.detail)
+    }).collect()
+}
+
+pub fn display_check(wayland: Option<String>, x11: Option<String>) -> Check {
+    match (wayland, x11) {
+        (Some(w), _) => Check::new("display", Status::Ok, &format!(
+            "Wayland ({}); window placement and global hotkeys are up to \
+             the compositor", w)),
+        (None, Some(x)) => Check::new("display", Status::Ok,
+                                      &format!("X11 ({})", x)),
+        (None, None) => Check::new("display", Status::Failure,
+            "Neither WAYLAND_DISPLAY nor DISPLAY is set; run blaunch from \
+             a graphical session"),
+    }
+}
+
+fn icon_theme_check() -> Check {
+    // hicolor is only the fallback theme, so it doesn't count
+    let found = paths::data_dirs().iter().
+        filter_map(|d| fs::read_dir(d.join("icons")).ok()).
+        flat_map(|entries| entries.filter_map(|e| e.ok())).
+        any(|e| e.file_name() != "hicolor" && e.path().is_dir());
+
+    if found {
+        Check::new("icons", Status::Ok, "Icon theme installed")
+    } else {
+        Check::new("icons", Status::Warning,
+                   "No icon theme found; install one like adwaita-icon-theme")
+    }
+}
+
+pub fn runtime_dir_check(dir: Option<PathBuf>) -> Check {
+    let dir = match dir {
+        Some(d) => d,
+        None    => return Check::new("runtime dir", Status::Failure,
+            "XDG_RUNTIME_DIR is not set; caffeine can't track its inhibitor"),
+    };
+
+    let probe = dir.join(format!("blaunch-doctor.{}", std::process::id()));
+
+    match File::create(&probe) {
+        Ok (_) => {
+            let _ = fs::remove_file(&probe);
+            Check::new("runtime dir", Status::Ok,
+                       &format!("{} is writable", dir.display()))
+        },
+        Err(e) => Check::new("runtime dir", Status::Failure,
+            &format!("Can't write to {}: {}", dir.display(), e)),
+    }
+}
+
+// The daemon's socket directory must be private, or another user could
+// answer in its place. The daemon creates it when it's missing.
+pub fn socket_check(path: &Path) -> Check {
+    let dir = path.parent().unwrap_or(Path::new("/"));
+
+    if !dir.exists() {
+        return Check::new("socket", Status::Ok, &format!(
+            "{} will be created when the daemon starts", dir.display()));
+    }
+
+    match daemon::check_dir(dir) {
+        Ok (_) => Check::new("socket", Status::Ok,
+                             &format!("{} is private", dir.display())),
+        Err(e) => Check::new("socket", Status::Failure, &e.to_string()),
+    }
+}
+
+fn builtins_used(nodes: &[Node], found: &mut Vec<String>) {
+    for n in nodes {
+        if let Some(ref b) = n.builtin {
+            if !found.contains(b) {
+                found.push(b.clone());
+            }
+        }
+
+        if let Some(ref c) = n.children {
+            builtins_used(c, found);
+        }
+    }
+}
+
+// External tools each used builtin and configured feature shells out to.
+// Any one of the listed tools is enough.
+fn required_tools(config: &Config) -> Vec<(String, Vec<&str>)> {
+    let mut required = vec![];
+
+    let mut builtins = vec![];
+    builtins_used(&config.menu, &mut builtins);
+
+    for b in builtins {
+        let tools = match b.as_str() {
+            session::LOCK |
+            session::LOGOUT      => vec!["gdbus", "loginctl"],
+            session::SUSPEND | session::HIBERNATE | session::REBOOT |
+            session::POWEROFF    => vec!["gdbus", "systemctl"],
+            session::CAFFEINE    => vec!["systemd-inhibit"],
+            desktop::NIGHT_LIGHT => vec!["gammastep", "redshift"],
+            screenshot::SCREENSHOT =>
+                vec![screenshot::tool(&config.screenshot)],
+            _                    => continue,
+        };
+
+        required.push((b, tools));
+    }
+
+    // Quick settings are part of the window rather than a feature with a
+    // config of its own to ask
+    if config.quick_settings.is_some() {
+        required.push(("brightness".to_string(), vec!["brightnessctl"]));
+        required.push(("volume".to_string(), vec!["pactl", "wpctl"]));
+    }
+
+    required.extend(config.tools().into_iter().
+        map(|(feature, tools)| (feature.to_string(), tools)));
+    required
+}
+
+pub fn tool_checks<F>(config: &Config, installed: &F) -> Vec<Check>
+    where F: Fn(&str) -> bool {
+    required_tools(config).into_iter().map(|(feature, tools)| {
+        match tools.iter().find(|t| installed(t)) {
+            Some(t) => Check::new(&feature, Status::Ok,
+                                  &format!("using {}", t)),
+            None    => Check::new(&feature, Status::Warning,
+                &format!("install {}", tools.join(" or "))),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+    use std::process;
+    use std::collections::BTreeMap;
+    use config::Config;
+    use containers;
+    use desktop;
+    use emoji;
+    use kube;
+    use node::Node;
+    use notes;
+    use pass;
+    use search;
+    use snippets;
+    use super::{Status, display_check, runtime_dir_check, socket_check,
+                tool_checks};
+
+    #[test]
+    fn it_prefers_wayland_display() {
+        let check = display_check(Some("wayland-0".to_string()),
+                                  Some(":0".to_string()));
+        assert_eq!(check.status, Status::Ok);
+        assert!(check.detail.starts_with("Wayland"));
+
+        assert_eq!(display_check(None, None).status, Status::Failure);
+    }
+
+    #[test]
+    fn it_fails_without_runtime_dir() {
+        assert_eq!(runtime_dir_check(None).status, Status::Failure);
+        assert_eq!(runtime_dir_check(Some(PathBuf::from("/nonexistent"))).
+                   status, Status::Failure);
+    }
+
+    #[test]
+    fn it_checks_tools_for_used_builtins() {
+        let config = Config {
+            shell_prefix: "!".to_string(),
+            menu: vec![Node {
+                shortcut: "n".to_string(),
+                builtin: Some("nightlight".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let checks = tool_checks(&config, &|t: &str| t == "redshift");
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, Status::Ok);
+        assert_eq!(checks[0].detail, "using redshift");
+
+        let checks = tool_checks(&config, &|_: &str| false);
+        assert_eq!(checks[0].status, Status::Warning);
+        assert_eq!(checks[0].detail, "install gammastep or redshift");
+    }
+
+    #[test]
+    fn it_checks_tools_for_configured_providers() {
+        let config = Config {
+            kube: Some(kube::Config { prefix: "k8s".to_string() }),
+            containers: Some(containers::Config {
+                prefix: "dk".to_string(),
+                engine: None,
+                terminal: "xterm -e".to_string(),
+            }),
+            ..Default::default()
+        };
+
+        let checks = tool_checks(&config, &|t: &str| t == "podman");
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].name, "containers");
+        assert_eq!(checks[0].detail, "using podman");
+        assert_eq!(checks[1].name, "kube");
+        assert_eq!(checks[1].detail, "install kubectl");
+    }
+
+    #[test]
+    fn it_checks_tools_for_typing_copying_and_opening() {
+        let config = Config {
+            notes: Some(notes::Config {
+                file: "~/notes.txt".to_string(),
+                prefix: "note".to_string(),
+                command: Some("jrnl --stdin".to_string()),
+            }),
+            snippets: Some(snippets::Config {
+                prefix: ";".to_string(),
+                type_it: true,
+                snippets: BTreeMap::new(),
+            }),
+            emoji: Some(emoji::Config {
+                prefix: ":".to_string(),
+                type_it: false,
+            }),
+            search: Some(search::Config {
+                suggest: Some("https://example.com/ac?q={query}".to_string()),
+                ..Default::default()
+            }),
+            pass: Some(pass::Config {
+                prefix: "pass".to_string(),
+                type_it: false,
+            }),
+            ..Default::default()
+        };
+
+        let checks = tool_checks(&config, &|_: &str| false);
+        let details: Vec<(&str, &str)> = checks.iter().
+            map(|c| (c.name.as_str(), c.detail.as_str())).collect();
+
+        // Which typing and clipboard tools depends on the session
+        let typing = format!("install {}", desktop::typing_tool());
+        let copying = format!("install {}", desktop::clipboard_tool());
+
+        assert_eq!(details, vec![
+            ("pass", "install pass"),
+            ("notes", "install jrnl"),
+            ("snippets", typing.as_str()),
+            ("search", "install xdg-open"),
+            ("pass", copying.as_str()),
+            ("suggest", "install curl"),
+        ]);
+    }
+
+    #[test]
+    fn it_checks_the_socket_dir() {
+        let dir = env::temp_dir().
+            join(format!("blaunch-doctor-socket.{}", process::id()));
+        let path = dir.join("blaunch.sock");
+
+        assert_eq!(socket_check(&path).status, Status::Ok);
+
+        fs::create_dir(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(socket_check(&path).status, Status::Failure);
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700)).unwrap();
+        assert_eq!(socket_check(&path).status, Status::Ok);
+
+        fs::remove_dir(&dir).unwrap();
+    }
+}
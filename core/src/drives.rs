@@ -0,0 +1,191 @@
+// Removable drives. lsblk lists them; mounting, unmounting and powering off
+// go through udisksctl, so udisks2 handles permissions over D-Bus like a
+// file manager would.
+
+use serde_json::{self, Value};
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // Opens a mountpoint after mounting
+    #[serde(default = "default_open")]
+    pub open: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["udisksctl"]
+    }
+}
+
+fn default_prefix() -> String {
+    "mnt".to_string()
+}
+
+fn default_open() -> String {
+    "xdg-open".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Drive {
+    // The filesystem's block device, like /dev/sdb1
+    pub device: String,
+    // The whole disk it's on, which is what gets powered off
+    pub disk: String,
+    pub label: Option<String>,
+    pub size: String,
+    pub mountpoint: Option<String>,
+}
+
+impl Drive {
+    pub fn name(&self) -> &str {
+        self.label.as_ref().unwrap_or(&self.device)
+    }
+}
+
+// lsblk gives "1" and "0" in older versions and booleans in newer ones.
+fn flag(value: &Value, key: &str) -> bool {
+    cli::field(value, &[key]).is_some_and(|v| v == "1" || v == "true")
+}
+
+fn text(value: &Value, keys: &[&str]) -> Option<String> {
+    cli::field(value, keys).filter(|v| !v.is_empty())
+}
+
+// Filesystems on a disk, or the disk itself when it's formatted whole.
+fn collect(value: &Value, disk: &str, drives: &mut Vec<Drive>) {
+    if let Some(children) = value.get("children").and_then(|c| c.as_array()) {
+        for child in children {
+            collect(child, disk, drives);
+        }
+
+        return;
+    }
+
+    let device = match text(value, &["path"]) {
+        Some(d) => d,
+        None    => return,
+    };
+
+    if text(value, &["fstype"]).is_none() {
+        return;
+    }
+
+    drives.push(Drive {
+        device,
+        disk: disk.to_string(),
+        label: text(value, &["label"]),
+        size: text(value, &["size"]).unwrap_or_default(),
+        // Newer lsblk has a list of mountpoints
+        mountpoint: text(value, &["mountpoint", "mountpoints"]),
+    });
+}
+
+// `lsblk --json --output PATH,LABEL,SIZE,MOUNTPOINT,HOTPLUG,RM,FSTYPE`,
+// keeping disks that are removable or hot-plugged.
+pub fn parse_lsblk(json: &str) -> Result<Vec<Drive>, String> {
+    let doc: Value = serde_json::from_str(json).
+        map_err(|e| format!("Can't parse lsblk output: {}", e))?;
+    let mut drives = vec![];
+
+    let disks = doc.get("blockdevices").and_then(|b| b.as_array()).
+        cloned().unwrap_or_default();
+
+    for disk in disks.iter() {
+        if !flag(disk, "rm") && !flag(disk, "hotplug") {
+            continue;
+        }
+
+        if let Some(path) = text(disk, &["path"]) {
+            collect(disk, &path, &mut drives);
+        }
+    }
+
+    Ok(drives)
+}
+
+pub fn list() -> Result<Vec<Drive>, String> {
+    cli::output("lsblk", &["--json", "--output",
+                           "PATH,LABEL,SIZE,MOUNTPOINT,HOTPLUG,RM,FSTYPE"]).
+        and_then(|o| parse_lsblk(&o))
+}
+
+// Mounts the drive and opens wherever udisks put it.
+pub fn mount_command(config: &Config, drive: &Drive) -> String {
+    let device = shell_quote(&drive.device);
+
+    format!("udisksctl mount --no-user-interaction -b {0} && \
+             {1} \"$(lsblk --noheadings --output MOUNTPOINT {0} | \
+             head -n 1)\"", device, config.open)
+}
+
+pub fn open_command(config: &Config, mountpoint: &str) -> String {
+    format!("{} {}", config.open, shell_quote(mountpoint))
+}
+
+pub fn unmount_command(drive: &Drive) -> String {
+    format!("udisksctl unmount --no-user-interaction -b {}",
+            shell_quote(&drive.device))
+}
+
+// Unmounts first if needed, then powers the whole disk off so it's safe to
+// pull.
+pub fn eject_command(drive: &Drive) -> String {
+    let power_off = format!("udisksctl power-off --no-user-interaction -b {}",
+                            shell_quote(&drive.disk));
+
+    match drive.mountpoint {
+        Some(_) => format!("{} && {}", unmount_command(drive), power_off),
+        None    => power_off,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Drive, eject_command, parse_lsblk};
+
+    #[test]
+    fn it_lists_removable_filesystems() {
+        let json = r#"{"blockdevices": [
+            {"path": "/dev/nvme0n1", "rm": false, "hotplug": false,
+             "fstype": null, "children": [
+                {"path": "/dev/nvme0n1p1", "rm": false, "hotplug": false,
+                 "fstype": "ext4", "mountpoints": ["/"]}]},
+            {"path": "/dev/sdb", "rm": "1", "hotplug": "1", "fstype": null,
+             "size": "16G", "children": [
+                {"path": "/dev/sdb1", "rm": "1", "hotplug": "1",
+                 "fstype": "vfat", "label": "USB", "size": "16G",
+                 "mountpoint": "/run/media/b/USB"},
+                {"path": "/dev/sdb2", "rm": "1", "hotplug": "1",
+                 "fstype": null}]},
+            {"path": "/dev/sdc", "rm": true, "hotplug": true,
+             "fstype": "exfat", "size": "64G", "mountpoints": [null]}
+        ]}"#;
+
+        let drives = parse_lsblk(json).unwrap();
+
+        assert_eq!(drives, vec![
+            Drive {
+                device: "/dev/sdb1".to_string(),
+                disk: "/dev/sdb".to_string(),
+                label: Some("USB".to_string()),
+                size: "16G".to_string(),
+                mountpoint: Some("/run/media/b/USB".to_string()),
+            },
+            Drive {
+                device: "/dev/sdc".to_string(),
+                disk: "/dev/sdc".to_string(),
+                label: None,
+                size: "64G".to_string(),
+                mountpoint: None,
+            },
+        ]);
+        assert_eq!(eject_command(&drives[1]),
+                   "udisksctl power-off --no-user-interaction -b '/dev/sdc'");
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+use std::process::Command;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+
+use serde_json;
+
+use node::Node;
+
+pub type Output = Result<String, String>;
+
+// Runs children_command scripts on worker threads so a slow script doesn't
+// freeze the window. Each command runs at most once; the UI polls for
+// finished commands and stores their output in the menu.
+pub struct Loader {
+    started: HashSet<String>,
+    sender: Sender<(String, Output)>,
+    receiver: Receiver<(String, Output)>,
+}
+
+impl Default for Loader {
+    fn default() -> Loader {
+        Loader::new()
+    }
+}
+
+impl Loader {
+    pub fn new() -> Loader {
+        let (sender, receiver) = channel();
+
+        Loader {
+            started: HashSet::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn request(&mut self, command: &str) {
+        if !self.started.insert(command.to_string()) {
+            return;
+        }
+
+        let command = command.to_string();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            let output = run(&command);
+            let _ = sender.send((command, output));
+        });
+    }
+
+    pub fn poll(&self) -> Vec<(String, Output)> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+// Runs a children_command to completion, returning its stdout.
+pub fn run(command: &str) -> Output {
+    let output = match Command::new("sh").arg("-c").arg(command).output() {
+        Ok (o) => o,
+        Err(e) => return Err(format!("Can't run {}: {}", command, e)),
+    };
+
+    if !output.status.success() {
+        return Err(format!("{} failed: {}", command,
+            String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Runs a children_command and parses the nodes it prints, for callers that
+// can afford to wait for it.
+pub fn load_children(command: &str) -> Result<Vec<Node>, String> {
+    run(command).and_then(|o| serde_json::from_str(&o).
+        map_err(|e| format!("Can't load {}: {}", command, e)))
+}
@@ -0,0 +1,79 @@
+// Readline-style editing for the query box, on Ctrl+U, Ctrl+W, Ctrl+A and
+// Ctrl+E unless the keymap says otherwise. Positions are in characters,
+// like GTK's, and "line" means the line the cursor is on in a multi-line
+// entry.
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Edit {
+    // Delete back to the start of the line
+    KillLine,
+    // Delete the word before the cursor, and any spaces after it
+    KillWord,
+    Home,
+    End,
+}
+
+fn line_start(chars: &[char], cursor: usize) -> usize {
+    chars[..cursor].iter().rposition(|c| *c == '\n').map_or(0, |i| i + 1)
+}
+
+fn line_end(chars: &[char], cursor: usize) -> usize {
+    chars[cursor..].iter().position(|c| *c == '\n').
+        map_or(chars.len(), |i| cursor + i)
+}
+
+fn word_start(chars: &[char], cursor: usize) -> usize {
+    let mut at = cursor;
+
+    while at > 0 && chars[at - 1].is_whitespace() && chars[at - 1] != '\n' {
+        at -= 1;
+    }
+
+    while at > 0 && !chars[at - 1].is_whitespace() {
+        at -= 1;
+    }
+
+    at
+}
+
+// The text and cursor position after an edit.
+pub fn apply(edit: Edit, text: &str, cursor: usize) -> (String, usize) {
+    let chars: Vec<char> = text.chars().collect();
+    let cursor = cursor.min(chars.len());
+
+    let from = match edit {
+        Edit::KillLine => line_start(&chars, cursor),
+        Edit::KillWord => word_start(&chars, cursor),
+        Edit::Home     => return (text.to_string(),
+                                  line_start(&chars, cursor)),
+        Edit::End      => return (text.to_string(), line_end(&chars, cursor)),
+    };
+
+    let kept: String = chars[..from].iter().chain(chars[cursor..].iter()).
+        collect();
+
+    (kept, from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Edit, apply};
+
+    #[test]
+    fn it_kills_words_and_lines() {
+        assert_eq!(apply(Edit::KillWord, "!ssh host  ", 11),
+                   ("!ssh ".to_string(), 5));
+        assert_eq!(apply(Edit::KillWord, "!ssh host", 4),
+                   (" host".to_string(), 0));
+        assert_eq!(apply(Edit::KillLine, "!make\nsudo make", 12),
+                   ("!make\nake".to_string(), 6));
+        assert_eq!(apply(Edit::KillLine, "wf", 2), ("".to_string(), 0));
+    }
+
+    #[test]
+    fn it_moves_within_the_line() {
+        assert_eq!(apply(Edit::Home, "ab\ncd", 4), ("ab\ncd".to_string(), 3));
+        assert_eq!(apply(Edit::End, "ab\ncd", 1), ("ab\ncd".to_string(), 2));
+        assert_eq!(apply(Edit::End, "ab\ncd", 3), ("ab\ncd".to_string(), 5));
+    }
+}
@@ -0,0 +1,227 @@
+use config::Tools;
+use desktop;
+use fuzzy;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // Type the emoji into the window that had focus before the launcher,
+    // with wtype or xdotool, instead of copying it
+    #[serde(default)]
+    pub type_it: bool,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        if self.type_it { vec![desktop::typing_tool()] } else { vec![] }
+    }
+}
+
+fn default_prefix() -> String {
+    ":".to_string()
+}
+
+// Emoji, name and extra search keywords
+pub const EMOJI: &[(&str, &str, &str)] = &[
+    ("😀", "grinning face", "smile happy"),
+    ("😃", "smiling face with big eyes", "smile happy joy"),
+    ("😄", "smiling face with smiling eyes", "smile happy laugh"),
+    ("😁", "beaming face", "grin teeth"),
+    ("😆", "grinning squinting face", "laugh satisfied"),
+    ("😅", "grinning face with sweat", "relief phew"),
+    ("🤣", "rolling on the floor laughing", "rofl lol"),
+    ("😂", "face with tears of joy", "lol laugh cry"),
+    ("🙂", "slightly smiling face", "smile"),
+    ("🙃", "upside-down face", "silly sarcasm"),
+    ("😉", "winking face", "wink"),
+    ("😊", "smiling face", "blush happy"),
+    ("😇", "smiling face with halo", "angel innocent"),
+    ("🥰", "smiling face with hearts", "love adore"),
+    ("😍", "smiling face with heart-eyes", "love crush"),
+    ("🤩", "star-struck", "wow excited"),
+    ("😘", "face blowing a kiss", "kiss love"),
+    ("😋", "face savoring food", "yum delicious"),
+    ("😛", "face with tongue", "tongue silly"),
+    ("😜", "winking face with tongue", "joke silly"),
+    ("🤪", "zany face", "crazy goofy"),
+    ("🤔", "thinking face", "hmm think wonder"),
+    ("🤨", "face with raised eyebrow", "skeptic doubt"),
+    ("😐", "neutral face", "meh blank"),
+    ("😑", "expressionless face", "meh"),
+    ("😶", "face without mouth", "silent quiet"),
+    ("🙄", "face with rolling eyes", "eyeroll whatever"),
+    ("😏", "smirking face", "smirk smug"),
+    ("😬", "grimacing face", "awkward eek"),
+    ("😌", "relieved face", "calm"),
+    ("😔", "pensive face", "sad"),
+    ("😴", "sleeping face", "sleep zzz tired"),
+    ("🤒", "face with thermometer", "sick ill"),
+    ("🤯", "exploding head", "mind blown shocked"),
+    ("🥳", "partying face", "party celebrate birthday"),
+    ("😎", "smiling face with sunglasses", "cool"),
+    ("🤓", "nerd face", "geek glasses"),
+    ("😕", "confused face", "puzzled"),
+    ("😮", "face with open mouth", "surprised wow"),
+    ("😲", "astonished face", "shocked"),
+    ("😳", "flushed face", "embarrassed"),
+    ("🥺", "pleading face", "puppy eyes please"),
+    ("😢", "crying face", "sad tear"),
+    ("😭", "loudly crying face", "sob sad"),
+    ("😱", "face screaming in fear", "scream scared"),
+    ("😤", "face with steam from nose", "triumph huff"),
+    ("😡", "pouting face", "angry mad rage"),
+    ("🤬", "face with symbols on mouth", "swear curse"),
+    ("💀", "skull", "dead"),
+    ("💩", "pile of poo", "poop"),
+    ("🤡", "clown face", "clown"),
+    ("👻", "ghost", "halloween boo"),
+    ("👽", "alien", "ufo space"),
+    ("🤖", "robot", "bot"),
+    ("👋", "waving hand", "wave hello bye"),
+    ("👌", "ok hand", "okay perfect"),
+    ("✌", "victory hand", "peace"),
+    ("🤞", "crossed fingers", "luck hope"),
+    ("👈", "backhand index pointing left", "point left"),
+    ("👉", "backhand index pointing right", "point right"),
+    ("👆", "backhand index pointing up", "point up"),
+    ("👇", "backhand index pointing down", "point down"),
+    ("👍", "thumbs up", "yes approve like +1"),
+    ("👎", "thumbs down", "no disapprove -1"),
+    ("👏", "clapping hands", "clap applause"),
+    ("🙌", "raising hands", "hooray celebrate"),
+    ("🙏", "folded hands", "please thanks pray"),
+    ("💪", "flexed biceps", "strong muscle"),
+    ("👀", "eyes", "look see"),
+    ("🧠", "brain", "smart think"),
+    ("❤", "red heart", "love"),
+    ("🧡", "orange heart", "love"),
+    ("💛", "yellow heart", "love"),
+    ("💚", "green heart", "love"),
+    ("💙", "blue heart", "love"),
+    ("💜", "purple heart", "love"),
+    ("🖤", "black heart", "love"),
+    ("💔", "broken heart", "heartbreak sad"),
+    ("💯", "hundred points", "100 perfect score"),
+    ("💥", "collision", "boom bang"),
+    ("💤", "zzz", "sleep"),
+    ("✨", "sparkles", "shiny magic"),
+    ("⭐", "star", "favorite"),
+    ("🔥", "fire", "hot lit flame"),
+    ("🌈", "rainbow", "pride"),
+    ("☀", "sun", "sunny weather"),
+    ("☁", "cloud", "weather"),
+    ("🌧", "cloud with rain", "rain weather"),
+    ("⚡", "high voltage", "lightning zap electric"),
+    ("❄", "snowflake", "snow cold winter"),
+    ("🌙", "crescent moon", "night"),
+    ("🌍", "globe showing europe-africa", "earth world"),
+    ("🐶", "dog face", "puppy pet"),
+    ("🐱", "cat face", "kitten pet"),
+    ("🦊", "fox", "animal"),
+    ("🐻", "bear", "animal"),
+    ("🐼", "panda", "animal"),
+    ("🐧", "penguin", "linux tux"),
+    ("🐍", "snake", "python"),
+    ("🦀", "crab", "rust ferris"),
+    ("🐛", "bug", "insect debug"),
+    ("🦄", "unicorn", "magic"),
+    ("🌱", "seedling", "plant grow"),
+    ("🌲", "evergreen tree", "tree forest"),
+    ("🌸", "cherry blossom", "flower spring"),
+    ("🍎", "red apple", "fruit"),
+    ("🍌", "banana", "fruit"),
+    ("🍕", "pizza", "food"),
+    ("🍔", "hamburger", "burger food"),
+    ("🌮", "taco", "food"),
+    ("🍣", "sushi", "food"),
+    ("🍪", "cookie", "food"),
+    ("🎂", "birthday cake", "cake party"),
+    ("☕", "hot beverage", "coffee tea"),
+    ("🍺", "beer mug", "beer drink cheers"),
+    ("🍷", "wine glass", "wine drink"),
+    ("🎉", "party popper", "tada celebrate party"),
+    ("🎁", "wrapped gift", "present birthday"),
+    ("🏆", "trophy", "win award"),
+    ("⚽", "soccer ball", "football sport"),
+    ("🎮", "video game", "controller gaming"),
+    ("🎵", "musical note", "music"),
+    ("🎧", "headphone", "music audio"),
+    ("📷", "camera", "photo"),
+    ("💻", "laptop", "computer"),
+    ("⌨", "keyboard", "type"),
+    ("🖥", "desktop computer", "monitor screen"),
+    ("📱", "mobile phone", "cell smartphone"),
+    ("🔋", "battery", "power"),
+    ("🔌", "electric plug", "power"),
+    ("💡", "light bulb", "idea"),
+    ("🔍", "magnifying glass tilted left", "search find"),
+    ("🔒", "locked", "lock secure"),
+    ("🔑", "key", "password"),
+    ("🔧", "wrench", "tool fix"),
+    ("🔨", "hammer", "tool build"),
+    ("⚙", "gear", "settings cog"),
+    ("📦", "package", "box ship"),
+    ("📁", "file folder", "directory"),
+    ("📝", "memo", "note write"),
+    ("📅", "calendar", "date"),
+    ("📌", "pushpin", "pin"),
+    ("📎", "paperclip", "attach"),
+    ("✂", "scissors", "cut"),
+    ("🗑", "wastebasket", "trash delete"),
+    ("📧", "e-mail", "email mail"),
+    ("🚀", "rocket", "launch ship deploy"),
+    ("🚗", "automobile", "car"),
+    ("✈", "airplane", "plane travel flight"),
+    ("🏠", "house", "home"),
+    ("⏰", "alarm clock", "time wake"),
+    ("⌛", "hourglass done", "time wait"),
+    ("✅", "check mark button", "done yes ok"),
+    ("✔", "check mark", "done yes"),
+    ("❌", "cross mark", "no wrong x"),
+    ("❓", "red question mark", "question"),
+    ("❗", "red exclamation mark", "exclamation important"),
+    ("⚠", "warning", "caution"),
+    ("🚫", "prohibited", "no forbidden"),
+    ("➕", "plus", "add"),
+    ("➖", "minus", "subtract"),
+    ("➡", "right arrow", "arrow"),
+    ("⬅", "left arrow", "arrow"),
+    ("⬆", "up arrow", "arrow"),
+    ("⬇", "down arrow", "arrow"),
+    ("🔴", "red circle", "dot"),
+    ("🟢", "green circle", "dot"),
+    ("🏁", "chequered flag", "finish race"),
+];
+
+// Emoji matching a query, best first. Names count for more than keywords.
+pub fn search(query: &str) -> Vec<(&'static str, &'static str)> {
+    let mut found: Vec<(usize, &str, &str)> = EMOJI.iter().
+        filter_map(|&(emoji, name, keywords)| {
+            let by_name = fuzzy::score(query, name);
+            let by_keyword = keywords.split(' ').
+                filter_map(|k| fuzzy::score(query, k)).min().map(|s| s + 1);
+
+            let score = match (by_name, by_keyword) {
+                (Some(n), Some(k)) => n.min(k),
+                (n, k)             => n.or(k)?,
+            };
+
+            Some((score, emoji, name))
+        }).collect();
+
+    found.sort_by_key(|&(score, _, _)| score);
+    found.into_iter().map(|(_, emoji, name)| (emoji, name)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::search;
+
+    #[test]
+    fn it_searches_names_and_keywords() {
+        assert_eq!(search("thumbs")[0], ("👍", "thumbs up"));
+        assert_eq!(search("rust")[0].0, "🦀");
+        assert!(search("qqqq").is_empty());
+    }
+}
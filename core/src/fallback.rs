@@ -0,0 +1,68 @@
+use desktop::shell_quote;
+use suggest::url_encode;
+
+// What Enter does when nothing matches the query, so no query is a dead
+// end. "search" opens `url` with the query filled in for the {}, "note"
+// saves the query as a note and "shell" runs it as a command.
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    pub action: String,
+    #[serde(default = "default_url")]
+    pub url: String,
+}
+
+fn default_url() -> String {
+    "https://duckduckgo.com/?q={}".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Fallback {
+    // Command line that opens the search
+    Search(String),
+    Note(String),
+    Shell(String),
+}
+
+pub fn resolve(config: &Config, query: &str) -> Result<Fallback, String> {
+    match config.action.as_str() {
+        "search" => {
+            let url = config.url.replace("{}", &url_encode(query));
+            Ok(Fallback::Search(format!("xdg-open {}", shell_quote(&url))))
+        },
+        "note"   => Ok(Fallback::Note(query.to_string())),
+        "shell"  => Ok(Fallback::Shell(query.to_string())),
+        _        => Err(format!("Unknown fallback action {}", config.action)),
+    }
+}
+
+// Hint shown in place of results when Enter would fall back.
+pub fn describe(fallback: &Fallback, query: &str) -> String {
+    match *fallback {
+        Fallback::Search(_) => format!("Enter: search the web for {}", query),
+        Fallback::Note(_)   => format!("Enter: save \"{}\" as a note", query),
+        Fallback::Shell(_)  => format!("Enter: run {}", query),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, Fallback, resolve};
+
+    #[test]
+    fn it_resolves_fallbacks() {
+        let mut config = Config {
+            action: "search".to_string(),
+            url: "https://example.com/?q={}".to_string(),
+        };
+
+        assert_eq!(resolve(&config, "rust lang"), Ok(Fallback::Search(
+            "xdg-open 'https://example.com/?q=rust+lang'".to_string())));
+
+        config.action = "shell".to_string();
+        assert_eq!(resolve(&config, "ls"),
+                   Ok(Fallback::Shell("ls".to_string())));
+
+        config.action = "dance".to_string();
+        assert!(resolve(&config, "ls").is_err());
+    }
+}
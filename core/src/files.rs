@@ -0,0 +1,268 @@
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use config::{Tools, program};
+use desktop::shell_quote;
+use paths::expand_home;
+
+// Wait for a pause in typing before starting a search
+const DEBOUNCE_MS: u64 = 150;
+// Results past this many aren't worth scrolling through
+const LIMIT: usize = 200;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // Search command with a {query} placeholder, printing one path per
+    // line, e.g. "plocate -i -l 200 -- {query}". Without one the files
+    // under `root` are walked and kept in memory for the next search.
+    pub command: Option<String>,
+    #[serde(default = "default_root")]
+    pub root: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        self.command.as_deref().map(program).into_iter().collect()
+    }
+}
+
+fn default_prefix() -> String {
+    "/".to_string()
+}
+
+fn default_root() -> String {
+    "~".to_string()
+}
+
+// The search command line with the query filled in, quoted for the shell.
+pub fn fill_command(template: &str, query: &str) -> String {
+    template.replace("{query}", &shell_quote(query))
+}
+
+// Every file under `root`, skipping hidden files and directories.
+pub fn walk(root: &Path, found: &mut dyn FnMut(PathBuf)) {
+    let entries = match fs::read_dir(root) {
+        Ok (e) => e,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let path = entry.path();
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+
+        if is_dir {
+            walk(&path, found);
+        }
+
+        found(path);
+    }
+}
+
+fn name_matches(path: &Path, query: &str) -> bool {
+    path.file_name().is_some_and(|n| {
+        n.to_string_lossy().to_lowercase().contains(query)
+    })
+}
+
+enum Message {
+    Found(usize, PathBuf),
+    Done(usize),
+}
+
+// Runs searches on a worker thread, streaming paths back as they turn up.
+// A newer search stops an older one, like the suggestions.
+pub struct Searcher {
+    config: Config,
+    query: Option<String>,
+    results: Vec<PathBuf>,
+    running: bool,
+    generation: Arc<AtomicUsize>,
+    // Everything under the root, once the walker has been through it
+    walked: Arc<Mutex<Option<Vec<PathBuf>>>>,
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+}
+
+impl Searcher {
+    pub fn new(config: Config) -> Searcher {
+        let (sender, receiver) = channel();
+
+        Searcher {
+            config,
+            query: None,
+            results: vec![],
+            running: false,
+            generation: Arc::new(AtomicUsize::new(0)),
+            walked: Arc::new(Mutex::new(None)),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn results(&self) -> &[PathBuf] {
+        &self.results
+    }
+
+    pub fn running(&self) -> bool {
+        self.running
+    }
+
+    pub fn request(&mut self, query: &str) {
+        if self.query.as_deref() == Some(query) {
+            return;
+        }
+
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.query = Some(query.to_string());
+        self.results.clear();
+        self.running = true;
+
+        let query = query.to_string();
+        let command = self.config.command.clone();
+        let root = PathBuf::from(expand_home(&self.config.root));
+        let current = self.generation.clone();
+        let walked = self.walked.clone();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(DEBOUNCE_MS));
+
+            let mut found = |path: PathBuf| {
+                current.load(Ordering::SeqCst) == generation &&
+                sender.send(Message::Found(generation, path)).is_ok()
+            };
+
+            if current.load(Ordering::SeqCst) == generation {
+                match command {
+                    Some(c) => run(&fill_command(&c, &query), &mut found),
+                    None    => search_walked(&root, &walked, &query,
+                                             &mut found),
+                };
+            }
+
+            let _ = sender.send(Message::Done(generation));
+        });
+    }
+
+    // Collects paths found since the last poll, returning true if the
+    // current search got any further.
+    pub fn poll(&mut self) -> bool {
+        let generation = self.generation.load(Ordering::SeqCst);
+        let mut changed = false;
+
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Found(g, path) if g == generation &&
+                                           self.results.len() < LIMIT => {
+                    self.results.push(path);
+                    changed = true;
+                },
+                Message::Done(g) if g == generation => {
+                    self.running = false;
+                    changed = true;
+                },
+                _ => {},
+            };
+        }
+
+        changed
+    }
+}
+
+// Streams a command's output lines as paths until it ends or `found`
+// says to stop, in which case the command is killed.
+fn run(command: &str, found: &mut dyn FnMut(PathBuf) -> bool) {
+    let mut child = match Command::new("sh").arg("-c").arg(command).
+            stdout(Stdio::piped()).stderr(Stdio::null()).spawn() {
+        Ok (c) => c,
+        Err(_) => return,
+    };
+
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if !line.is_empty() && !found(PathBuf::from(line)) {
+                let _ = child.kill();
+                break;
+            }
+        }
+    }
+
+    let _ = child.wait();
+}
+
+// Filters the walked files, walking the root first if nobody has yet.
+fn search_walked(root: &Path, walked: &Mutex<Option<Vec<PathBuf>>>,
+                 query: &str, found: &mut dyn FnMut(PathBuf) -> bool) {
+    let query = query.to_lowercase();
+    let mut walked = match walked.lock() {
+        Ok (w) => w,
+        Err(_) => return,
+    };
+
+    if let Some(ref paths) = *walked {
+        for path in paths.iter().filter(|p| name_matches(p, &query)) {
+            if !found(path.clone()) {
+                return;
+            }
+        }
+
+        return;
+    }
+
+    // The walk goes on after a newer search takes over, since that one
+    // is waiting to use what it finds
+    let mut paths = vec![];
+    let mut stopped = false;
+
+    walk(root, &mut |path| {
+        if !stopped && name_matches(&path, &query) {
+            stopped = !found(path.clone());
+        }
+
+        paths.push(path);
+    });
+
+    *walked = Some(paths);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use super::{fill_command, walk};
+
+    #[test]
+    fn it_fills_in_the_query() {
+        assert_eq!(fill_command("fd -- {query} ~", "it's"),
+                   "fd -- 'it'\\''s' ~");
+    }
+
+    #[test]
+    fn it_walks_visible_files() {
+        let root = std::env::temp_dir().join("blaunch-walk-test");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("a/.hidden")).unwrap();
+        fs::write(root.join("a/one.txt"), "").unwrap();
+        fs::write(root.join("a/.hidden/two.txt"), "").unwrap();
+
+        let mut found: Vec<PathBuf> = vec![];
+        walk(&root, &mut |p| found.push(p));
+        found.sort();
+
+        assert_eq!(found, vec![root.join("a"), root.join("a/one.txt")]);
+        let _ = fs::remove_dir_all(&root);
+    }
+}
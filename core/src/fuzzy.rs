@@ -0,0 +1,47 @@
+// Loose matching for pickers where the query is a few letters of a name,
+// like "tup" for "thumbs up".
+
+// Where the letters of the query turn up in order in `text`, scored by how
+// spread out they are (lower is better). None when they don't all appear.
+pub fn score(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut query = query.to_lowercase().chars().
+        filter(|c| !c.is_whitespace()).collect::<Vec<char>>().into_iter();
+
+    let first = match query.next() {
+        Some(q) => q,
+        None    => return Some(0),
+    };
+
+    // The first letter can be anywhere, but the start of a word is better
+    let word_start = (0..text.len()).find(|&i| {
+        text[i] == first && (i == 0 || text[i - 1] == ' ')
+    });
+
+    let (mut at, mut score) = match word_start {
+        Some(i) => (i + 1, 0),
+        None    => (text.iter().position(|c| *c == first)? + 1, 1),
+    };
+
+    for q in query {
+        let found = text[at..].iter().position(|c| *c == q)?;
+        score += found;
+        at += found + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::score;
+
+    #[test]
+    fn it_scores_fuzzy_matches() {
+        assert_eq!(score("thu", "thumbs up"), Some(0));
+        assert_eq!(score("tup", "thumbs up"), Some(6));
+        assert_eq!(score("mb", "thumbs up"), Some(1));
+        assert_eq!(score("up", "thumbs up"), Some(0));
+        assert_eq!(score("zz", "thumbs up"), None);
+    }
+}
@@ -0,0 +1,128 @@
+use serde_json;
+
+use dynamic;
+use node::{Node, Resolved, resolve, borrow_nodes, fill_children};
+use providers::menu::node_action;
+use provider::Action;
+
+// What `blaunch resolve <query>` prints: how the query resolves against the
+// menu, without a window. Meant for scripts, config checks in CI and finding
+// out why a shortcut doesn't do what it should.
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct Report {
+    pub query: String,
+    // "complete", "partial" or "none"
+    pub status: String,
+    pub matches: Vec<Match>,
+    // Set when a children_command on the way failed
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct Match {
+    pub shortcut: String,
+    pub description: String,
+    pub command: Option<String>,
+    pub builtin: Option<String>,
+    pub steps: Option<Vec<String>>,
+    pub submenu: bool,
+}
+
+fn to_match(node: &Node) -> Match {
+    let (command, builtin, steps) = match node_action(node) {
+        Some(Action::Spawn(c)) |
+        Some(Action::Exec(c))    => (Some(c), None, None),
+        Some(Action::Builtin(b)) => (None, Some(b), None),
+        Some(Action::Macro(s))   => (None, None, Some(s)),
+        _                        => (None, None, None),
+    };
+
+    Match {
+        shortcut: node.shortcut.clone(),
+        description: node.description.clone(),
+        command,
+        builtin,
+        steps,
+        submenu: node.children.is_some() || node.children_command.is_some(),
+    }
+}
+
+// Resolves the query, running any children_command it passes through right
+// away instead of in the background.
+pub fn resolve_query(nodes: &[Node], query: &str) -> Report {
+    let mut nodes = nodes.to_vec();
+
+    let report = |status: &str, matches: Vec<Match>, error| Report {
+        query: query.to_string(),
+        status: status.to_string(),
+        matches,
+        error,
+    };
+
+    loop {
+        let command = match resolve(borrow_nodes(&nodes), query.to_string()) {
+            Resolved::Complete(n) => {
+                return report("complete", vec![to_match(n)], None);
+            },
+            Resolved::Partial(ns) => {
+                let status = if ns.is_empty() { "none" } else { "partial" };
+                return report(status, ns.into_iter().map(to_match).collect(),
+                              None);
+            },
+            Resolved::Loading(n) => match n.children_command {
+                Some(ref c) => c.clone(),
+                None        => return report("none", vec![], None),
+            },
+        };
+
+        let children = match dynamic::load_children(&command) {
+            Ok (c) => c,
+            Err(e) => return report("none", vec![], Some(e)),
+        };
+
+        // With the children filled in, the next pass descends into them
+        fill_children(&mut nodes, &command, &children);
+    }
+}
+
+pub fn to_json(report: &Report) -> String {
+    serde_json::to_string_pretty(report).
+        unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use node::Node;
+    use super::resolve_query;
+
+    #[test]
+    fn it_resolves_without_a_window() {
+        let nodes = vec![
+            Node {
+                shortcut: "t".to_string(),
+                description: "terminal".to_string(),
+                command: Some("xterm".to_string()),
+                ..Default::default()
+            },
+            Node {
+                shortcut: "v".to_string(),
+                description: "vms".to_string(),
+                children_command: Some(
+                    "echo '[{\"shortcut\": \"w\", \"description\": \"win\", \
+                     \"command\": \"virsh start win\"}]'".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let report = resolve_query(&nodes, "t");
+        assert_eq!(report.status, "complete");
+        assert_eq!(report.matches[0].command, Some("xterm".to_string()));
+
+        let report = resolve_query(&nodes, "vw");
+        assert_eq!(report.status, "complete");
+        assert_eq!(report.matches[0].description, "win");
+
+        assert_eq!(resolve_query(&nodes, "").matches.len(), 2);
+        assert_eq!(resolve_query(&nodes, "x").status, "none");
+    }
+}
@@ -0,0 +1,99 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use paths::expand_home;
+use provider::{Action, encode_action, decode_action};
+
+// Opt-in local log of what gets launched. Nothing is recorded unless the
+// config has a "history" section and nothing ever leaves the machine.
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_file")]
+    pub file: String,
+    // How many recent launches to list above the menu when nothing is
+    // typed. 0 turns the section off.
+    #[serde(default = "default_recent")]
+    pub recent: usize,
+}
+
+fn default_file() -> String {
+    "~/.local/share/blaunch/history.log".to_string()
+}
+
+fn default_recent() -> usize {
+    5
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Entry {
+    // Seconds since the epoch
+    pub time: u64,
+    pub keystrokes: usize,
+    pub title: String,
+    // What was launched, so it can be launched again. Older lines and
+    // actions that can't be stored on one line don't have one.
+    pub action: Option<Action>,
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).
+        map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// Lines are "time, keystrokes, title, action", tab separated.
+pub fn record(path: &str, keystrokes: usize, title: &str,
+              action: Option<&Action>) -> io::Result<()> {
+    let path = expand_home(path);
+
+    if let Some(dir) = Path::new(&path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+    let title = title.replace(['\t', '\n'], " ");
+    let action = action.and_then(encode_action).unwrap_or_default();
+
+    writeln!(file, "{}\t{}\t{}\t{}", now(), keystrokes, title, action)
+}
+
+fn parse_line(raw: &str) -> Option<Entry> {
+    let mut parts = raw.splitn(4, '\t');
+
+    Some(Entry {
+        time: parts.next()?.parse().ok()?,
+        keystrokes: parts.next()?.parse().ok()?,
+        title: parts.next()?.to_string(),
+        action: parts.next().and_then(decode_action),
+    })
+}
+
+// Oldest first. Lines that don't parse are skipped.
+pub fn load(path: &str) -> Vec<Entry> {
+    fs::read_to_string(expand_home(path)).unwrap_or_default().lines().
+        filter_map(parse_line).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use provider::Action;
+    use super::{Entry, parse_line};
+
+    #[test]
+    fn it_parses_history_lines() {
+        assert_eq!(parse_line("1700000000\t3\tfirefox"), Some(Entry {
+            time: 1700000000,
+            keystrokes: 3,
+            title: "firefox".to_string(),
+            action: None,
+        }));
+
+        let entry = parse_line("1700000000\t3\tfirefox\tspawn:firefox -P").
+            unwrap();
+        assert_eq!(entry.action, Some(Action::Spawn("firefox -P".to_string())));
+        assert_eq!(parse_line("garbage"), None);
+        assert_eq!(parse_line("1700000000\tx\tfirefox"), None);
+    }
+}
@@ -0,0 +1,71 @@
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Modifier {
+    Shift,
+    Control,
+    Alt,
+    Super,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Hotkey {
+    pub modifiers: Vec<Modifier>,
+    // X keysym name, like "space" or "d"
+    pub key: String,
+}
+
+// Parses chords like "Super+Space" or "ctrl+alt+d". Modifier names are case
+// insensitive; single-letter and named keys are lowercased to the keysym
+// spelling ("Space" is "space", "Return" is kept as is).
+pub fn parse(chord: &str) -> Result<Hotkey, String> {
+    let mut parts: Vec<&str> = chord.split('+').map(|p| p.trim()).collect();
+
+    let key = match parts.pop() {
+        Some(k) if !k.is_empty() => k,
+        _ => return Err(format!("No key in hotkey {}", chord)),
+    };
+
+    let mut modifiers = vec![];
+
+    for p in parts {
+        let modifier = match p.to_lowercase().as_str() {
+            "shift"                   => Modifier::Shift,
+            "ctrl" | "control"        => Modifier::Control,
+            "alt" | "mod1"            => Modifier::Alt,
+            "super" | "mod4" | "logo" => Modifier::Super,
+            _ => return Err(format!("Unknown modifier {} in hotkey {}",
+                                    p, chord)),
+        };
+
+        if !modifiers.contains(&modifier) {
+            modifiers.push(modifier);
+        }
+    }
+
+    let key = match key.to_lowercase().as_str() {
+        "space" | "tab" | "escape" => key.to_lowercase(),
+        _ if key.chars().count() == 1 => key.to_lowercase(),
+        _ => key.to_string(),
+    };
+
+    Ok(Hotkey { modifiers, key })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hotkey, Modifier, parse};
+
+    #[test]
+    fn it_parses_hotkeys() {
+        assert_eq!(parse("Super+Space"), Ok(Hotkey {
+            modifiers: vec![Modifier::Super],
+            key: "space".to_string(),
+        }));
+        assert_eq!(parse("ctrl + alt + D"), Ok(Hotkey {
+            modifiers: vec![Modifier::Control, Modifier::Alt],
+            key: "d".to_string(),
+        }));
+        assert_eq!(parse("F12").map(|h| h.key), Ok("F12".to_string()));
+        assert!(parse("Hyper+x").is_err());
+        assert!(parse("Super+").is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+
+use network;
+use paths::expand_home;
+
+// Icon theme names for results that don't come with an icon of their own,
+// so a mixed result list can be told apart at a glance. An item's icon is
+// either one of these names or the path of an image file.
+pub const APPLICATION: &str = "application-x-executable";
+pub const CALCULATOR : &str = "accessories-calculator";
+pub const FOLDER     : &str = "folder";
+pub const FILE       : &str = "text-x-generic";
+pub const SHELL      : &str = "utilities-terminal";
+pub const WEB        : &str = "web-browser";
+pub const WINDOW     : &str = "window-new";
+
+const FAVICON_DIR: &str = "~/.cache/blaunch/favicons";
+
+// Picks a folder icon for directories and a generic MIME type icon for
+// files, going by the extension.
+pub fn for_path(path: &Path) -> &'static str {
+    if path.is_dir() {
+        return FOLDER;
+    }
+
+    let extension = path.extension().and_then(|e| e.to_str()).
+        map(|e| e.to_lowercase()).unwrap_or_default();
+
+    match extension.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" => "image-x-generic",
+        "mp3" | "flac" | "ogg" | "wav" | "opus"         => "audio-x-generic",
+        "mp4" | "mkv" | "webm" | "avi" | "mov"          => "video-x-generic",
+        "pdf"                                           => "application-pdf",
+        "zip" | "tar" | "gz" | "xz" | "bz2" | "7z"      => "package-x-generic",
+        "sh" | "py" | "pl" | "rb"                       => "text-x-script",
+        "html" | "htm"                                  => "text-html",
+        _                                               => FILE,
+    }
+}
+
+// The host part of a URL, like "example.com" in "https://example.com/a".
+pub fn url_host(url: &str) -> Option<&str> {
+    let rest = match url.find("://") {
+        Some(i) => &url[i + 3..],
+        None    => return None,
+    };
+
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = host.rsplit('@').next().unwrap_or("");
+
+    if host.is_empty() {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+// Site icons for URL results, cached on disk. A site's icon is fetched in
+// the background the first time it's asked for; until it arrives (or if it
+// never does) the result gets the generic web icon.
+#[derive(Default)]
+pub struct Favicons {
+    started: RefCell<HashSet<String>>,
+    monitor: network::Monitor,
+}
+
+impl Favicons {
+    pub fn new() -> Favicons {
+        Favicons::default()
+    }
+
+    pub fn icon(&self, url: &str) -> String {
+        let host = match url_host(url) {
+            Some(h) => h,
+            None    => return WEB.to_string(),
+        };
+
+        let path = favicon_path(host);
+
+        if path.is_file() {
+            return path.to_string_lossy().into_owned();
+        }
+
+        // Offline hosts aren't marked as started, so they're tried again
+        // once the network is back
+        if self.monitor.is_online() &&
+           self.started.borrow_mut().insert(host.to_string()) {
+            fetch(host, path);
+        }
+
+        WEB.to_string()
+    }
+}
+
+fn favicon_path(host: &str) -> PathBuf {
+    PathBuf::from(expand_home(FAVICON_DIR)).join(format!("{}.ico", host))
+}
+
+fn fetch(host: &str, path: PathBuf) {
+    let url = format!("https://{}/favicon.ico", host);
+
+    thread::spawn(move || {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+
+        // Download next to the cache entry so a half written icon is never
+        // picked up
+        let partial = path.with_extension("part");
+        let fetched = Command::new("curl").
+            args(["-s", "-f", "-L", "--max-time", "5", "-o"]).
+            arg(&partial).arg(&url).status();
+
+        match fetched {
+            Ok (s) if s.success() => {
+                let _ = fs::rename(&partial, &path);
+            },
+            _ => {
+                let _ = fs::remove_file(&partial);
+            },
+        };
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::Path;
+    use super::{FILE, FOLDER, for_path, url_host};
+
+    #[test]
+    fn it_picks_icons_by_type() {
+        assert_eq!(for_path(&env::temp_dir()), FOLDER);
+        assert_eq!(for_path(Path::new("/nope/holiday.JPG")),
+                   "image-x-generic");
+        assert_eq!(for_path(Path::new("/nope/notes")), FILE);
+
+        assert_eq!(url_host("https://user@example.com:8080/a?b"),
+                   Some("example.com:8080"));
+        assert_eq!(url_host("example.com"), None);
+    }
+}
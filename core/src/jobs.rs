@@ -0,0 +1,170 @@
+use std::process::Command;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+
+use provider::Item;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Status {
+    Running,
+    Done,
+    Failed(String),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Job {
+    id: usize,
+    pub title: String,
+    pub status: Status,
+}
+
+// Runs commands that take a while (installs, VM starts, remote commands) on
+// worker threads so the launcher can stay open and show how they went. The
+// UI polls for finished jobs like it does for dynamic children.
+pub struct Runner {
+    jobs: Vec<Job>,
+    next_id: usize,
+    sender: Sender<(usize, Status)>,
+    receiver: Receiver<(usize, Status)>,
+}
+
+impl Default for Runner {
+    fn default() -> Runner {
+        Runner::new()
+    }
+}
+
+fn run(command: &str) -> Status {
+    let output = match Command::new("sh").arg("-c").arg(command).output() {
+        Ok (o) => o,
+        Err(e) => return Status::Failed(e.to_string()),
+    };
+
+    if output.status.success() {
+        return Status::Done;
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match stderr.lines().rev().find(|l| !l.trim().is_empty()) {
+        Some(l) => Status::Failed(l.trim().to_string()),
+        None    => Status::Failed(output.status.to_string()),
+    }
+}
+
+impl Runner {
+    pub fn new() -> Runner {
+        let (sender, receiver) = channel();
+
+        Runner {
+            jobs: vec![],
+            next_id: 0,
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn start(&mut self, title: &str, command: &str) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.jobs.push(Job {
+            id,
+            title: title.to_string(),
+            status: Status::Running,
+        });
+
+        let command = command.to_string();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            let _ = sender.send((id, run(&command)));
+        });
+    }
+
+    // Collects finished jobs, returning true if any finished since the last
+    // poll.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+
+        for (id, status) in self.receiver.try_iter() {
+            if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+                job.status = status;
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    // Forgets finished jobs. Running ones keep their rows.
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(|j| j.status == Status::Running);
+    }
+
+    // Rows for the output area, above the regular results.
+    pub fn items(&self) -> Vec<Item> {
+        self.jobs.iter().map(|j| {
+            let (description, class, loading) = match j.status {
+                Status::Running       => ("running..".to_string(), "running",
+                                          true),
+                Status::Done          => ("done".to_string(), "done", false),
+                Status::Failed(ref e) => (format!("failed: {}", e), "failed",
+                                          false),
+            };
+
+            Item {
+                title: j.title.clone(),
+                description,
+                loading,
+                classes: vec![class.to_string()],
+                ..Default::default()
+            }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+    use super::{Runner, Status};
+
+    fn wait_for(runner: &mut Runner, count: usize) {
+        for _ in 0..200 {
+            runner.poll();
+
+            let finished = runner.jobs().iter().
+                filter(|j| j.status != Status::Running).count();
+            if finished >= count {
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        panic!("Jobs didn't finish");
+    }
+
+    #[test]
+    fn it_reports_job_results() {
+        let mut runner = Runner::new();
+        runner.start("ok", "true");
+        runner.start("broken", "echo nope >&2; exit 3");
+
+        assert!(runner.items()[0].loading);
+
+        wait_for(&mut runner, 2);
+
+        assert_eq!(runner.jobs()[0].status, Status::Done);
+        assert_eq!(runner.jobs()[1].status,
+                   Status::Failed("nope".to_string()));
+        assert_eq!(runner.items()[1].description, "failed: nope");
+
+        runner.clear_finished();
+        assert!(runner.jobs().is_empty());
+    }
+}
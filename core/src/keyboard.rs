@@ -0,0 +1,126 @@
+// Keyboard layouts, switched with setxkbmap on X11 and swaymsg under sway.
+// Layouts are written the xkb way, "us" or with a variant as "us(dvorak)".
+
+use std::env;
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // The layouts to offer; the system's X11 layouts from localectl when
+    // empty
+    #[serde(default)]
+    pub layouts: Vec<String>,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        if env::var_os("SWAYSOCK").is_some() {
+            vec!["swaymsg"]
+        } else {
+            vec!["setxkbmap"]
+        }
+    }
+}
+
+fn default_prefix() -> String {
+    "kb".to_string()
+}
+
+// "us(dvorak)" into the layout and the variant.
+pub fn split_layout(layout: &str) -> (&str, &str) {
+    match layout.split_once('(') {
+        Some((l, v)) => (l, v.trim_end_matches(')')),
+        None         => (layout, ""),
+    }
+}
+
+fn join_layout(layout: &str, variant: &str) -> String {
+    if variant.is_empty() {
+        layout.to_string()
+    } else {
+        format!("{}({})", layout, variant)
+    }
+}
+
+// Comma-separated layouts and variants, paired up.
+fn pair(layouts: &str, variants: &str) -> Vec<String> {
+    let mut variants = variants.split(',');
+
+    layouts.split(',').filter(|l| !l.trim().is_empty()).map(|l| {
+        join_layout(l.trim(), variants.next().unwrap_or("").trim())
+    }).collect()
+}
+
+// Values after "<key>:" in `setxkbmap -query` or `localectl status`.
+fn value<'a>(output: &'a str, key: &str) -> &'a str {
+    output.lines().filter_map(|l| l.trim().strip_prefix(key)).
+        filter_map(|rest| rest.trim_start().strip_prefix(':')).
+        map(|v| v.trim()).next().unwrap_or("")
+}
+
+// The layouts from `localectl status`.
+pub fn parse_localectl(output: &str) -> Vec<String> {
+    pair(value(output, "X11 Layout"), value(output, "X11 Variant"))
+}
+
+// The active layout from `setxkbmap -query`; the first one when several
+// are set up for toggling.
+pub fn parse_query(output: &str) -> Option<String> {
+    pair(value(output, "layout"), value(output, "variant")).
+        into_iter().next()
+}
+
+pub fn layouts(config: &Config) -> Vec<String> {
+    if !config.layouts.is_empty() {
+        return config.layouts.clone();
+    }
+
+    cli::output("localectl", &["status"]).
+        map(|o| parse_localectl(&o)).unwrap_or_default()
+}
+
+// Sway doesn't keep setxkbmap's state, so there's nothing to mark there.
+pub fn current() -> Option<String> {
+    if env::var_os("SWAYSOCK").is_some() {
+        return None;
+    }
+
+    cli::output("setxkbmap", &["-query"]).ok().and_then(|o| parse_query(&o))
+}
+
+pub fn switch_command(layout: &str) -> String {
+    let (layout, variant) = split_layout(layout);
+
+    if env::var_os("SWAYSOCK").is_some() {
+        return format!("swaymsg input type:keyboard xkb_layout {} && \
+                        swaymsg input type:keyboard xkb_variant {}",
+                       shell_quote(layout), shell_quote(variant));
+    }
+
+    format!("setxkbmap -layout {} -variant {}", shell_quote(layout),
+            shell_quote(variant))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_localectl, parse_query, split_layout};
+
+    #[test]
+    fn it_reads_layouts() {
+        let status = concat!("   System Locale: LANG=en_US.UTF-8\n",
+                             "       X11 Layout: us,de\n",
+                             "      X11 Variant: dvorak,\n");
+        let query = "rules:      evdev\n\
+                     layout:     de\n";
+
+        assert_eq!(parse_localectl(status), vec!["us(dvorak)", "de"]);
+        assert_eq!(parse_query(query), Some("de".to_string()));
+        assert_eq!(split_layout("us(dvorak)"), ("us", "dvorak"));
+        assert_eq!(split_layout("de"), ("de", ""));
+    }
+}
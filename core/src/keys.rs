@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use hotkey::{self, Modifier};
+
+// What a key press does in the launcher. The "keys" section of the config
+// maps the names below to chords, e.g. {"quit": "Ctrl+Q", "next": "Tab"},
+// on top of the defaults.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Key {
+    Quit,
+    Launch,
+    // Run the selected result's alternate action, if it has one
+    LaunchAlternate,
+    Next,
+    Previous,
+    // Fill in the selected result's completion, like a submenu's path
+    Complete,
+    Clear,
+    // New line in a multi-line entry, for shell commands
+    Newline,
+    CopyResults,
+    // The same as an image of the results, as they look on screen
+    CopyResultsImage,
+    Record,
+    Pin,
+    KillLine,
+    KillWord,
+    Home,
+    End,
+    DeleteNote,
+    Decrease,
+    Increase,
+}
+
+const DEFAULTS: &[(&str, Key, &str)] = &[
+    ("quit",               Key::Quit,             "Escape"),
+    ("launch",             Key::Launch,           "Return"),
+    ("launch_alternate",   Key::LaunchAlternate,  "Ctrl+Return"),
+    ("next",               Key::Next,             "Down"),
+    ("previous",           Key::Previous,         "Up"),
+    ("complete",           Key::Complete,         "Tab"),
+    ("clear",              Key::Clear,            "Ctrl+L"),
+    ("newline",            Key::Newline,          "Shift+Return"),
+    ("copy_results",       Key::CopyResults,      "Ctrl+S"),
+    ("copy_results_image", Key::CopyResultsImage, "Ctrl+Shift+S"),
+    ("record",             Key::Record,           "Ctrl+R"),
+    ("pin",                Key::Pin,              "Ctrl+P"),
+    ("kill_line",          Key::KillLine,         "Ctrl+U"),
+    ("kill_word",          Key::KillWord,         "Ctrl+W"),
+    ("home",               Key::Home,             "Ctrl+A"),
+    ("end",                Key::End,              "Ctrl+E"),
+    ("delete_note",        Key::DeleteNote,       "Ctrl+Delete"),
+    ("decrease",           Key::Decrease,         "Left"),
+    ("increase",           Key::Increase,         "Right"),
+];
+
+pub const RETURN: u32 = 0xff0d;
+const ISO_LEFT_TAB: u32 = 0xfe20;
+const TAB: u32 = 0xff09;
+
+// X keysym values for the named keys a keymap is likely to use
+const NAMED: &[(&str, u32)] = &[
+    ("space", 0x20), ("backspace", 0xff08), ("tab", TAB), ("return", RETURN),
+    ("enter", RETURN), ("escape", 0xff1b), ("home", 0xff50),
+    ("left", 0xff51), ("up", 0xff52), ("right", 0xff53), ("down", 0xff54),
+    ("page_up", 0xff55), ("page_down", 0xff56), ("end", 0xff57),
+    ("insert", 0xff63), ("kp_enter", 0xff8d), ("delete", 0xffff),
+];
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Binding {
+    pub keyval: u32,
+    pub modifiers: Vec<Modifier>,
+    pub key: Key,
+}
+
+// The keysym value for a key name: printable characters are their own
+// value, function keys are F1 through F12.
+pub fn keyval(name: &str) -> Option<u32> {
+    let lower = name.to_lowercase();
+    let mut chars = lower.chars();
+
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return if c.is_ascii_graphic() { Some(c as u32) } else { None };
+    }
+
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u32>().
+            ok()).filter(|n| (1..=12).contains(n)) {
+        return Some(0xffbe + n - 1);
+    }
+
+    NAMED.iter().find(|(n, _)| *n == lower).map(|(_, v)| *v)
+}
+
+// What GDK reports varies with Shift: "A" for "a" and ISO_Left_Tab for Tab.
+pub fn normalize(keyval: u32) -> u32 {
+    match keyval {
+        ISO_LEFT_TAB                    => TAB,
+        k if (0x41..=0x5a).contains(&k) => k + 0x20,
+        k                               => k,
+    }
+}
+
+fn binding(key: Key, chord: &str) -> Result<Binding, String> {
+    let hotkey = hotkey::parse(chord)?;
+
+    let keyval = match keyval(&hotkey.key) {
+        Some(k) => k,
+        None    => return Err(format!("Unknown key {} in {}", hotkey.key,
+                                      chord)),
+    };
+
+    Ok(Binding { keyval, modifiers: hotkey.modifiers, key })
+}
+
+// The default keymap with the configured chords swapped in.
+pub fn keymap(overrides: &HashMap<String, String>)
+    -> Result<Vec<Binding>, String> {
+    if let Some(name) = overrides.keys().
+            find(|n| !DEFAULTS.iter().any(|(d, _, _)| d == n)) {
+        return Err(format!("Unknown key binding {}", name));
+    }
+
+    DEFAULTS.iter().map(|&(name, key, chord)| {
+        let chord = overrides.get(name).map_or(chord, |c| c.as_str());
+        binding(key, chord)
+    }).collect()
+}
+
+// What a press does. Modifiers have to match exactly, so Shift+Return
+// isn't also Return.
+pub fn lookup(keymap: &[Binding], keyval: u32, modifiers: &[Modifier])
+    -> Option<Key> {
+    let keyval = normalize(keyval);
+
+    keymap.iter().find(|b| {
+        b.keyval == keyval && b.modifiers.len() == modifiers.len() &&
+        b.modifiers.iter().all(|m| modifiers.contains(m))
+    }).map(|b| b.key)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use hotkey::Modifier;
+    use super::{Key, RETURN, keymap, keyval, lookup};
+
+    #[test]
+    fn it_knows_keyvals() {
+        assert_eq!(keyval("Escape"), Some(0xff1b));
+        assert_eq!(keyval("q"), Some(0x71));
+        assert_eq!(keyval("F12"), Some(0xffc9));
+        assert_eq!(keyval("Hyper"), None);
+    }
+
+    #[test]
+    fn it_overrides_default_keys() {
+        let mut overrides = HashMap::new();
+        overrides.insert("quit".to_string(), "Ctrl+q".to_string());
+        let keys = keymap(&overrides).unwrap();
+
+        assert_eq!(lookup(&keys, 0x71, &[Modifier::Control]), Some(Key::Quit));
+        assert_eq!(lookup(&keys, 0xff1b, &[]), None);
+        assert_eq!(lookup(&keys, RETURN, &[]), Some(Key::Launch));
+        assert_eq!(lookup(&keys, RETURN, &[Modifier::Shift]),
+                   Some(Key::Newline));
+        assert_eq!(lookup(&keys, 0x55, &[Modifier::Control]),
+                   Some(Key::KillLine));
+
+        overrides.insert("explode".to_string(), "x".to_string());
+        assert!(keymap(&overrides).is_err());
+    }
+}
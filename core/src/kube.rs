@@ -0,0 +1,222 @@
+// Kubernetes contexts read straight from the kubeconfig files, and the
+// namespaces of a cluster through kubectl.
+
+use std::env;
+use std::fs;
+use std::time::SystemTime;
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+use paths::expand_home;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["kubectl"]
+    }
+}
+
+fn default_prefix() -> String {
+    "k8s".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Context {
+    pub name: String,
+    pub cluster: String,
+    pub namespace: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct KubeConfig {
+    pub current: Option<String>,
+    pub contexts: Vec<Context>,
+}
+
+impl KubeConfig {
+    pub fn is_current(&self, context: &Context) -> bool {
+        self.current.as_ref() == Some(&context.name)
+    }
+}
+
+fn scalar(value: &str) -> String {
+    let value = value.trim();
+
+    let quoted = |q: char| value.strip_prefix(q)?.strip_suffix(q);
+
+    quoted('"').or_else(|| quoted('\'')).unwrap_or(value).to_string()
+}
+
+// Reads the block YAML kubectl writes: "current-context" and the "contexts"
+// list, whose entries have a name and a context with the cluster and
+// namespace. Anything else in the file is skipped.
+pub fn parse_kubeconfig(yaml: &str) -> KubeConfig {
+    let mut config = KubeConfig::default();
+    let mut section = "";
+    let mut context: Option<Context> = None;
+
+    for line in yaml.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        // A top-level key ends the section before it
+        if !line.starts_with(' ') && !line.starts_with('-') {
+            config.contexts.extend(context.take());
+
+            let (key, value) = line.split_once(':').unwrap_or((line, ""));
+            section = key.trim();
+
+            if section == "current-context" && !scalar(value).is_empty() {
+                config.current = Some(scalar(value));
+            }
+
+            continue;
+        }
+
+        if section != "contexts" {
+            continue;
+        }
+
+        let mut entry = line.trim_start();
+
+        if let Some(rest) = entry.strip_prefix("- ") {
+            config.contexts.extend(context.take());
+            context = Some(Context::default());
+            entry = rest;
+        }
+
+        let (c, (key, value)) =
+            match (context.as_mut(), entry.split_once(':')) {
+                (Some(c), Some(kv)) => (c, kv),
+                _                   => continue,
+            };
+
+        match key.trim() {
+            "name"      => c.name = scalar(value),
+            "cluster"   => c.cluster = scalar(value),
+            "namespace" => c.namespace = Some(scalar(value)),
+            _           => {},
+        };
+    }
+
+    config.contexts.extend(context.take());
+    config.contexts.retain(|c| !c.name.is_empty());
+    config
+}
+
+// KUBECONFIG's files, or ~/.kube/config.
+fn paths() -> Vec<String> {
+    match env::var("KUBECONFIG") {
+        Ok (ref k) if !k.is_empty() => k.split(':').
+            filter(|p| !p.is_empty()).map(|p| p.to_string()).collect(),
+        _ => vec![expand_home("~/.kube/config")],
+    }
+}
+
+// The files merged the way kubectl does: the first file to set something
+// wins.
+pub fn load() -> KubeConfig {
+    let mut merged = KubeConfig::default();
+
+    for path in paths() {
+        let config = parse_kubeconfig(&fs::read_to_string(path).
+            unwrap_or_default());
+
+        if merged.current.is_none() {
+            merged.current = config.current;
+        }
+
+        for c in config.contexts {
+            if !merged.contexts.iter().any(|m| m.name == c.name) {
+                merged.contexts.push(c);
+            }
+        }
+    }
+
+    merged
+}
+
+// When any of the files last changed.
+pub fn modified() -> Option<SystemTime> {
+    paths().iter().filter_map(|p| fs::metadata(p).and_then(|m| m.modified()).
+        ok()).max()
+}
+
+// `kubectl get namespaces -o name` prints "namespace/<name>" lines.
+pub fn parse_namespaces(output: &str) -> Vec<String> {
+    output.lines().filter_map(|l| l.trim().strip_prefix("namespace/")).
+        map(|n| n.to_string()).collect()
+}
+
+pub fn namespaces(context: &str) -> Result<Vec<String>, String> {
+    cli::output("kubectl", &["--context", context, "--request-timeout=5s",
+                             "get", "namespaces", "-o", "name"]).
+        map(|o| parse_namespaces(&o))
+}
+
+pub fn use_context_command(context: &str) -> String {
+    format!("kubectl config use-context {}", shell_quote(context))
+}
+
+// Switches to the context too, so picking a namespace anywhere leaves it
+// the active one.
+pub fn namespace_command(context: &str, namespace: &str) -> String {
+    format!("{} && kubectl config set-context {} --namespace={}",
+            use_context_command(context), shell_quote(context),
+            shell_quote(namespace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Context, parse_kubeconfig, parse_namespaces};
+
+    #[test]
+    fn it_parses_kubeconfig() {
+        let yaml = "apiVersion: v1\n\
+                    clusters:\n\
+                    - cluster:\n    \
+                        server: https://10.0.0.1:6443\n  \
+                      name: prod\n\
+                    contexts:\n\
+                    - context:\n    \
+                        cluster: prod\n    \
+                        namespace: web\n    \
+                        user: admin\n  \
+                      name: prod-admin\n\
+                    - context:\n    \
+                        cluster: kind-dev\n    \
+                        user: kind-dev\n  \
+                      name: \"kind-dev\"\n\
+                    current-context: prod-admin\n\
+                    kind: Config\n";
+
+        let config = parse_kubeconfig(yaml);
+
+        assert_eq!(config.current, Some("prod-admin".to_string()));
+        assert_eq!(config.contexts, vec![
+            Context {
+                name: "prod-admin".to_string(),
+                cluster: "prod".to_string(),
+                namespace: Some("web".to_string()),
+            },
+            Context {
+                name: "kind-dev".to_string(),
+                cluster: "kind-dev".to_string(),
+                namespace: None,
+            },
+        ]);
+    }
+
+    #[test]
+    fn it_parses_namespaces() {
+        assert_eq!(parse_namespaces("namespace/default\nnamespace/web\n"),
+                   vec!["default", "web"]);
+    }
+}
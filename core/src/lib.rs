@@ -0,0 +1,81 @@
+extern crate serde;
+extern crate serde_json;
+
+#[macro_use]
+extern crate serde_derive;
+
+pub mod args;
+pub mod audio;
+pub mod bluetooth;
+pub mod bookmarks;
+pub mod builtins;
+pub mod cache;
+pub mod calc;
+pub mod cli;
+pub mod clipboard;
+pub mod cnf;
+pub mod color;
+pub mod config;
+pub mod containers;
+pub mod convert;
+pub mod daemon;
+pub mod desktop;
+pub mod dictionary;
+pub mod displays;
+pub mod doctor;
+pub mod drives;
+pub mod dynamic;
+pub mod editing;
+pub mod emoji;
+pub mod fallback;
+pub mod files;
+pub mod fuzzy;
+pub mod headless;
+pub mod history;
+pub mod hotkey;
+pub mod icons;
+pub mod jobs;
+pub mod keyboard;
+pub mod keys;
+pub mod kube;
+pub mod macros;
+pub mod man;
+pub mod network;
+pub mod node;
+pub mod notes;
+pub mod pass;
+pub mod paths;
+pub mod pins;
+pub mod print;
+pub mod processes;
+pub mod projects;
+pub mod provider;
+pub mod providers;
+pub mod qr;
+pub mod quick;
+pub mod recent_files;
+pub mod repos;
+pub mod sandboxed;
+pub mod screenshot;
+pub mod search;
+pub mod session;
+pub mod snapshot;
+pub mod snippets;
+pub mod ssh;
+pub mod steam;
+pub mod suggest;
+pub mod theme;
+pub mod timers;
+pub mod tmux;
+pub mod todo;
+pub mod translate;
+pub mod trash;
+pub mod units;
+pub mod update;
+pub mod vdf;
+pub mod vms;
+pub mod vpn;
+pub mod weather;
+pub mod wifi;
+pub mod window;
+pub mod windows;
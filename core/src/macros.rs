@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::Path;
+
+use serde_json;
+
+use node::Node;
+use paths::expand_home;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_file")]
+    pub file: String,
+}
+
+fn default_file() -> String {
+    "~/.local/share/blaunch/macros.json".to_string()
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Macro {
+    pub shortcut: String,
+    pub steps: Vec<String>,
+}
+
+impl Macro {
+    // The synthetic node the macro shows up as in the menu
+    pub fn node(&self) -> Node {
+        Node {
+            shortcut: self.shortcut.clone(),
+            description: format!("macro: {}", self.steps.join(" > ")),
+            steps: Some(self.steps.clone()),
+            ..Default::default()
+        }
+    }
+}
+
+// Collects the queries activated while recording. Nothing runs until the
+// macro is saved and replayed.
+#[derive(Default)]
+pub struct Recorder {
+    steps: Option<Vec<String>>,
+}
+
+impl Recorder {
+    pub fn is_recording(&self) -> bool {
+        self.steps.is_some()
+    }
+
+    pub fn start(&mut self) {
+        self.steps = Some(vec![]);
+    }
+
+    pub fn record(&mut self, step: &str) {
+        if let Some(ref mut s) = self.steps {
+            s.push(step.to_string());
+        }
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.steps.as_ref().map_or(0, |s| s.len())
+    }
+
+    // Ends the recording, returning the steps if there were any
+    pub fn stop(&mut self) -> Option<Vec<String>> {
+        self.steps.take().filter(|s| !s.is_empty())
+    }
+}
+
+pub fn load(path: &str) -> Vec<Macro> {
+    fs::read_to_string(expand_home(path)).ok().
+        and_then(|c| serde_json::from_str(&c).ok()).
+        unwrap_or_default()
+}
+
+// Saves the steps under the given shortcut, or "macro1", "macro2", .. when
+// it's empty. Recording over an existing shortcut replaces that macro.
+pub fn save(path: &str, shortcut: &str, steps: Vec<String>)
+        -> Result<String, String> {
+    let mut macros = load(path);
+
+    let shortcut = if shortcut.is_empty() {
+        (1..).map(|i| format!("macro{}", i)).
+            find(|s| !macros.iter().any(|m| &m.shortcut == s)).
+            unwrap_or_default()
+    } else {
+        shortcut.to_string()
+    };
+
+    macros.retain(|m| m.shortcut != shortcut);
+    macros.push(Macro { shortcut: shortcut.clone(), steps });
+
+    let path = expand_home(path);
+
+    if let Some(dir) = Path::new(&path).parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+
+    let json = serde_json::to_string_pretty(&macros).
+        map_err(|e| e.to_string())?;
+
+    fs::write(&path, json).
+        map_err(|e| format!("Can't write {}: {}", path, e))?;
+
+    Ok(shortcut)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+    use super::{Recorder, load, save};
+
+    #[test]
+    fn it_records_steps() {
+        let mut recorder = Recorder::default();
+        recorder.record("ignored");
+        assert!(!recorder.is_recording());
+
+        recorder.start();
+        assert_eq!(recorder.stop(), None);
+
+        recorder.start();
+        recorder.record("wf");
+        recorder.record("t");
+        assert_eq!(recorder.step_count(), 2);
+        assert_eq!(recorder.stop(),
+                   Some(vec!["wf".to_string(), "t".to_string()]));
+        assert!(!recorder.is_recording());
+    }
+
+    #[test]
+    fn it_saves_and_names_macros() {
+        let path = env::temp_dir().
+            join(format!("blaunch-macros-test.{}.json", process::id()));
+        let path = path.to_str().unwrap();
+
+        assert_eq!(save(path, "", vec!["t".to_string()]),
+                   Ok("macro1".to_string()));
+        assert_eq!(save(path, "", vec!["wf".to_string()]),
+                   Ok("macro2".to_string()));
+        assert_eq!(save(path, "macro1", vec!["l".to_string()]),
+                   Ok("macro1".to_string()));
+
+        let macros = load(path);
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(macros.len(), 2);
+        assert_eq!(macros[1].shortcut, "macro1");
+        assert_eq!(macros[1].steps, vec!["l"]);
+    }
+}
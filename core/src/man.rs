@@ -0,0 +1,106 @@
+// Manual pages from the apropos index, opened with man in a terminal.
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_terminal")]
+    pub terminal: String,
+    // MANPAGER for the page, like "less -R"; man's own choice when not set
+    pub pager: Option<String>,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["apropos"]
+    }
+}
+
+fn default_prefix() -> String {
+    "man".to_string()
+}
+
+fn default_terminal() -> String {
+    "xterm".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Page {
+    pub name: String,
+    pub section: String,
+    pub description: String,
+}
+
+// apropos lines look like "printf (3)           - formatted output", and
+// pages with several names list them all before the section.
+pub fn parse_apropos(output: &str) -> Vec<Page> {
+    let mut pages = vec![];
+
+    for line in output.lines() {
+        let (names, description) = match line.split_once(" - ") {
+            Some(s) => s,
+            None    => continue,
+        };
+
+        let (names, section) = match names.trim().rsplit_once(" (") {
+            Some((n, s)) => (n, s.trim_end_matches(')')),
+            None         => continue,
+        };
+
+        for name in names.split(", ") {
+            pages.push(Page {
+                name: name.trim().to_string(),
+                section: section.to_string(),
+                description: description.trim().to_string(),
+            });
+        }
+    }
+
+    pages
+}
+
+// Every page in the index; filtering happens in the launcher so the index
+// is only read once.
+pub fn pages() -> Result<Vec<Page>, String> {
+    cli::output("apropos", &["."]).map(|o| parse_apropos(&o))
+}
+
+pub fn open_command(config: &Config, page: &Page) -> String {
+    let man = format!("man {} {}", shell_quote(&page.section),
+                      shell_quote(&page.name));
+
+    let command = match config.pager {
+        Some(ref p) => format!("env MANPAGER={} {}", shell_quote(p), man),
+        None        => man,
+    };
+
+    cli::in_terminal(&config.terminal, &command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Page, parse_apropos};
+
+    #[test]
+    fn it_parses_apropos_output() {
+        let output = "printf (3)           - formatted output conversion\n\
+                      git-log (1)          - Show commit logs\n\
+                      nothing appropriate.\n\
+                      sh, dash (1)         - command interpreter (shell)\n";
+
+        let pages = parse_apropos(output);
+
+        assert_eq!(pages.len(), 4);
+        assert_eq!(pages[0], Page {
+            name: "printf".to_string(),
+            section: "3".to_string(),
+            description: "formatted output conversion".to_string(),
+        });
+        assert_eq!(pages[3].name, "dash");
+        assert_eq!(pages[3].description, "command interpreter (shell)");
+    }
+}
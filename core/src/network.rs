@@ -0,0 +1,200 @@
+use std::fs;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How long a connectivity answer is trusted before asking again. Providers
+// consult the monitor on every keystroke so this keeps us from spawning a
+// process per character typed.
+const RECHECK_INTERVAL_SECS: u64 = 10;
+
+pub const OFFLINE_HINT: &str = "offline";
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Connectivity {
+    Online,
+    Offline,
+}
+
+// Detection runs gdbus and nmcli, so it happens on a worker thread and
+// callers only ever read the last answer. Clones share that answer.
+#[derive(Default, Clone)]
+pub struct Monitor {
+    state: Arc<Mutex<Option<(Connectivity, Instant)>>>,
+    checking: Arc<AtomicBool>,
+}
+
+impl Monitor {
+    pub fn new() -> Monitor {
+        Monitor::default()
+    }
+
+    // Until the first check comes back we assume we're online, so remote
+    // providers behave as they would without a monitor.
+    pub fn connectivity(&self) -> Connectivity {
+        let last = self.state.lock().ok().and_then(|s| *s);
+        let stale = last.is_none_or(|(_, checked)| {
+            checked.elapsed() >= Duration::from_secs(RECHECK_INTERVAL_SECS)
+        });
+
+        if stale && !self.checking.swap(true, Ordering::SeqCst) {
+            let state = self.state.clone();
+            let checking = self.checking.clone();
+
+            thread::spawn(move || {
+                let c = detect();
+
+                if let Ok(mut s) = state.lock() {
+                    *s = Some((c, Instant::now()));
+                }
+
+                checking.store(false, Ordering::SeqCst);
+            });
+        }
+
+        last.map_or(Connectivity::Online, |(c, _)| c)
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.connectivity() == Connectivity::Online
+    }
+}
+
+// Remote providers call this instead of failing when the network is down, so
+// the user sees a quiet hint rather than an error on every keystroke.
+pub fn offline_description(description: &str) -> String {
+    format!("{} ({})", description, OFFLINE_HINT)
+}
+
+// Ask the desktop portal first (works inside sandboxes), then NetworkManager,
+// and finally fall back to checking whether any real interface is up.
+pub fn detect() -> Connectivity {
+    if let Some(c) = portal_connectivity() {
+        return c;
+    }
+
+    if let Some(c) = network_manager_connectivity() {
+        return c;
+    }
+
+    interface_connectivity()
+}
+
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = match Command::new(program).args(args).output() {
+        Ok (o) => o,
+        Err(_) => return None,
+    };
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn portal_connectivity() -> Option<Connectivity> {
+    let output = command_stdout("gdbus", &[
+        "call", "--session",
+        "--dest", "org.freedesktop.portal.Desktop",
+        "--object-path", "/org/freedesktop/portal/desktop",
+        "--method",
+        "org.freedesktop.portal.NetworkMonitor.GetConnectivity",
+    ]);
+
+    output.and_then(|o| parse_portal_connectivity(&o))
+}
+
+// The portal replies with a GVariant tuple like "(uint32 4,)" where 4 means
+// full network access; anything lower is local-only, limited or a captive
+// portal.
+fn parse_portal_connectivity(output: &str) -> Option<Connectivity> {
+    let value = output.trim().trim_start_matches("(uint32").
+        trim_end_matches(",)").trim();
+
+    match value.parse::<u32>() {
+        Ok(4)  => Some(Connectivity::Online),
+        Ok(_)  => Some(Connectivity::Offline),
+        Err(_) => None,
+    }
+}
+
+fn network_manager_connectivity() -> Option<Connectivity> {
+    let output = command_stdout("nmcli",
+        &["-t", "-f", "CONNECTIVITY", "general"]);
+
+    output.and_then(|o| parse_nmcli_connectivity(&o))
+}
+
+fn parse_nmcli_connectivity(output: &str) -> Option<Connectivity> {
+    match output.trim() {
+        "full"                        => Some(Connectivity::Online),
+        "none" | "portal" | "limited" => Some(Connectivity::Offline),
+        _                             => None,
+    }
+}
+
+fn interface_connectivity() -> Connectivity {
+    let entries = match fs::read_dir("/sys/class/net") {
+        Ok (e) => e,
+        Err(_) => return Connectivity::Online,
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        if entry.file_name() == "lo" {
+            continue;
+        }
+
+        let state = fs::read_to_string(entry.path().join("operstate")).
+            unwrap_or(String::new());
+
+        if state.trim() == "up" {
+            return Connectivity::Online;
+        }
+    }
+
+    Connectivity::Offline
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+    use super::{Connectivity, Monitor, offline_description,
+                parse_portal_connectivity, parse_nmcli_connectivity};
+
+    #[test]
+    fn it_answers_from_the_last_check() {
+        let monitor = Monitor::new();
+        *monitor.state.lock().unwrap() =
+            Some((Connectivity::Offline, Instant::now()));
+
+        assert!(!monitor.is_online());
+        assert!(!monitor.clone().is_online());
+    }
+
+    #[test]
+    fn it_marks_offline_descriptions() {
+        assert_eq!(offline_description("Can't translate"),
+                   "Can't translate (offline)");
+    }
+
+    #[test]
+    fn it_parses_portal_connectivity() {
+        assert_eq!(parse_portal_connectivity("(uint32 4,)\n"),
+                   Some(Connectivity::Online));
+        assert_eq!(parse_portal_connectivity("(uint32 3,)\n"),
+                   Some(Connectivity::Offline));
+        assert_eq!(parse_portal_connectivity("garbage"), None);
+    }
+
+    #[test]
+    fn it_parses_nmcli_connectivity() {
+        assert_eq!(parse_nmcli_connectivity("full\n"),
+                   Some(Connectivity::Online));
+        assert_eq!(parse_nmcli_connectivity("portal\n"),
+                   Some(Connectivity::Offline));
+        assert_eq!(parse_nmcli_connectivity("unknown\n"), None);
+    }
+}
@@ -0,0 +1,461 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+#[derive(Deserialize, PartialEq, Eq, Debug, Clone, Default)]
+pub struct Node {
+    pub shortcut: String,
+    pub description: String,
+    pub command: Option<String>,
+    pub children: Option<Vec<Node>>,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub hidden: bool,
+    pub requires: Option<String>,
+    pub only_on_hosts: Option<Vec<String>>,
+    pub only_if_env: Option<HashMap<String, String>>,
+    pub builtin: Option<String>,
+    pub children_command: Option<String>,
+    pub preview: Option<String>,
+    // Icon theme name, or path to an image, shown at the start of the row
+    pub icon: Option<String>,
+    // Queries replayed in order, each activating its first result
+    pub steps: Option<Vec<String>>,
+    // CSS classes for the node's result row, e.g. "danger" or "work"
+    #[serde(default)]
+    pub classes: Vec<String>,
+    // Keep the launcher open with a progress row until the command finishes
+    #[serde(default)]
+    pub wait: bool,
+    // List first, as a favorite, when nothing is typed
+    #[serde(default)]
+    pub pinned: bool,
+    // Ask for an argument once the shortcut is typed, e.g. "Search for:".
+    // It replaces {query} in the command, or is added to the end.
+    pub prompt: Option<String>,
+    // Run the command through sh -c rather than as a program name, for
+    // commands with arguments or shell syntax, like generated ones
+    #[serde(default)]
+    pub shell: bool,
+}
+
+pub fn is_executable(path: &Path) -> bool {
+    match fs::metadata(path) {
+        Ok (m) => m.is_file() && m.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+pub fn on_path(binary: &str) -> bool {
+    if binary.contains('/') {
+        return is_executable(Path::new(binary));
+    }
+
+    let path = match env::var_os("PATH") {
+        Some(p) => p,
+        None    => return false,
+    };
+
+    env::split_paths(&path).any(|dir| is_executable(&dir.join(binary)))
+}
+
+pub fn hostname() -> String {
+    let raw = fs::read_to_string("/proc/sys/kernel/hostname").
+        or_else(|_| fs::read_to_string("/etc/hostname")).
+        unwrap_or_default();
+
+    raw.trim().to_string()
+}
+
+// Checks a node's host and environment filters against the given hostname
+// and environment variable lookup.
+pub fn conditions_met<F>(node: &Node, host: &str, var: &F) -> bool
+    where F: Fn(&str) -> Option<String> {
+    if let Some(ref hosts) = node.only_on_hosts {
+        if !hosts.iter().any(|h| h == host) {
+            return false;
+        }
+    }
+
+    if let Some(ref vars) = node.only_if_env {
+        for (name, expected) in vars {
+            if var(name).as_ref() != Some(expected) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+pub fn node_available(node: &Node, host: &str) -> bool {
+    if let Some(ref binary) = node.requires {
+        if !on_path(binary) {
+            return false;
+        }
+    }
+
+    conditions_met(node, host, &|name| env::var(name).ok())
+}
+
+// Drops nodes (and their whole subtree) that aren't usable on this machine
+// so a single config can be shared between machines.
+pub fn prune_nodes<F>(nodes: Vec<Node>, keep: &F) -> Vec<Node>
+    where F: Fn(&Node) -> bool {
+    nodes.into_iter().filter(|n| keep(n)).map(|mut n| {
+        n.children = n.children.map(|c| prune_nodes(c, keep));
+        n
+    }).collect()
+}
+
+// Fills every node using the given builtin with generated children.
+pub fn fill_builtin_children(nodes: &mut [Node], builtin: &str,
+                         children: &[Node]) {
+    for n in nodes.iter_mut() {
+        if n.builtin.as_deref() == Some(builtin) {
+            n.children = Some(children.to_vec());
+        } else if let Some(ref mut c) = n.children {
+            fill_builtin_children(c, builtin, children);
+        }
+    }
+}
+
+pub fn borrow_nodes(nodes: &Vec<Node>) -> Vec<&Node> {
+    let mut ret = Vec::new();
+
+    for n in nodes {
+        ret.push(n);
+    }
+
+    ret
+}
+
+// Nodes as shown in a partial listing. Hidden nodes are left out (they can
+// only be reached by typing their full shortcut) and higher priority nodes
+// float to the top. The sort is stable so nodes with equal priority keep
+// their config order.
+pub fn list_nodes(nodes: Vec<&Node>) -> Vec<&Node> {
+    let mut ret: Vec<&Node> = nodes.into_iter().filter(|n| !n.hidden).
+        collect();
+
+    ret.sort_by_key(|n| Reverse(n.priority));
+    ret
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum Resolved<'a> {
+    Partial(Vec<&'a Node>),
+    Complete(&'a Node),
+    Loading(&'a Node),
+}
+
+pub fn resolve<'a>(nodes: Vec<&'a Node>, command: String) -> Resolved<'a> {
+    resolve_path(nodes, command).1
+}
+
+// Resolves a command along with the submenus it descended into on the way,
+// outermost first.
+pub fn resolve_path<'a>(nodes: Vec<&'a Node>, command: String)
+    -> (Vec<&'a Node>, Resolved<'a>) {
+    if command.is_empty() {
+        return (vec![], Resolved::Partial(list_nodes(nodes)));
+    }
+
+    let mut partial = vec![];
+
+    for n in nodes {
+        if n.shortcut.as_str().starts_with(command.as_str()) {
+            partial.push(n);
+        }
+
+        if !command.as_str().starts_with(n.shortcut.as_str()) {
+            continue;
+        }
+
+        // Dynamic children are fetched the first time we descend into them
+        if n.children.is_none() && n.children_command.is_some() {
+            return (vec![], Resolved::Loading(n));
+        }
+
+        let remaining: String = command.chars().skip(n.shortcut.len()).
+            collect();
+
+        let children = match n.children {
+            Some(ref c) => c,
+            None if remaining.is_empty() => {
+                return (vec![], Resolved::Complete(n));
+            },
+            None => return (vec![], Resolved::Partial(vec![])),
+        };
+
+        let (mut path, resolved) = if remaining.is_empty() {
+            (vec![], Resolved::Partial(list_nodes(borrow_nodes(children))))
+        } else {
+            resolve_path(borrow_nodes(children), remaining)
+        };
+
+        path.insert(0, n);
+        return (path, resolved);
+    }
+
+    (vec![], Resolved::Partial(list_nodes(partial)))
+}
+
+// Stores the output of a children_command in every node that uses it. Output
+// that doesn't parse leaves an empty submenu so we don't retry forever.
+pub fn fill_children(nodes: &mut [Node], command: &str, children: &[Node]) {
+    for n in nodes.iter_mut() {
+        if n.children.is_none() &&
+           n.children_command.as_deref() == Some(command) {
+            n.children = Some(children.to_vec());
+        }
+
+        if let Some(ref mut c) = n.children {
+            fill_children(c, command, children);
+        }
+    }
+}
+
+// The children of the submenu at a shortcut path like "web", for using that
+// submenu as the root of the menu. Dynamic children are fetched with `load`.
+pub fn submenu<F>(nodes: &[Node], path: &str, load: &F)
+    -> Result<Vec<Node>, String>
+    where F: Fn(&str) -> Result<Vec<Node>, String> {
+    if path.is_empty() {
+        return Ok(nodes.to_vec());
+    }
+
+    let node = match nodes.iter().find(|n| !n.shortcut.is_empty() &&
+                                           path.starts_with(&n.shortcut)) {
+        Some(n) => n,
+        None    => return Err(format!("No submenu at {}", path)),
+    };
+
+    let children = match (&node.children, &node.children_command) {
+        (Some(c), _)    => c.clone(),
+        (None, Some(c)) => load(c)?,
+        (None, None)    => return Err(format!("{} has no submenu",
+                                              node.shortcut)),
+    };
+
+    submenu(&children, &path[node.shortcut.len()..], load)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::{Node, Resolved, resolve, borrow_nodes, prune_nodes,
+                conditions_met, fill_children, submenu, resolve_path};
+
+    fn test_data() -> Vec<Node> {
+        vec![Node {
+            shortcut: "terminal".to_string(),
+            description: "terminal emulator".to_string(),
+            command: Some("xfce4-terminal".to_string()),
+            children: None,
+            ..Default::default()
+        }, Node {
+            shortcut: "web".to_string(),
+            description: "web browsers".to_string(),
+            command: None,
+            children: Some(vec![Node {
+                shortcut: "chrome".to_string(),
+                description: "Google Chrome".to_string(),
+                command: Some("chromium".to_string()),
+                children: None,
+                ..Default::default()
+            }, Node {
+                shortcut: "firefox".to_string(),
+                description: "Mozilla FireFox".to_string(),
+                command: Some("firefox".to_string()),
+                children: None,
+                priority: 1,
+                ..Default::default()
+            }, Node {
+                shortcut: "tor".to_string(),
+                description: "Tor Browser".to_string(),
+                command: Some("torbrowser-launcher".to_string()),
+                hidden: true,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }]
+    }
+
+    fn expect_partial(command: &str, mut expected: Vec<&str>) {
+        let data = test_data();
+        let nodes = match resolve(borrow_nodes(&data), command.to_string()) {
+            Resolved::Partial(n) => n,
+            _                    => panic!("Expected partial match"),
+        };
+
+        for node in nodes {
+            let p = match expected.iter().position(|&e| e == node.shortcut) {
+                None    => panic!("Unexpected node {}", node.shortcut),
+                Some(p) => p,
+            };
+
+            expected.remove(p);
+        }
+
+        if !expected.is_empty() {
+            panic!("Expected nodes missing: {:?}", expected);
+        }
+    }
+
+    fn expect_complete(command: &str, expected: &str) {
+        let data = test_data();
+        let node = match resolve(borrow_nodes(&data), command.to_string()) {
+            Resolved::Complete(n) => n,
+            _                     => panic!("Expected complete match"),
+        };
+
+        assert_eq!(node.shortcut, expected);
+    }
+
+    #[test]
+    fn it_resolves_no_match_to_empty() {
+        expect_partial("wrong", vec![]);
+    }
+
+    #[test]
+    fn it_resolves_empty_string_to_root_node() {
+        expect_partial("", vec!["web", "terminal"]);
+    }
+
+    #[test]
+    fn it_resolves_partial_first_level() {
+        expect_partial("t", vec!["terminal"]);
+    }
+
+    #[test]
+    fn it_resolves_second_level() {
+        expect_partial("web", vec!["firefox", "chrome"]);
+    }
+
+    #[test]
+    fn it_resolves_partial_second_level() {
+        expect_partial("webchr", vec!["chrome"]);
+    }
+
+    #[test]
+    fn it_resolves_complete_first_level() {
+        expect_complete("terminal", "terminal");
+    }
+
+    #[test]
+    fn it_resolves_complete_second_level() {
+        expect_complete("webfirefox", "firefox");
+    }
+
+    #[test]
+    fn it_omits_hidden_nodes_from_partial() {
+        expect_partial("webt", vec![]);
+    }
+
+    #[test]
+    fn it_resolves_hidden_node_by_exact_match() {
+        expect_complete("webtor", "tor");
+    }
+
+    #[test]
+    fn it_prunes_unavailable_nodes() {
+        let data = prune_nodes(test_data(), &|n: &Node| n.shortcut != "tor");
+
+        assert_eq!(data[1].children.as_ref().unwrap().len(), 2);
+        assert!(data[1].children.as_ref().unwrap().iter().
+            all(|n| n.shortcut != "tor"));
+    }
+
+    #[test]
+    fn it_checks_host_and_env_conditions() {
+        let mut env = HashMap::new();
+        env.insert("XDG_SESSION_TYPE".to_string(), "wayland".to_string());
+
+        let node = Node {
+            only_on_hosts: Some(vec!["laptop".to_string()]),
+            only_if_env: Some(env),
+            ..Default::default()
+        };
+
+        let wayland = |name: &str| match name {
+            "XDG_SESSION_TYPE" => Some("wayland".to_string()),
+            _                  => None,
+        };
+        let x11 = |_: &str| Some("x11".to_string());
+
+        assert!(conditions_met(&node, "laptop", &wayland));
+        assert!(!conditions_met(&node, "desktop", &wayland));
+        assert!(!conditions_met(&node, "laptop", &x11));
+    }
+
+    #[test]
+    fn it_loads_dynamic_children_once() {
+        let mut data = vec![Node {
+            shortcut: "tmux".to_string(),
+            children_command: Some("tmux-sessions".to_string()),
+            ..Default::default()
+        }];
+
+        match resolve(borrow_nodes(&data), "tmuxw".to_string()) {
+            Resolved::Loading(n) => assert_eq!(n.shortcut, "tmux"),
+            _                    => panic!("Expected loading"),
+        };
+
+        fill_children(&mut data, "tmux-sessions", &[Node {
+            shortcut: "work".to_string(),
+            command: Some("tmux attach -t work".to_string()),
+            ..Default::default()
+        }]);
+
+        match resolve(borrow_nodes(&data), "tmuxw".to_string()) {
+            Resolved::Partial(n) => assert_eq!(n[0].shortcut, "work"),
+            _                    => panic!("Expected partial match"),
+        };
+    }
+
+    #[test]
+    fn it_sorts_partial_by_priority() {
+        let data = test_data();
+        let nodes = match resolve(borrow_nodes(&data), "web".to_string()) {
+            Resolved::Partial(n) => n,
+            _                    => panic!("Expected partial match"),
+        };
+
+        let shortcuts: Vec<&str> = nodes.iter().
+            map(|n| n.shortcut.as_str()).collect();
+
+        assert_eq!(shortcuts, vec!["firefox", "chrome"]);
+    }
+
+    #[test]
+    fn it_finds_submenus() {
+        let data = test_data();
+        let load = |_: &str| Err("no dynamic children".to_string());
+
+        let web: Vec<String> = submenu(&data, "web", &load).unwrap().
+            into_iter().map(|n| n.shortcut).collect();
+        assert_eq!(web, vec!["chrome", "firefox", "tor"]);
+
+        assert!(submenu(&data, "terminal", &load).is_err());
+        assert!(submenu(&data, "games", &load).is_err());
+    }
+
+    #[test]
+    fn it_returns_the_resolved_path() {
+        let data = test_data();
+        let path = |command: &str| -> Vec<String> {
+            resolve_path(borrow_nodes(&data), command.to_string()).0.
+                into_iter().map(|n| n.shortcut.clone()).collect()
+        };
+
+        assert!(path("we").is_empty());
+        assert_eq!(path("web"), vec!["web"]);
+        assert_eq!(path("webfi"), vec!["web"]);
+        assert!(path("terminal").is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::process::Command;
+
+use config::{Tools, program};
+use desktop::shell_quote;
+use paths::expand_home;
+use provider;
+
+// How many notes the listing shows before the filter narrows it down.
+pub const RECENT_LIMIT: usize = 20;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_file")]
+    pub file: String,
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // Receives each new note on stdin, as "<timestamp>\t<text>", instead of
+    // it being appended to the file
+    pub command: Option<String>,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        self.command.as_deref().map(program).into_iter().collect()
+    }
+}
+
+fn default_file() -> String {
+    "~/notes.txt".to_string()
+}
+
+fn default_prefix() -> String {
+    "note".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum Input<'a> {
+    Append(&'a str),
+    List(&'a str),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Note {
+    pub line: usize,
+    pub timestamp: String,
+    pub text: String,
+}
+
+// "<prefix> text" appends a note and "<prefix>s [filter]" lists them, so with
+// the default prefix that's "note buy stamps" and "notes stamps".
+pub fn parse_input<'a>(prefix: &str, value: &'a str) -> Option<Input<'a>> {
+    let list = format!("{}s", prefix);
+
+    if let Some(filter) = provider::prefix_filter(value, &list) {
+        return Some(Input::List(filter));
+    }
+
+    provider::prefix_filter(value, prefix).map(Input::Append)
+}
+
+fn timestamp() -> String {
+    let output = Command::new("date").arg("+%Y-%m-%d %H:%M").output();
+
+    match output {
+        Ok (ref o) if o.status.success() =>
+            String::from_utf8_lossy(&o.stdout).trim().to_string(),
+        _ => String::from("????-??-?? ??:??"),
+    }
+}
+
+fn line(text: &str) -> String {
+    format!("{}\t{}\n", timestamp(), text)
+}
+
+// With a command configured, the shell line that hands it the note on
+// stdin. It's run as a job so a slow command doesn't hold up the window.
+pub fn pipe_command(config: &Config, text: &str) -> Option<String> {
+    let command = config.command.as_ref()?;
+    Some(format!("printf %s {} | {}", shell_quote(&line(text)), command))
+}
+
+pub fn append(config: &Config, text: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).
+        open(expand_home(&config.file))?;
+
+    file.write_all(line(text).as_bytes())
+}
+
+fn parse_line(line: usize, raw: &str) -> Option<Note> {
+    let mut parts = raw.splitn(2, '\t');
+
+    let timestamp = parts.next().unwrap_or("");
+    let text = parts.next()?;
+
+    Some(Note {
+        line,
+        timestamp: timestamp.to_string(),
+        text: text.to_string(),
+    })
+}
+
+fn parse(contents: &str) -> Vec<Note> {
+    contents.lines().enumerate().
+        filter_map(|(i, l)| parse_line(i, l)).collect()
+}
+
+// Newest notes first, narrowed to those containing `filter`.
+pub fn recent(path: &str, filter: &str) -> Vec<Note> {
+    let contents = fs::read_to_string(expand_home(path)).
+        unwrap_or_default();
+
+    filter_recent(parse(&contents), filter)
+}
+
+fn filter_recent(notes: Vec<Note>, filter: &str) -> Vec<Note> {
+    let filter = filter.to_lowercase();
+
+    notes.into_iter().rev().
+        filter(|n| n.text.to_lowercase().contains(&filter)).
+        take(RECENT_LIMIT).collect()
+}
+
+// Rewrites the file without the given note. The new contents are written to
+// a temporary file first so a crash can't leave the notes half-written.
+pub fn delete(path: &str, note: &Note) -> io::Result<()> {
+    let path = expand_home(path);
+    let contents = fs::read_to_string(&path)?;
+
+    let mut kept = String::new();
+    for (i, line) in contents.lines().enumerate() {
+        if i != note.line {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+
+    let temp = format!("{}.tmp", path);
+    fs::write(&temp, kept)?;
+    fs::rename(&temp, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use super::{Config, Input, Note, parse_input, parse, filter_recent,
+                pipe_command};
+
+    #[test]
+    fn it_parses_note_input() {
+        assert_eq!(parse_input("note", "note buy stamps"),
+                   Some(Input::Append("buy stamps")));
+        assert_eq!(parse_input("note", "notes"), Some(Input::List("")));
+        assert_eq!(parse_input("note", "notes stamps"),
+                   Some(Input::List("stamps")));
+        assert_eq!(parse_input("note", "notebook"), None);
+        assert_eq!(parse_input("note", "web"), None);
+    }
+
+    #[test]
+    fn it_pipes_notes_into_the_command() {
+        let mut config = Config {
+            file: "~/notes.txt".to_string(),
+            prefix: "note".to_string(),
+            command: None,
+        };
+
+        assert_eq!(pipe_command(&config, "buy stamps"), None);
+
+        config.command = Some("cut -f 2".to_string());
+        let command = pipe_command(&config, "it's $HOME").unwrap();
+        let output = Command::new("sh").arg("-c").arg(&command).output().
+            unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "it's $HOME\n");
+    }
+
+    #[test]
+    fn it_lists_newest_matching_notes_first() {
+        let notes = parse("2017-01-01 10:00\tbuy stamps\n\
+                           2017-01-02 11:00\tcall bob\n\
+                           2017-01-03 12:00\tbuy milk\n");
+
+        let found = filter_recent(notes, "buy");
+
+        assert_eq!(found, vec![Note {
+            line: 2,
+            timestamp: "2017-01-03 12:00".to_string(),
+            text: "buy milk".to_string(),
+        }, Note {
+            line: 0,
+            timestamp: "2017-01-01 10:00".to_string(),
+            text: "buy stamps".to_string(),
+        }]);
+    }
+}
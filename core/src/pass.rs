@@ -0,0 +1,85 @@
+// Entries in a pass (passwordstore.org) store. Only entry names are ever
+// read here; the secrets stay with pass, which decrypts them straight to
+// the clipboard or to the typing tool.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use config::Tools;
+use desktop::{self, shell_quote};
+use files;
+use paths::expand_home;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // Type the password into the focused window instead of copying it
+    #[serde(default)]
+    pub type_it: bool,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["pass"]
+    }
+}
+
+fn default_prefix() -> String {
+    "pass".to_string()
+}
+
+pub fn store_dir() -> PathBuf {
+    env::var_os("PASSWORD_STORE_DIR").map(PathBuf::from).
+        unwrap_or_else(|| PathBuf::from(expand_home("~/.password-store")))
+}
+
+// Entry names like "web/github" for every .gpg file under the store.
+pub fn entries(store: &Path) -> Vec<String> {
+    let mut found = vec![];
+
+    files::walk(store, &mut |path| {
+        if path.extension().is_none_or(|e| e != "gpg") {
+            return;
+        }
+
+        if let Ok(relative) = path.with_extension("").strip_prefix(store) {
+            found.push(relative.to_string_lossy().into_owned());
+        }
+    });
+
+    found.sort();
+    found
+}
+
+// pass copies the first line and clears the clipboard again after a while.
+pub fn copy_command(entry: &str) -> String {
+    format!("pass show -c {}", shell_quote(entry))
+}
+
+pub fn type_command(entry: &str) -> String {
+    desktop::type_output_command(&format!(
+        "pass show {} | head -n 1 | tr -d '\\n'", shell_quote(entry)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use super::{copy_command, entries};
+
+    #[test]
+    fn it_lists_entries() {
+        let store = std::env::temp_dir().join("blaunch-pass-test");
+        let _ = fs::remove_dir_all(&store);
+        fs::create_dir_all(store.join("web")).unwrap();
+        fs::create_dir_all(store.join(".git")).unwrap();
+        fs::write(store.join("web/github.gpg"), "").unwrap();
+        fs::write(store.join("mail.gpg"), "").unwrap();
+        fs::write(store.join(".gpg-id"), "").unwrap();
+        fs::write(store.join(".git/config.gpg"), "").unwrap();
+
+        assert_eq!(entries(&store), vec!["mail", "web/github"]);
+        assert_eq!(copy_command("web/github"), "pass show -c 'web/github'");
+        let _ = fs::remove_dir_all(&store);
+    }
+}
@@ -0,0 +1,32 @@
+use std::env;
+use std::path::PathBuf;
+
+pub fn expand_home(path: &str) -> String {
+    if !path.starts_with("~/") {
+        return path.to_string();
+    }
+
+    match env::var("HOME") {
+        Ok (home) => format!("{}{}", home, &path[1..]),
+        Err(_)    => path.to_string(),
+    }
+}
+
+// The user's own XDG data directory.
+pub fn data_home() -> PathBuf {
+    PathBuf::from(env::var("XDG_DATA_HOME").
+        unwrap_or_else(|_| expand_home("~/.local/share")))
+}
+
+// XDG data directories, most specific first: the user's own, then the
+// system ones.
+pub fn data_dirs() -> Vec<PathBuf> {
+    let system = env::var("XDG_DATA_DIRS").
+        unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+
+    let mut dirs = vec![data_home()];
+    dirs.extend(system.split(':').filter(|d| !d.is_empty()).
+        map(PathBuf::from));
+
+    dirs
+}
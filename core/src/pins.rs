@@ -0,0 +1,147 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use node::Node;
+use paths::expand_home;
+use provider::{Action, encode_action, decode_action};
+use providers::menu::node_action;
+
+// Favorites listed first when nothing is typed. Nodes can be pinned in the
+// config with "pinned": true, and any result can be pinned or unpinned at
+// runtime with Ctrl+P, which is saved here.
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_file")]
+    pub file: String,
+}
+
+fn default_file() -> String {
+    "~/.local/share/blaunch/pins".to_string()
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config { file: default_file() }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Pin {
+    pub title: String,
+    pub action: Action,
+}
+
+// Lines are "title, action", tab separated. Lines that don't parse are
+// skipped.
+pub fn load(path: &str) -> Vec<Pin> {
+    fs::read_to_string(expand_home(path)).unwrap_or_default().lines().
+        filter_map(|l| {
+            let mut parts = l.splitn(2, '\t');
+            let title = parts.next()?.to_string();
+            let action = decode_action(parts.next()?)?;
+
+            Some(Pin { title, action })
+        }).collect()
+}
+
+// Pins the action, or unpins it if it's already pinned. Returns whether it's
+// pinned now.
+pub fn toggle(path: &str, title: &str, action: &Action) -> io::Result<bool> {
+    if encode_action(action).is_none() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  "This can't be pinned"));
+    }
+
+    let mut pins = load(path);
+    let pinned = match pins.iter().position(|p| p.action == *action) {
+        Some(i) => {
+            pins.remove(i);
+            false
+        },
+        None => {
+            pins.push(Pin { title: title.to_string(), action: action.clone() });
+            true
+        },
+    };
+
+    let path = expand_home(path);
+    if let Some(dir) = Path::new(&path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let lines: Vec<String> = pins.iter().filter_map(|p| {
+        encode_action(&p.action).
+            map(|a| format!("{}\t{}\n", p.title.replace('\t', " "), a))
+    }).collect();
+
+    fs::write(&path, lines.concat())?;
+    Ok(pinned)
+}
+
+// Nodes pinned in the config, anywhere in the menu, titled by their full
+// shortcut path.
+pub fn flagged(nodes: &[Node]) -> Vec<Pin> {
+    let mut ret = vec![];
+    collect_flagged(nodes, "", &mut ret);
+    ret
+}
+
+fn collect_flagged(nodes: &[Node], path: &str, pins: &mut Vec<Pin>) {
+    for n in nodes {
+        let shortcut = format!("{}{}", path, n.shortcut);
+
+        if n.pinned {
+            if let Some(action) = node_action(n) {
+                pins.push(Pin { title: shortcut.clone(), action });
+            }
+        }
+
+        if let Some(ref c) = n.children {
+            collect_flagged(c, &shortcut, pins);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+    use node::Node;
+    use provider::Action;
+    use super::{flagged, load, toggle};
+
+    #[test]
+    fn it_toggles_pins() {
+        let path = env::temp_dir().
+            join(format!("blaunch-pins-test.{}", process::id()));
+        let path = path.to_str().unwrap();
+        let action = Action::Spawn("firefox".to_string());
+
+        assert!(toggle(path, "f", &action).unwrap());
+        assert_eq!(load(path)[0].title, "f");
+        assert!(!toggle(path, "f", &action).unwrap());
+        assert!(load(path).is_empty());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn it_finds_pinned_nodes() {
+        let nodes = vec![Node {
+            shortcut: "w".to_string(),
+            children: Some(vec![Node {
+                shortcut: "f".to_string(),
+                command: Some("firefox".to_string()),
+                pinned: true,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }];
+
+        let pins = flagged(&nodes);
+        assert_eq!(pins.len(), 1);
+        assert_eq!(pins[0].title, "wf");
+    }
+}
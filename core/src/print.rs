@@ -0,0 +1,64 @@
+use provider::{Action, Item};
+
+// What `blaunch --print` writes to stdout for the chosen entry instead of
+// running it, so blaunch can be used as a chooser in shell pipelines.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Field {
+    // The command line (or builtin, copied text or macro steps)
+    Command,
+    Title,
+    // The shortcut path typed to reach the entry
+    Shortcut,
+}
+
+// Parses the part after `--print=`. Plain `--print` is the command.
+pub fn parse_field(name: &str) -> Result<Field, String> {
+    match name {
+        "" | "command" => Ok(Field::Command),
+        "title"        => Ok(Field::Title),
+        "shortcut"     => Ok(Field::Shortcut),
+        _              => Err(format!("Unknown --print field {}", name)),
+    }
+}
+
+// The text to print for an item chosen with `input` typed in the entry.
+// Macros print one step per line.
+pub fn selection(field: Field, item: &Item, input: &str) -> Option<String> {
+    match field {
+        Field::Title    => Some(item.title.clone()),
+        Field::Shortcut => Some(input.to_string()),
+        Field::Command  => match item.action {
+            Some(Action::Spawn(ref c))    => Some(c.clone()),
+            Some(Action::Exec(ref p))     => Some(p.clone()),
+            Some(Action::Builtin(ref b))  => Some(b.clone()),
+            Some(Action::Copy(ref t))     => Some(t.clone()),
+            Some(Action::Macro(ref s))    => Some(s.join("\n")),
+            Some(Action::Activate(ref w)) => Some(w.clone()),
+            Some(Action::Query(_))        => None,
+            None                          => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use provider::{Action, Item};
+    use super::{Field, parse_field, selection};
+
+    #[test]
+    fn it_prints_the_chosen_field() {
+        let item = Item::new("Firefox", "", Action::Spawn("firefox".into()));
+
+        assert_eq!(parse_field(""), Ok(Field::Command));
+        assert_eq!(parse_field("shortcut"), Ok(Field::Shortcut));
+        assert!(parse_field("color").is_err());
+
+        assert_eq!(selection(Field::Command, &item, "wf"),
+                   Some("firefox".to_string()));
+        assert_eq!(selection(Field::Title, &item, "wf"),
+                   Some("Firefox".to_string()));
+        assert_eq!(selection(Field::Shortcut, &item, "wf"),
+                   Some("wf".to_string()));
+        assert_eq!(selection(Field::Command, &Item::message("hi"), ""), None);
+    }
+}
@@ -0,0 +1,157 @@
+// Running processes, read from /proc.
+
+use std::cmp::Reverse;
+use std::fs;
+
+// Clock ticks per second that /proc counts CPU time in. It's 100 on every
+// Linux port that matters.
+const TICKS: u64 = 100;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+fn default_prefix() -> String {
+    "kill".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Process {
+    pub pid: u32,
+    pub name: String,
+    pub uid: u32,
+    // Average CPU use over the process's lifetime, in tenths of a percent
+    pub cpu: u32,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Stat {
+    pub name: String,
+    // User plus system time, in ticks
+    pub time: u64,
+    // Ticks after boot that the process started
+    pub start: u64,
+}
+
+// /proc/<pid>/stat is "pid (name) state ppid ...", where the name can
+// contain spaces and brackets, so fields are counted from the last ")".
+pub fn parse_stat(contents: &str) -> Option<Stat> {
+    let open = contents.find('(')?;
+    let close = contents.rfind(')')?;
+    let fields: Vec<&str> = contents[close + 1..].split_whitespace().
+        collect();
+
+    // utime, stime and starttime are fields 14, 15 and 22, counting the
+    // pid as 1 and the name as 2
+    let field = |n: usize| fields.get(n - 3)?.parse::<u64>().ok();
+
+    Some(Stat {
+        name: contents[open + 1..close].to_string(),
+        time: field(14)? + field(15)?,
+        start: field(22)?,
+    })
+}
+
+// The real user id from a /proc/<pid>/status "Uid:" line.
+pub fn parse_uid(status: &str) -> Option<u32> {
+    status.lines().find_map(|l| l.strip_prefix("Uid:")).
+        and_then(|ids| ids.split_whitespace().next()?.parse().ok())
+}
+
+// Tenths of a percent of one CPU used since the process started.
+pub fn cpu(stat: &Stat, uptime_secs: f64) -> u32 {
+    let alive = uptime_secs - stat.start as f64 / TICKS as f64;
+
+    if alive <= 0.0 {
+        return 0;
+    }
+
+    (stat.time as f64 / TICKS as f64 / alive * 1000.0).round() as u32
+}
+
+pub fn current_uid() -> Option<u32> {
+    fs::read_to_string("/proc/self/status").ok().
+        and_then(|s| parse_uid(&s))
+}
+
+// Every process, busiest first. Kernel threads and processes that exit
+// while being read are left out.
+pub fn list() -> Vec<Process> {
+    let uptime = fs::read_to_string("/proc/uptime").ok().
+        and_then(|u| u.split_whitespace().next()?.parse::<f64>().ok()).
+        unwrap_or(0.0);
+
+    let entries = match fs::read_dir("/proc") {
+        Ok (e) => e,
+        Err(_) => return vec![],
+    };
+
+    let mut processes: Vec<Process> = entries.filter_map(|e| e.ok()).
+        filter_map(|e| {
+            let pid = e.file_name().to_str()?.parse::<u32>().ok()?;
+            let dir = e.path();
+
+            // Kernel threads have no command line
+            if fs::read(dir.join("cmdline")).ok()?.is_empty() {
+                return None;
+            }
+
+            let stat = parse_stat(&fs::read_to_string(dir.join("stat")).
+                                  ok()?)?;
+            let uid = parse_uid(&fs::read_to_string(dir.join("status")).
+                                ok()?)?;
+
+            Some(Process {
+                pid,
+                cpu: cpu(&stat, uptime),
+                name: stat.name,
+                uid,
+            })
+        }).collect();
+
+    processes.sort_by_key(|p| Reverse(p.cpu));
+    processes
+}
+
+// Someone else's processes need root, which pkexec asks for.
+pub fn kill_command(pid: u32, signal: &str, own: bool) -> String {
+    let kill = format!("kill -{} {}", signal, pid);
+
+    if own {
+        kill
+    } else {
+        format!("pkexec {}", kill)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stat, cpu, kill_command, parse_stat, parse_uid};
+
+    #[test]
+    fn it_parses_proc_files() {
+        let stat = "4242 (Web Content) S 1 2 3 4 5 6 7 8 9 10 150 50 0 0 \
+                    20 0 1 0 1000 0 0";
+
+        assert_eq!(parse_stat(stat), Some(Stat {
+            name: "Web Content".to_string(),
+            time: 200,
+            start: 1000,
+        }));
+        assert_eq!(parse_stat("garbage"), None);
+
+        assert_eq!(parse_uid("Name:\tbash\nUid:\t1000\t1000\t1000\t1000\n"),
+                   Some(1000));
+    }
+
+    #[test]
+    fn it_averages_cpu_use() {
+        let stat = Stat { name: "x".to_string(), time: 200, start: 1000 };
+
+        // 2 seconds of CPU over the 20 seconds since it started at 10
+        assert_eq!(cpu(&stat, 30.0), 100);
+        assert_eq!(kill_command(7, "TERM", false), "pkexec kill -TERM 7");
+    }
+}
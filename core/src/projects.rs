@@ -0,0 +1,95 @@
+use std::env;
+
+use desktop::shell_quote;
+use node::on_path;
+
+// A set of programs opened together, like an editor, a terminal and a
+// browser for one project. Under i3 or sway each window can go to its own
+// workspace with a layout; elsewhere the commands are just started.
+#[derive(Deserialize, Clone)]
+pub struct Project {
+    pub name: String,
+    // Workspace for windows that don't name their own
+    pub workspace: Option<String>,
+    // i3/sway layout for the workspace: splith, splitv, tabbed or stacking
+    pub layout: Option<String>,
+    pub windows: Vec<Window>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Window {
+    pub command: String,
+    pub workspace: Option<String>,
+}
+
+// The i3-compatible IPC client for the running window manager, if any.
+pub fn ipc_tool() -> Option<&'static str> {
+    if env::var_os("SWAYSOCK").is_some() && on_path("swaymsg") {
+        return Some("swaymsg");
+    }
+
+    if env::var_os("I3SOCK").is_some() || on_path("i3-msg") {
+        return Some("i3-msg");
+    }
+
+    None
+}
+
+// Shell command line that opens the project. Windows are started one by one
+// with a short pause, so each maps on its workspace before the next switch.
+pub fn launch_command(project: &Project, tool: Option<&str>) -> String {
+    let tool = match tool {
+        Some(t) => t,
+        None    => {
+            let commands: Vec<String> = project.windows.iter().
+                map(|w| format!("{} &", w.command)).collect();
+            return commands.join(" ");
+        },
+    };
+
+    let steps: Vec<String> = project.windows.iter().map(|w| {
+        let mut parts = vec![];
+
+        if let Some(ws) = w.workspace.as_ref().or(project.workspace.as_ref()) {
+            parts.push(format!("workspace {}", ws));
+        }
+
+        if let Some(ref l) = project.layout {
+            parts.push(format!("layout {}", l));
+        }
+
+        parts.push(format!("exec {}", w.command));
+
+        format!("{} {}", tool, shell_quote(&parts.join("; ")))
+    }).collect();
+
+    steps.join("; sleep 0.3; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Project, Window, launch_command};
+
+    #[test]
+    fn it_builds_project_commands() {
+        let project = Project {
+            name: "blog".to_string(),
+            workspace: Some("3".to_string()),
+            layout: Some("splith".to_string()),
+            windows: vec![
+                Window { command: "code ~/blog".to_string(), workspace: None },
+                Window {
+                    command: "firefox".to_string(),
+                    workspace: Some("4".to_string()),
+                },
+            ],
+        };
+
+        assert_eq!(launch_command(&project, Some("i3-msg")),
+                   "i3-msg 'workspace 3; layout splith; exec code ~/blog'; \
+                    sleep 0.3; \
+                    i3-msg 'workspace 4; layout splith; exec firefox'");
+        assert_eq!(launch_command(&project, None),
+                   "code ~/blog & firefox &");
+    }
+}
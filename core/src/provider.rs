@@ -0,0 +1,419 @@
+use std::time::Duration;
+
+use builtins;
+use cache::Cache;
+use desktop::shell_quote;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Action {
+    // Shell command line, run through sh -c
+    Spawn(String),
+    // Program started directly, without a shell, like a menu node's command
+    Exec(String),
+    Builtin(String),
+    // Text for the clipboard. The clipboard belongs to the toolkit so the
+    // frontend carries this one out.
+    Copy(String),
+    // Queries to replay through the registry, recorded as a macro
+    Macro(Vec<String>),
+    // X window id to bring to the front instead of starting anything
+    Activate(String),
+    // Replace the query with this instead of closing, for a step like
+    // confirming something
+    Query(String),
+}
+
+// One result row. `title` and `description` are the left and right columns.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Item {
+    pub title: String,
+    pub description: String,
+    pub action: Option<Action>,
+    pub preview: Option<String>,
+    // Icon theme name or image path, shown before the title
+    pub icon: Option<String>,
+    // Activate without waiting for Enter, like a fully typed menu shortcut
+    pub immediate: bool,
+    // Placeholder shown while a provider fetches results in the background
+    pub loading: bool,
+    // Extra CSS classes for the row so themes can color-code entries
+    pub classes: Vec<String>,
+    // Run the action as a job with a progress row instead of closing
+    pub wait: bool,
+    // Heading that starts a group of results, like "Recent"
+    pub section: bool,
+    // Query that leads to this item, like a submenu's full shortcut path,
+    // filled in when the row is clicked
+    pub completion: Option<String>,
+    // What the launch_alternate key (Ctrl+Enter) does instead, like
+    // killing a process outright rather than asking it to quit
+    pub alternate: Option<Action>,
+    // Longer text under the title, wrapped over as many lines as it needs,
+    // like a definition
+    pub body: Option<String>,
+    // Color drawn as a small square before the title, as RGBA
+    pub swatch: Option<[u8; 4]>,
+    // Dark and light cells drawn as a picture under the title, like the
+    // modules of a QR code
+    pub bitmap: Option<Vec<Vec<bool>>>,
+    // Asks for an argument before running the action, like "Search for:";
+    // see answer_prompt
+    pub prompt: Option<String>,
+}
+
+impl Item {
+    pub fn new(title: &str, description: &str, action: Action) -> Item {
+        Item {
+            title: title.to_string(),
+            description: description.to_string(),
+            action: Some(action),
+            ..Default::default()
+        }
+    }
+
+    pub fn message(text: &str) -> Item {
+        Item {
+            title: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn section(text: &str) -> Item {
+        Item {
+            section: true,
+            classes: vec!["section".to_string()],
+            ..Item::message(text)
+        }
+    }
+
+    pub fn loading(text: &str) -> Item {
+        Item {
+            loading: true,
+            ..Item::message(text)
+        }
+    }
+}
+
+// Actions as one line of text, for the history log and pins. Copies,
+// macros and window switches aren't stored.
+pub fn encode_action(action: &Action) -> Option<String> {
+    let encoded = match *action {
+        Action::Spawn(ref c)   => format!("spawn:{}", c),
+        Action::Exec(ref p)    => format!("exec:{}", p),
+        Action::Builtin(ref b) => format!("builtin:{}", b),
+        _                      => return None,
+    };
+
+    if encoded.contains('\t') || encoded.contains('\n') {
+        return None;
+    }
+
+    Some(encoded)
+}
+
+pub fn decode_action(raw: &str) -> Option<Action> {
+    if let Some(c) = raw.strip_prefix("spawn:") {
+        return Some(Action::Spawn(c.to_string()));
+    }
+
+    if let Some(p) = raw.strip_prefix("exec:") {
+        return Some(Action::Exec(p.to_string()));
+    }
+
+    raw.strip_prefix("builtin:").map(|b| Action::Builtin(b.to_string()))
+}
+
+// What an action will run, spelled out for the status line under the
+// results.
+pub fn command_line(action: &Action) -> String {
+    match *action {
+        Action::Spawn(ref c)    => c.clone(),
+        Action::Exec(ref p)     => p.clone(),
+        Action::Builtin(ref b)  => format!("builtin {}", b),
+        Action::Copy(ref t)     => format!("copy {}", t),
+        Action::Macro(ref s)    => format!("macro {}", s.join(" \u{2192} ")),
+        Action::Activate(ref w) => format!("activate window {}", w),
+        Action::Query(ref q)    => format!("go to {}", q),
+    }
+}
+
+// Fills a prompt's answer into an action: in place of {query} where there
+// is one, otherwise after the command or builtin as its last argument.
+// Commands get it shell-quoted; a program run without a shell gets it as
+// its only argument.
+pub fn answer_prompt(action: &Action, answer: &str) -> Action {
+    let fill = |template: &str, value: &str| {
+        if template.contains("{query}") {
+            template.replace("{query}", value)
+        } else {
+            format!("{} {}", template, value)
+        }
+    };
+
+    match *action {
+        Action::Spawn(ref c)   => Action::Spawn(fill(c, &shell_quote(answer))),
+        Action::Exec(ref p)    => Action::Spawn(format!("{} {}",
+            shell_quote(p), shell_quote(answer))),
+        Action::Builtin(ref b) => Action::Builtin(fill(b, answer)),
+        Action::Macro(ref s)   => Action::Macro(s.iter().
+            map(|s| s.replace("{query}", answer)).collect()),
+        ref a                  => a.clone(),
+    }
+}
+
+// The answer filled in, once it's something the action can take: not
+// empty, and for a builtin, arguments it understands.
+pub fn checked_answer(action: &Action, answer: &str) -> Result<Action, String> {
+    if answer.trim().is_empty() {
+        return Err("Type an answer first".to_string());
+    }
+
+    let action = answer_prompt(action, answer.trim());
+
+    if let Action::Builtin(ref b) = action {
+        builtins::check(b)?;
+    }
+
+    Ok(action)
+}
+
+// The confirm step for something drastic: picking the item goes to
+// "<prefix>! <what>", where the provider lists the real action instead.
+pub fn confirm(prefix: &str, what: &str) -> Action {
+    Action::Query(format!("{}! {}", prefix, what))
+}
+
+// What's being confirmed, when the input is a confirm step for `prefix`.
+pub fn confirming<'a>(prefix: &str, input: &'a str) -> Option<&'a str> {
+    input.strip_prefix(prefix)?.strip_prefix('!').map(|w| w.trim())
+}
+
+// The text after a word prefix like "kill", trimmed. The prefix has to be
+// the whole input or be followed by a space, so "kill" doesn't also claim
+// "killall".
+pub fn prefix_filter<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    prefix_rest(input, prefix).map(|r| r.trim_end())
+}
+
+// The same, keeping trailing space, for providers where "<name> " moves on
+// to picking a verb for that name.
+pub fn prefix_rest<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    match input.strip_prefix(prefix) {
+        Some(rest) if rest.is_empty() ||
+                      rest.starts_with(' ') => Some(rest.trim_start()),
+        _                                => None,
+    }
+}
+
+pub trait Provider {
+    fn query(&self, input: &str) -> Vec<Item>;
+
+    // How long results for a given query stay valid. Zero (the default)
+    // means the provider is asked on every keystroke. Results that are
+    // still loading or have nothing to launch, like an error, are asked for
+    // again regardless.
+    fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(0)
+    }
+
+    // The submenus the input has descended into, shown as a breadcrumb.
+    // Only the menu has submenus.
+    fn path(&self, _input: &str) -> Vec<String> {
+        vec![]
+    }
+
+    // Called periodically by the frontend. Providers that work in the
+    // background return true when new results arrived, which drops their
+    // cache and makes the frontend query again.
+    fn poll(&self) -> bool {
+        false
+    }
+}
+
+struct Entry {
+    provider: Box<dyn Provider>,
+    cache: Cache<Vec<Item>>,
+}
+
+// A placeholder is replaced once the provider's background work is done,
+// and errors or offline hints should go away as soon as they're fixed.
+fn cacheable(items: &[Item]) -> bool {
+    !items.iter().any(|i| i.loading) && items.iter().all(|i| {
+        i.action.is_some() || i.section
+    })
+}
+
+// Merges results from every registered provider in registration order.
+#[derive(Default)]
+pub struct Registry {
+    entries: Vec<Entry>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry { entries: vec![] }
+    }
+
+    pub fn add(&mut self, provider: Box<dyn Provider>) {
+        let cache = Cache::new(provider.cache_ttl());
+
+        self.entries.push(Entry { provider, cache });
+    }
+
+    pub fn query(&mut self, input: &str) -> Vec<Item> {
+        let mut ret = vec![];
+
+        for entry in self.entries.iter_mut() {
+            let items = match entry.cache.get(input) {
+                Some(i) => i,
+                None    => {
+                    let i = entry.provider.query(input);
+
+                    if cacheable(&i) {
+                        entry.cache.insert(input, i.clone());
+                    }
+
+                    i
+                },
+            };
+
+            ret.extend(items);
+        }
+
+        ret
+    }
+
+    // The first path any provider has for the input.
+    pub fn path(&self, input: &str) -> Vec<String> {
+        self.entries.iter().map(|e| e.provider.path(input)).
+            find(|p| !p.is_empty()).unwrap_or_default()
+    }
+
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+
+        for entry in self.entries.iter_mut() {
+            if entry.provider.poll() {
+                entry.cache.clear();
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+    use super::{Action, Item, Provider, Registry, answer_prompt,
+                checked_answer, command_line, prefix_filter,
+                prefix_rest};
+
+    struct Echo;
+
+    impl Provider for Echo {
+        fn query(&self, input: &str) -> Vec<Item> {
+            vec![Item::new(input, "echo", Action::Copy(input.to_string()))]
+        }
+    }
+
+    struct Counting {
+        calls: Cell<usize>,
+    }
+
+    impl Provider for Counting {
+        fn query(&self, input: &str) -> Vec<Item> {
+            self.calls.set(self.calls.get() + 1);
+
+            if input == "error" {
+                return vec![Item::message("error")];
+            }
+
+            let calls = self.calls.get().to_string();
+            vec![Item::new(&calls, "counting", Action::Copy(calls.clone()))]
+        }
+
+        fn cache_ttl(&self) -> Duration {
+            Duration::from_secs(60)
+        }
+    }
+
+    #[test]
+    fn it_spells_out_actions() {
+        assert_eq!(command_line(&Action::Spawn("xterm -e htop".to_string())),
+                   "xterm -e htop");
+        assert_eq!(command_line(&Action::Macro(vec!["wf".to_string(),
+                                                    "t".to_string()])),
+                   "macro wf \u{2192} t");
+    }
+
+    #[test]
+    fn it_fills_in_prompt_answers() {
+        let spawn = |c: &str| Action::Spawn(c.to_string());
+
+        assert_eq!(answer_prompt(&spawn("man {query} | less"), "git log"),
+                   spawn("man 'git log' | less"));
+        assert_eq!(answer_prompt(&spawn("ssh"), "host"), spawn("ssh 'host'"));
+        assert_eq!(answer_prompt(&Action::Builtin("timer start".to_string()),
+                                 "600"),
+                   Action::Builtin("timer start 600".to_string()));
+    }
+
+    #[test]
+    fn it_checks_prompt_answers() {
+        let timer = Action::Builtin("timer start".to_string());
+
+        assert_eq!(checked_answer(&timer, " 600 "),
+                   Ok(Action::Builtin("timer start 600".to_string())));
+        assert!(checked_answer(&timer, "abc").is_err());
+        assert!(checked_answer(&timer, "  ").is_err());
+        assert!(checked_answer(&Action::Builtin("reboot now".to_string()),
+                               "please").is_err());
+    }
+
+    #[test]
+    fn it_filters_after_word_prefixes() {
+        assert_eq!(prefix_filter("kill", "kill"), Some(""));
+        assert_eq!(prefix_filter("kill  fire fox ", "kill"), Some("fire fox"));
+        assert_eq!(prefix_filter("killall", "kill"), None);
+        assert_eq!(prefix_filter("firefox", "kill"), None);
+        assert_eq!(prefix_rest("dk web ", "dk"), Some("web "));
+    }
+
+    #[test]
+    fn it_merges_providers_in_order() {
+        let mut registry = Registry::new();
+        registry.add(Box::new(Echo));
+        registry.add(Box::new(Counting { calls: Cell::new(0) }));
+
+        let titles: Vec<String> = registry.query("hi").into_iter().
+            map(|i| i.title).collect();
+
+        assert_eq!(titles, vec!["hi", "1"]);
+    }
+
+    #[test]
+    fn it_caches_results_per_provider() {
+        let mut registry = Registry::new();
+        registry.add(Box::new(Counting { calls: Cell::new(0) }));
+
+        registry.query("hi");
+        let items = registry.query(" hi ");
+
+        assert_eq!(items[0].title, "1");
+    }
+
+    #[test]
+    fn it_asks_again_after_errors() {
+        let mut registry = Registry::new();
+        registry.add(Box::new(Counting { calls: Cell::new(0) }));
+
+        registry.query("error");
+        registry.query("error");
+        let items = registry.query("hi");
+
+        assert_eq!(items[0].title, "3");
+    }
+}
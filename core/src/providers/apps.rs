@@ -0,0 +1,260 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use cli;
+use icons;
+use paths;
+use provider::{self, Action, Item, Provider};
+use sandboxed;
+
+// How long a scan is used before looking for newly installed applications
+const REFRESH_SECS: u64 = 300;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    // Only list applications after this prefix, e.g. "app firefox". Without
+    // one they're mixed in with the menu whenever something is typed.
+    pub prefix: Option<String>,
+    #[serde(default = "default_terminal")]
+    pub terminal: String,
+}
+
+fn default_terminal() -> String {
+    "xterm".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct App {
+    pub name: String,
+    pub exec: String,
+    pub icon: Option<String>,
+    pub terminal: bool,
+}
+
+// Installed applications from XDG .desktop files, along with Flatpak and
+// Snap applications, scanned the first time they're needed and again when
+// the scan gets old.
+pub struct Apps {
+    config: Config,
+    apps: RefCell<Option<(Instant, Vec<App>)>>,
+}
+
+impl Apps {
+    pub fn new(config: Config) -> Apps {
+        Apps {
+            config,
+            apps: RefCell::new(None),
+        }
+    }
+
+    fn item(&self, app: &App) -> Item {
+        let command = if app.terminal {
+            cli::in_terminal(&self.config.terminal, &app.exec)
+        } else {
+            app.exec.clone()
+        };
+
+        let description = sandboxed::source(&app.exec).
+            unwrap_or("application");
+
+        Item {
+            icon: Some(app.icon.clone().
+                unwrap_or_else(|| icons::APPLICATION.to_string())),
+            ..Item::new(&app.name, description, Action::Spawn(command))
+        }
+    }
+}
+
+// Drops the field codes (%f, %U, ..) that a launcher is supposed to fill
+// in with files or URLs, since we never pass any.
+pub fn strip_field_codes(exec: &str) -> String {
+    let mut ret = String::new();
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            ret.push(c);
+            continue;
+        }
+
+        if let Some('%') = chars.next() {
+            ret.push('%');
+        }
+    }
+
+    // Flatpak wraps file arguments in @@ markers for --file-forwarding
+    ret.split_whitespace().filter(|w| !w.starts_with("@@")).
+        collect::<Vec<&str>>().join(" ")
+}
+
+// Reads the [Desktop Entry] group of a .desktop file. Entries that are
+// hidden, marked NoDisplay or have nothing to run come back as None.
+pub fn parse_desktop_entry(contents: &str) -> Option<App> {
+    let mut app = App::default();
+    let mut in_entry = false;
+
+    for line in contents.lines() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+
+        if !in_entry {
+            continue;
+        }
+
+        let (key, value) = match line.find('=') {
+            Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+            None    => continue,
+        };
+
+        match key {
+            "Type" if value != "Application"      => return None,
+            "NoDisplay" | "Hidden" if value == "true" => return None,
+            "Name"     => app.name = value.to_string(),
+            "Exec"     => app.exec = strip_field_codes(value),
+            "Icon"     => app.icon = Some(value.to_string()),
+            "Terminal" => app.terminal = value == "true",
+            _          => {},
+        };
+    }
+
+    if app.name.is_empty() || app.exec.is_empty() {
+        return None;
+    }
+
+    Some(app)
+}
+
+// A user's own .desktop file overrides a system one with the same name.
+pub fn scan(dirs: &[&Path]) -> Vec<App> {
+    let mut seen = HashSet::new();
+    let mut apps = vec![];
+
+    for dir in dirs {
+        let entries = match fs::read_dir(dir) {
+            Ok (e) => e,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+
+            if path.extension().is_none_or(|e| e != "desktop") ||
+               !seen.insert(entry.file_name()) {
+                continue;
+            }
+
+            let parsed = fs::read_to_string(&path).ok().
+                and_then(|c| parse_desktop_entry(&c));
+
+            if let Some(app) = parsed {
+                apps.push(app);
+            }
+        }
+    }
+
+    apps.sort_by_key(|a| a.name.to_lowercase());
+    apps
+}
+
+fn load() -> Vec<App> {
+    let mut dirs = paths::data_dirs();
+
+    for dir in sandboxed::export_dirs() {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+
+    let dirs: Vec<_> = dirs.into_iter().map(|d| d.join("applications")).
+        collect();
+    let mut apps = scan(&dirs.iter().map(|d| d.as_path()).
+        collect::<Vec<_>>());
+
+    let unexported = sandboxed::unexported(&apps);
+    apps.extend(unexported);
+    apps.sort_by_key(|a| a.name.to_lowercase());
+    apps
+}
+
+impl Provider for Apps {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match self.config.prefix {
+            Some(ref p) => match provider::prefix_filter(input, p) {
+                Some(f) => f,
+                None    => return vec![],
+            },
+            None if input.is_empty() => return vec![],
+            None => input,
+        };
+
+        let mut apps = self.apps.borrow_mut();
+        let fresh = apps.as_ref().is_some_and(|(at, _)| {
+            at.elapsed() < Duration::from_secs(REFRESH_SECS)
+        });
+
+        if !fresh {
+            *apps = Some((Instant::now(), load()));
+        }
+
+        let apps = apps.as_ref().map(|(_, a)| a.as_slice()).unwrap_or(&[]);
+
+        let filter = filter.to_lowercase();
+
+        apps.iter().filter(|a| a.name.to_lowercase().contains(&filter)).
+            map(|a| self.item(a)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{App, parse_desktop_entry, strip_field_codes};
+
+    #[test]
+    fn it_strips_field_codes() {
+        assert_eq!(strip_field_codes("firefox %u"), "firefox");
+        assert_eq!(strip_field_codes("app --name=%c -x %F"),
+                   "app --name= -x");
+        assert_eq!(strip_field_codes("printf 100%%"), "printf 100%");
+        assert_eq!(strip_field_codes("flatpak run --file-forwarding \
+                                      org.gimp.GIMP @@ %F @@"),
+                   "flatpak run --file-forwarding org.gimp.GIMP");
+    }
+
+    #[test]
+    fn it_parses_desktop_entry() {
+        let contents = "[Desktop Entry]\n\
+                        Type=Application\n\
+                        Name=htop\n\
+                        Exec=htop %F\n\
+                        Icon=htop\n\
+                        Terminal=true\n\
+                        \n\
+                        [Desktop Action Kill]\n\
+                        Name=Kill\n\
+                        Exec=killall htop\n";
+
+        assert_eq!(parse_desktop_entry(contents), Some(App {
+            name: "htop".to_string(),
+            exec: "htop".to_string(),
+            icon: Some("htop".to_string()),
+            terminal: true,
+        }));
+    }
+
+    #[test]
+    fn it_skips_hidden_entries() {
+        let contents = "[Desktop Entry]\n\
+                        Name=Settings daemon\n\
+                        Exec=settingsd\n\
+                        NoDisplay=true\n";
+
+        assert_eq!(parse_desktop_entry(contents), None);
+    }
+}
@@ -0,0 +1,57 @@
+use audio::{self, Config, Sink};
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+
+// Audio outputs after the prefix, e.g. "audio head". Enter makes the
+// output the default and moves playing streams over; the current default
+// is marked in its row.
+pub struct Audio {
+    config: Config,
+}
+
+impl Audio {
+    pub fn new(config: Config) -> Audio {
+        Audio { config }
+    }
+}
+
+fn item(sink: &Sink, default: bool) -> Item {
+    let (description, classes) = if default {
+        ("default output", vec!["default".to_string()])
+    } else {
+        ("audio output", vec![])
+    };
+
+    Item {
+        icon: Some("audio-speakers".to_string()),
+        classes,
+        ..Item::new(&sink.description, description,
+                    Action::Spawn(audio::select_command(&sink.name)))
+    }
+}
+
+impl Provider for Audio {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let sinks = match audio::sinks() {
+            Ok (s) => s,
+            Err(e) => return vec![Item::message(&e)],
+        };
+
+        let default = audio::default_sink();
+
+        let mut found: Vec<(usize, &Sink)> = sinks.iter().
+            filter_map(|s| {
+                fuzzy::score(filter, &s.description).map(|score| (score, s))
+            }).collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, s)| {
+            item(s, default.as_ref() == Some(&s.name))
+        }).collect()
+    }
+}
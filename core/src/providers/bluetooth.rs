@@ -0,0 +1,75 @@
+use bluetooth::{self, Config, Device};
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+
+// Paired Bluetooth devices after the prefix, e.g. "bt head". Enter
+// connects a device or disconnects it if it's connected, and a last row
+// turns the adapter on or off.
+pub struct Bluetooth {
+    config: Config,
+}
+
+impl Bluetooth {
+    pub fn new(config: Config) -> Bluetooth {
+        Bluetooth { config }
+    }
+}
+
+fn item(device: &Device) -> Item {
+    let (description, class) = if device.connected {
+        ("connected, Enter disconnects", "connected")
+    } else {
+        ("disconnected, Enter connects", "disconnected")
+    };
+
+    Item {
+        icon: Some("bluetooth".to_string()),
+        classes: vec![class.to_string()],
+        wait: true,
+        ..Item::new(&device.name, description,
+                    Action::Spawn(bluetooth::connect_command(device)))
+    }
+}
+
+impl Provider for Bluetooth {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let powered = bluetooth::powered();
+
+        let mut items = if powered {
+            let devices = match bluetooth::devices() {
+                Ok (d) => d,
+                Err(e) => return vec![Item::message(&e)],
+            };
+
+            let mut found: Vec<(usize, &Device)> = devices.iter().
+                filter_map(|d| fuzzy::score(filter, &d.name).map(|s| (s, d))).
+                collect();
+
+            found.sort_by_key(|&(score, _)| score);
+            found.into_iter().map(|(_, d)| item(d)).collect()
+        } else {
+            vec![]
+        };
+
+        let title = if powered {
+            "Turn Bluetooth off"
+        } else {
+            "Turn Bluetooth on"
+        };
+
+        if fuzzy::score(filter, title).is_some() {
+            items.push(Item {
+                icon: Some("bluetooth".to_string()),
+                ..Item::new(title, "adapter",
+                            Action::Spawn(bluetooth::power_command(!powered)))
+            });
+        }
+
+        items
+    }
+}
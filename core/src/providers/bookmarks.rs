@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+
+use bookmarks::{Bookmark, Config, Store};
+use desktop::shell_quote;
+use fuzzy;
+use icons::Favicons;
+use provider::{self, Action, Item, Provider};
+
+// Browser bookmarks after the prefix, e.g. "bm rust", matched on title and
+// URL. Enter opens one in the default browser.
+pub struct Bookmarks {
+    config: Config,
+    store: RefCell<Store>,
+    favicons: Favicons,
+}
+
+impl Bookmarks {
+    pub fn new(config: Config) -> Bookmarks {
+        Bookmarks {
+            config,
+            store: RefCell::new(Store::new()),
+            favicons: Favicons::new(),
+        }
+    }
+
+    fn item(&self, bookmark: &Bookmark) -> Item {
+        Item {
+            icon: Some(self.favicons.icon(&bookmark.url)),
+            ..Item::new(&bookmark.title, &bookmark.url,
+                        Action::Spawn(format!("xdg-open {}",
+                                              shell_quote(&bookmark.url))))
+        }
+    }
+}
+
+impl Provider for Bookmarks {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let mut store = self.store.borrow_mut();
+
+        let mut found: Vec<(usize, &Bookmark)> = store.bookmarks().iter().
+            filter_map(|b| {
+                let score = match (fuzzy::score(filter, &b.title),
+                                   fuzzy::score(filter, &b.url)) {
+                    (Some(t), Some(u)) => t.min(u + 1),
+                    (t, u)             => t.or(u.map(|u| u + 1))?,
+                };
+
+                Some((score, b))
+            }).collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, b)| self.item(b)).collect()
+    }
+}
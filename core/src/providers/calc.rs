@@ -0,0 +1,54 @@
+use calc;
+use icons;
+use provider::{Action, Item, Provider};
+
+pub const PREFIX: &str = "=";
+
+// Evaluates arithmetic typed after "=", or on its own when it can't be
+// anything else ("12*4"). Enter copies the result.
+pub struct Calc;
+
+impl Provider for Calc {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let expr = match input.strip_prefix(PREFIX) {
+            Some(e) => e,
+            None if calc::looks_like_math(input) => input,
+            None    => return vec![],
+        };
+
+        if expr.trim().is_empty() {
+            return vec![];
+        }
+
+        match calc::eval(expr) {
+            Ok (v) => {
+                let result = calc::format(v);
+
+                vec![Item {
+                    icon: Some(icons::CALCULATOR.to_string()),
+                    ..Item::new(&result, &format!("= {}", expr.trim()),
+                                Action::Copy(result.clone()))
+                }]
+            },
+            // Typing "=" is asking for the calculator, so say what's wrong
+            Err(e) if input.starts_with(PREFIX) => vec![Item::message(&e)],
+            Err(_) => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use provider::{Action, Provider};
+    use super::Calc;
+
+    #[test]
+    fn it_copies_the_result() {
+        let items = Calc.query("=2^10");
+
+        assert_eq!(items[0].title, "1024");
+        assert_eq!(items[0].action, Some(Action::Copy("1024".to_string())));
+        assert!(Calc.query("wf").is_empty());
+        assert_eq!(Calc.query("=2+").len(), 1);
+    }
+}
@@ -0,0 +1,64 @@
+use clipboard::{self, Config};
+use provider::{self, Action, Item, Provider};
+
+// Longest title before a copy is cut short; the whole text is copied back
+const TITLE_CHARS: usize = 80;
+
+// Recent copies after the prefix, newest first. Enter puts one back on the
+// clipboard.
+pub struct Clips {
+    config: Config,
+}
+
+impl Clips {
+    pub fn new(config: Config) -> Clips {
+        Clips { config }
+    }
+}
+
+// The first line of a copy, shortened to fit a row.
+pub fn title(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or("");
+
+    if line.chars().count() <= TITLE_CHARS {
+        return line.to_string();
+    }
+
+    let cut: String = line.chars().take(TITLE_CHARS - 1).collect();
+    format!("{}\u{2026}", cut)
+}
+
+fn item(text: &str) -> Item {
+    let lines = text.trim().lines().count();
+    let description = if lines > 1 {
+        format!("{} lines", lines)
+    } else {
+        "clipboard".to_string()
+    };
+
+    Item::new(&title(text), &description, Action::Copy(text.to_string()))
+}
+
+impl Provider for Clips {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f.to_lowercase(),
+            None    => return vec![],
+        };
+
+        clipboard::load(&self.config).iter().
+            filter(|e| e.to_lowercase().contains(&filter)).
+            map(|e| item(e)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::title;
+
+    #[test]
+    fn it_shortens_titles() {
+        assert_eq!(title("  ls -l\nls -a"), "ls -l");
+        assert_eq!(title(&"x".repeat(100)).chars().count(), 80);
+    }
+}
@@ -0,0 +1,20 @@
+use color;
+use provider::{Action, Item, Provider};
+
+// A hex, rgb() or hsl() color typed on its own, e.g. "#3366cc", shown with
+// a swatch in each notation. Enter copies the chosen one.
+pub struct Color;
+
+impl Provider for Color {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let color = match color::parse(input) {
+            Some(c) => c,
+            None    => return vec![],
+        };
+
+        color::formats(&color).into_iter().map(|(name, text)| Item {
+            swatch: Some(color.rgba()),
+            ..Item::new(&text, name, Action::Copy(text.clone()))
+        }).collect()
+    }
+}
@@ -0,0 +1,97 @@
+use containers::{self, Config, Container, Image};
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+
+// Containers and images after the prefix, e.g. "dk web". Picking a
+// container goes to "dk <name> " with start for a stopped one and stop and
+// shell for a running one; Enter on an image opens a shell in a throwaway
+// container.
+pub struct Containers {
+    config: Config,
+}
+
+impl Containers {
+    pub fn new(config: Config) -> Containers {
+        Containers { config }
+    }
+
+    fn path(&self, container: &Container) -> String {
+        format!("{} {} ", self.config.prefix, container.name)
+    }
+
+    fn container_item(&self, container: &Container) -> Item {
+        let next = self.path(container);
+
+        Item {
+            icon: Some("package-x-generic".to_string()),
+            completion: Some(next.clone()),
+            classes: vec![container.state.clone()],
+            ..Item::new(&container.name,
+                        &format!("{}, {}", container.image, container.status),
+                        Action::Query(next))
+        }
+    }
+
+    fn image_item(&self, image: &Image) -> Item {
+        Item::new(&image.name, "image, Enter opens a shell",
+                  Action::Spawn(containers::run_command(&self.config, image)))
+    }
+
+    fn verbs(&self, engine: &str, container: &Container, filter: &str)
+        -> Vec<Item> {
+        let actions = if container.running() {
+            vec![
+                ("stop", containers::command(engine, container, "stop")),
+                ("shell",
+                 containers::shell_command(&self.config, container)),
+            ]
+        } else {
+            vec![("start", containers::command(engine, container, "start"))]
+        };
+
+        actions.into_iter().filter(|&(v, _)| v.starts_with(filter)).
+            map(|(v, command)| {
+                Item::new(v, &container.name, Action::Spawn(command))
+            }).collect()
+    }
+}
+
+impl Provider for Containers {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_rest(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let engine = containers::engine(&self.config);
+
+        let found = match containers::containers(&engine) {
+            Ok (c) => c,
+            Err(e) => return vec![Item::message(&e)],
+        };
+
+        // "<name> <verb>" once a container's been picked
+        if let Some((name, verb)) = filter.split_once(' ') {
+            if let Some(c) = found.iter().find(|c| c.name == name) {
+                return self.verbs(&engine, c, verb.trim());
+            }
+        }
+
+        let images = containers::images(&engine).unwrap_or_default();
+
+        let mut scored: Vec<(usize, Item)> = found.iter().
+            filter_map(|c| fuzzy::score(filter, &c.name).
+                map(|s| (s, self.container_item(c)))).
+            collect();
+
+        // Images go after the containers
+        let mut images: Vec<(usize, Item)> = images.iter().
+            filter_map(|i| fuzzy::score(filter, &i.name).
+                map(|s| (s, self.image_item(i)))).
+            collect();
+
+        scored.sort_by_key(|&(score, _)| score);
+        images.sort_by_key(|&(score, _)| score);
+        scored.into_iter().chain(images).map(|(_, item)| item).collect()
+    }
+}
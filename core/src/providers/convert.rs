@@ -0,0 +1,65 @@
+use std::cell::RefCell;
+
+use convert::{self, Config, Exchange};
+use icons;
+use provider::{Action, Item, Provider};
+
+// Converts "5 mi to km" or "100 usd to eur" typed on its own. Enter copies
+// the converted amount.
+pub struct Convert {
+    exchange: RefCell<Exchange>,
+}
+
+impl Convert {
+    pub fn new(config: Config) -> Convert {
+        Convert {
+            exchange: RefCell::new(Exchange::new(config)),
+        }
+    }
+}
+
+fn item(result: String, conversion: &convert::Conversion) -> Item {
+    let description = format!("{} {} in {}", conversion.amount,
+                              conversion.from, conversion.to);
+
+    Item {
+        icon: Some(icons::CALCULATOR.to_string()),
+        ..Item::new(&format!("{} {}", result, conversion.to), &description,
+                    Action::Copy(result))
+    }
+}
+
+impl Provider for Convert {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let conversion = match convert::parse(input) {
+            Some(c) => c,
+            None    => return vec![],
+        };
+
+        match convert::convert_units(&conversion) {
+            Some(Ok (v)) => return vec![item(convert::format(v, 6),
+                                             &conversion)],
+            Some(Err(e)) => return vec![Item::message(&e)],
+            None         => {},
+        };
+
+        // Only three-letter codes can be currencies
+        if conversion.from.len() != 3 || conversion.to.len() != 3 {
+            return vec![];
+        }
+
+        let mut exchange = self.exchange.borrow_mut();
+
+        match exchange.rates() {
+            Ok (Some(rates)) => rates.convert(&conversion).
+                map(|v| item(convert::format(v, 2), &conversion)).
+                into_iter().collect(),
+            Ok (None) => vec![Item::loading("Fetching exchange rates..")],
+            Err(e)    => vec![Item::message(&e)],
+        }
+    }
+
+    fn poll(&self) -> bool {
+        self.exchange.borrow_mut().poll()
+    }
+}
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use dictionary::{self, Config, Definition};
+use network;
+use provider::{self, Action, Item, Provider};
+
+// Lookups are kept while the same word is typed again
+const CACHE_SECS: u64 = 600;
+
+// Definitions of the word after the prefix, e.g. "def launch", one row per
+// dictionary with the definition wrapped under it. Enter copies the text.
+pub struct Dictionary {
+    config: Config,
+    monitor: network::Monitor,
+}
+
+impl Dictionary {
+    pub fn new(config: Config) -> Dictionary {
+        Dictionary {
+            config,
+            monitor: network::Monitor::new(),
+        }
+    }
+}
+
+fn item(definition: &Definition) -> Item {
+    Item {
+        icon: Some("accessories-dictionary".to_string()),
+        body: Some(definition.text.clone()),
+        ..Item::new(&definition.word, &definition.source,
+                    Action::Copy(definition.text.clone()))
+    }
+}
+
+impl Provider for Dictionary {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let word = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(w) if !w.is_empty() => w,
+            _                        => return vec![],
+        };
+
+        if dictionary::remote(&self.config) &&
+           !self.monitor.is_online() {
+            return vec![Item::message(&network::offline_description(
+                &format!("Can't look up {}", word)))];
+        }
+
+        match dictionary::define(&self.config, word) {
+            Ok (ref d) if d.is_empty() =>
+                vec![Item::message(&format!("No definitions for {}", word))],
+            Ok (d) => d.iter().map(item).collect(),
+            Err(e) => vec![Item::message(&e)],
+        }
+    }
+
+    fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(CACHE_SECS)
+    }
+}
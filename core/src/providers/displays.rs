@@ -0,0 +1,51 @@
+use displays::{self, Config, Layout};
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+
+// Saved display layouts after the prefix, e.g. "disp dock". Profiles that
+// match the connected screens come first; Enter applies a layout.
+pub struct Displays {
+    config: Config,
+}
+
+impl Displays {
+    pub fn new(config: Config) -> Displays {
+        Displays { config }
+    }
+}
+
+fn item(layout: &Layout) -> Item {
+    let description = match (layout.current, layout.detected) {
+        (true, _)      => "current layout",
+        (false, true)  => "matches connected screens",
+        (false, false) => "display layout",
+    };
+
+    Item {
+        icon: Some("video-display".to_string()),
+        ..Item::new(&layout.name, description,
+                    Action::Spawn(layout.command.clone()))
+    }
+}
+
+impl Provider for Displays {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let layouts = displays::layouts(&self.config);
+
+        if layouts.is_empty() {
+            return vec![Item::message("No saved display layouts")];
+        }
+
+        let mut found: Vec<(usize, &Layout)> = layouts.iter().
+            filter_map(|l| fuzzy::score(filter, &l.name).map(|s| (s, l))).
+            collect();
+
+        found.sort_by_key(|&(score, l)| (!l.detected, score));
+        found.into_iter().map(|(_, l)| item(l)).collect()
+    }
+}
@@ -0,0 +1,85 @@
+use drives::{self, Config, Drive};
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+
+// Removable drives after the prefix, e.g. "mnt usb". Picking one goes to
+// "mnt <device> " with mount or open and unmount, and eject; mounting
+// opens the drive in the file manager.
+pub struct Drives {
+    config: Config,
+}
+
+impl Drives {
+    pub fn new(config: Config) -> Drives {
+        Drives { config }
+    }
+
+    fn path(&self, drive: &Drive) -> String {
+        format!("{} {} ", self.config.prefix, drive.device)
+    }
+
+    fn drive_item(&self, drive: &Drive) -> Item {
+        let next = self.path(drive);
+        let state = match drive.mountpoint {
+            Some(ref m) => format!("{}, mounted at {}", drive.size, m),
+            None        => format!("{}, not mounted", drive.size),
+        };
+
+        Item {
+            icon: Some("drive-removable-media".to_string()),
+            completion: Some(next.clone()),
+            ..Item::new(drive.name(), &state, Action::Query(next))
+        }
+    }
+
+    fn verbs(&self, drive: &Drive, filter: &str) -> Vec<Item> {
+        let mut actions = match drive.mountpoint {
+            Some(ref m) => vec![
+                ("open", drives::open_command(&self.config, m)),
+                ("unmount", drives::unmount_command(drive)),
+            ],
+            None => vec![("mount", drives::mount_command(&self.config, drive))],
+        };
+
+        actions.push(("eject", drives::eject_command(drive)));
+
+        // Waited on, so udisks' complaints about busy drives show up
+        actions.into_iter().filter(|&(v, _)| v.starts_with(filter)).
+            map(|(v, command)| Item {
+                wait: v != "open",
+                ..Item::new(v, drive.name(), Action::Spawn(command))
+            }).collect()
+    }
+}
+
+impl Provider for Drives {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_rest(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let found = match drives::list() {
+            Ok (d) => d,
+            Err(e) => return vec![Item::message(&e)],
+        };
+
+        // "<device> <verb>" once a drive's been picked
+        if let Some((device, verb)) = filter.split_once(' ') {
+            if let Some(d) = found.iter().find(|d| d.device == device) {
+                return self.verbs(d, verb.trim());
+            }
+        }
+
+        if found.is_empty() {
+            return vec![Item::message("No removable drives")];
+        }
+
+        let mut scored: Vec<(usize, &Drive)> = found.iter().
+            filter_map(|d| fuzzy::score(filter, d.name()).map(|s| (s, d))).
+            collect();
+
+        scored.sort_by_key(|&(score, _)| score);
+        scored.into_iter().map(|(_, d)| self.drive_item(d)).collect()
+    }
+}
@@ -0,0 +1,61 @@
+use desktop;
+use emoji::{self, Config};
+use provider::{Action, Item, Provider};
+
+// Most matches worth listing; a couple of letters match half the table
+const LIMIT: usize = 30;
+
+// Emoji by name or keyword after the prefix, e.g. ":thumbs". Enter copies
+// the emoji, or types it when the config says so.
+pub struct Emoji {
+    config: Config,
+}
+
+impl Emoji {
+    pub fn new(config: Config) -> Emoji {
+        Emoji { config }
+    }
+
+    fn item(&self, emoji: &str, name: &str) -> Item {
+        let action = if self.config.type_it {
+            Action::Spawn(desktop::type_command(emoji))
+        } else {
+            Action::Copy(emoji.to_string())
+        };
+
+        Item::new(&format!("{}  {}", emoji, name), "emoji", action)
+    }
+}
+
+impl Provider for Emoji {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let query = match input.strip_prefix(self.config.prefix.as_str()) {
+            Some(q) if !q.trim().is_empty() => q.trim(),
+            _                               => return vec![],
+        };
+
+        emoji::search(query).into_iter().take(LIMIT).
+            map(|(e, name)| self.item(e, name)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emoji::Config;
+    use provider::{Action, Provider};
+    use super::Emoji;
+
+    #[test]
+    fn it_copies_the_emoji() {
+        let provider = Emoji::new(Config {
+            prefix: ":".to_string(),
+            type_it: false,
+        });
+
+        let items = provider.query(":crab");
+
+        assert_eq!(items[0].title, "🦀  crab");
+        assert_eq!(items[0].action, Some(Action::Copy("🦀".to_string())));
+        assert!(provider.query("crab").is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use std::cell::RefCell;
+use std::path::Path;
+
+use desktop::shell_quote;
+use files::{Config, Searcher};
+use icons;
+use provider::{Action, Item, Provider};
+
+// Files after the prefix, e.g. "/invoice", found by the configured command
+// or by walking the home directory. Results appear as they're found and
+// Enter opens one with xdg-open.
+pub struct Files {
+    prefix: String,
+    searcher: RefCell<Searcher>,
+}
+
+impl Files {
+    pub fn new(config: Config) -> Files {
+        Files {
+            prefix: config.prefix.clone(),
+            searcher: RefCell::new(Searcher::new(config)),
+        }
+    }
+}
+
+fn item(path: &Path) -> Item {
+    let name = path.file_name().map_or_else(|| path.to_string_lossy(),
+                                            |n| n.to_string_lossy());
+    let parent = path.parent().map(|p| p.to_string_lossy()).
+        unwrap_or_default();
+    let command = format!("xdg-open {}", shell_quote(&path.to_string_lossy()));
+
+    Item {
+        icon: Some(icons::for_path(path).to_string()),
+        ..Item::new(&name, &parent, Action::Spawn(command))
+    }
+}
+
+impl Provider for Files {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let query = match input.strip_prefix(self.prefix.as_str()) {
+            Some(q) => q.trim(),
+            None    => return vec![],
+        };
+
+        if query.is_empty() {
+            return vec![Item::message("Type part of a file name")];
+        }
+
+        let mut searcher = self.searcher.borrow_mut();
+        searcher.request(query);
+
+        let mut items: Vec<Item> = searcher.results().iter().
+            map(|p| item(p)).collect();
+
+        if searcher.running() {
+            items.push(Item::loading("Searching.."));
+        } else if items.is_empty() {
+            items.push(Item::message(&format!("No files match {}", query)));
+        }
+
+        items
+    }
+
+    fn poll(&self) -> bool {
+        self.searcher.borrow_mut().poll()
+    }
+}
@@ -0,0 +1,52 @@
+use fuzzy;
+use keyboard::{self, Config};
+use provider::{self, Action, Item, Provider};
+
+// Keyboard layouts after the prefix, e.g. "kb de", with the active one
+// marked. Enter switches to a layout.
+pub struct Keyboard {
+    config: Config,
+}
+
+impl Keyboard {
+    pub fn new(config: Config) -> Keyboard {
+        Keyboard { config }
+    }
+}
+
+impl Provider for Keyboard {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let layouts = keyboard::layouts(&self.config);
+
+        if layouts.is_empty() {
+            return vec![Item::message("No keyboard layouts configured")];
+        }
+
+        let current = keyboard::current();
+
+        let mut found: Vec<(usize, &String)> = layouts.iter().
+            filter_map(|l| fuzzy::score(filter, l).map(|s| (s, l))).
+            collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, layout)| {
+            let (description, classes) = if current.as_ref() == Some(layout) {
+                ("current layout", vec!["current".to_string()])
+            } else {
+                ("layout", vec![])
+            };
+
+            Item {
+                icon: Some("input-keyboard".to_string()),
+                classes,
+                ..Item::new(layout, description,
+                            Action::Spawn(keyboard::switch_command(layout)))
+            }
+        }).collect()
+    }
+}
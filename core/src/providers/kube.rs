@@ -0,0 +1,126 @@
+use std::cell::Cell;
+use std::time::{Duration, SystemTime};
+
+use fuzzy;
+use kube::{self, Config, Context};
+use provider::{self, Action, Item, Provider};
+
+// Namespaces come from the cluster, so results are kept for a while
+const CACHE_SECS: u64 = 60;
+
+// Kubernetes contexts after the prefix, e.g. "k8s prod", with the current
+// one marked. Enter switches to a context; completing it lists the
+// context's namespaces, and picking one of those switches to both.
+pub struct Kube {
+    config: Config,
+    // When the kubeconfig last changed, to drop cached results once a
+    // context is switched
+    modified: Cell<Option<SystemTime>>,
+}
+
+impl Kube {
+    pub fn new(config: Config) -> Kube {
+        Kube {
+            config,
+            modified: Cell::new(kube::modified()),
+        }
+    }
+
+    fn context_item(&self, context: &Context, current: bool) -> Item {
+        let namespace = context.namespace.as_deref().unwrap_or("default");
+
+        let (description, classes) = if current {
+            (format!("current context, namespace {}", namespace),
+             vec!["default".to_string()])
+        } else {
+            (format!("{}, namespace {}", context.cluster, namespace), vec![])
+        };
+
+        Item {
+            icon: Some("network-server".to_string()),
+            completion: Some(format!("{} {} ", self.config.prefix,
+                                     context.name)),
+            classes,
+            ..Item::new(&context.name, &description, Action::Spawn(
+                kube::use_context_command(&context.name)))
+        }
+    }
+
+    fn namespace_items(&self, context: &Context, filter: &str) -> Vec<Item> {
+        let namespaces = match kube::namespaces(&context.name) {
+            Ok (n) => n,
+            Err(e) => return vec![Item::message(&e)],
+        };
+
+        let active = context.namespace.as_deref().unwrap_or("default");
+
+        let mut found: Vec<(usize, &String)> = namespaces.iter().
+            filter_map(|n| fuzzy::score(filter, n).map(|s| (s, n))).
+            collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, n)| {
+            let classes = if n == active {
+                vec!["default".to_string()]
+            } else {
+                vec![]
+            };
+
+            Item {
+                icon: Some("folder".to_string()),
+                classes,
+                ..Item::new(n, &format!("namespace in {}", context.name),
+                            Action::Spawn(kube::namespace_command(
+                                &context.name, n)))
+            }
+        }).collect()
+    }
+}
+
+impl Provider for Kube {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_rest(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let config = kube::load();
+
+        if config.contexts.is_empty() {
+            return vec![Item::message("No contexts in the kubeconfig")];
+        }
+
+        // "<context> <namespace>" once a context's been completed
+        if let Some((name, namespace)) = filter.split_once(' ') {
+            if let Some(c) = config.contexts.iter().find(|c| c.name == name) {
+                return self.namespace_items(c, namespace.trim());
+            }
+        }
+
+        let mut found: Vec<(usize, &Context)> = config.contexts.iter().
+            filter_map(|c| fuzzy::score(filter, &c.name).map(|s| (s, c))).
+            collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, c)| {
+            self.context_item(c, config.is_current(c))
+        }).collect()
+    }
+
+    fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(CACHE_SECS)
+    }
+
+    // Switching contexts rewrites the kubeconfig, which moves the current
+    // marker
+    fn poll(&self) -> bool {
+        let modified = kube::modified();
+
+        if modified == self.modified.get() {
+            return false;
+        }
+
+        self.modified.set(modified);
+        true
+    }
+}
@@ -0,0 +1,32 @@
+use macros::{self, Config};
+use node::{Node, Resolved, resolve, borrow_nodes};
+use provider::{Item, Provider};
+use providers::menu::node_item;
+
+// Recorded macros, resolved by shortcut like the menu. The file is re-read
+// on every query so a macro is usable as soon as it's saved.
+pub struct Macros {
+    config: Config,
+}
+
+impl Macros {
+    pub fn new(config: Config) -> Macros {
+        Macros { config }
+    }
+}
+
+impl Provider for Macros {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let nodes: Vec<Node> = macros::load(&self.config.file).iter().
+            map(|m| m.node()).collect();
+
+        match resolve(borrow_nodes(&nodes), input.to_string()) {
+            Resolved::Complete(n) => vec![Item {
+                immediate: true,
+                ..node_item(n)
+            }],
+            Resolved::Partial(ns) => ns.into_iter().map(node_item).collect(),
+            Resolved::Loading(_)  => vec![],
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use fuzzy;
+use man::{self, Config, Page};
+use provider::{self, Action, Item, Provider};
+
+// The index rarely changes, so it's read again only now and then
+const INDEX_TTL_SECS: u64 = 600;
+
+// Most matches worth listing; a couple of letters match thousands of pages
+const LIMIT: usize = 30;
+
+type Index = Result<Vec<Page>, String>;
+
+// Manual pages by name after the prefix, e.g. "man printf", with their
+// short description. Enter opens the page in a terminal.
+pub struct Man {
+    config: Config,
+    index: RefCell<Option<(Instant, Index)>>,
+}
+
+impl Man {
+    pub fn new(config: Config) -> Man {
+        Man {
+            config,
+            index: RefCell::new(None),
+        }
+    }
+
+    fn pages(&self) -> Index {
+        let mut index = self.index.borrow_mut();
+        let fresh = index.as_ref().is_some_and(|(at, _)| {
+            at.elapsed() < Duration::from_secs(INDEX_TTL_SECS)
+        });
+
+        if !fresh {
+            *index = Some((Instant::now(), man::pages()));
+        }
+
+        index.as_ref().map(|(_, p)| p.clone()).unwrap_or(Ok(vec![]))
+    }
+}
+
+impl Provider for Man {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        if filter.is_empty() {
+            return vec![Item::message("Type part of a page name")];
+        }
+
+        let pages = match self.pages() {
+            Ok (p) => p,
+            Err(e) => return vec![Item::message(&e)],
+        };
+
+        // Shorter names first among equally good matches, so "ls" comes
+        // before "lsblk"
+        let mut found: Vec<(usize, usize, &Page)> = pages.iter().
+            filter_map(|p| {
+                fuzzy::score(filter, &p.name).map(|s| (s, p.name.len(), p))
+            }).collect();
+
+        found.sort_by_key(|&(score, length, _)| (score, length));
+        found.into_iter().take(LIMIT).map(|(_, _, p)| Item {
+            icon: Some("help-browser".to_string()),
+            ..Item::new(&format!("{}({})", p.name, p.section), &p.description,
+                        Action::Spawn(man::open_command(&self.config, p)))
+        }).collect()
+    }
+}
@@ -0,0 +1,203 @@
+use std::cell::RefCell;
+
+use serde_json;
+
+use builtins;
+use dynamic::Loader;
+use node::{Node, Resolved, resolve_path, borrow_nodes, fill_children};
+use paths::expand_home;
+use provider::{Action, Item, Provider};
+
+// The JSON menu from the config file. Typing a node's full shortcut path
+// activates it immediately; nodes with a children_command load their
+// children in the background the first time they're reached.
+pub struct Menu {
+    nodes: RefCell<Vec<Node>>,
+    loader: RefCell<Loader>,
+    error: RefCell<Option<String>>,
+}
+
+impl Menu {
+    pub fn new(nodes: Vec<Node>) -> Menu {
+        Menu {
+            nodes: RefCell::new(nodes),
+            loader: RefCell::new(Loader::new()),
+            error: RefCell::new(None),
+        }
+    }
+}
+
+pub fn node_action(node: &Node) -> Option<Action> {
+    if let Some(ref b) = node.builtin {
+        return Some(Action::Builtin(b.clone()));
+    }
+
+    if let Some(ref s) = node.steps {
+        return Some(Action::Macro(s.clone()));
+    }
+
+    node.command.as_ref().map(|c| if node.shell {
+        Action::Spawn(c.clone())
+    } else {
+        Action::Exec(c.clone())
+    })
+}
+
+pub fn node_item(node: &Node) -> Item {
+    Item {
+        title: node.shortcut.clone(),
+        description: builtins::describe(node),
+        action: node_action(node),
+        preview: node.preview.clone(),
+        icon: node.icon.as_ref().map(|i| expand_home(i)),
+        classes: node.classes.clone(),
+        wait: node.wait,
+        prompt: node.prompt.clone(),
+        ..Default::default()
+    }
+}
+
+impl Provider for Menu {
+    fn query(&self, input: &str) -> Vec<Item> {
+        // Report a failed children_command once, on the redraw after it
+        if let Some(e) = self.error.borrow_mut().take() {
+            return vec![Item::message(&e)];
+        }
+
+        let nodes = self.nodes.borrow();
+
+        let (path, resolved) = resolve_path(borrow_nodes(&nodes),
+                                            input.to_string());
+
+        match resolved {
+            Resolved::Complete(n) => vec![Item {
+                immediate: true,
+                ..node_item(n)
+            }],
+            Resolved::Partial(ns) => {
+                let parent: String = path.iter().
+                    map(|n| n.shortcut.as_str()).collect();
+
+                ns.into_iter().map(|n| Item {
+                    completion: Some(format!("{}{}", parent, n.shortcut)),
+                    ..node_item(n)
+                }).collect()
+            },
+            Resolved::Loading(n) => {
+                if let Some(ref c) = n.children_command {
+                    self.loader.borrow_mut().request(c);
+                }
+
+                vec![Item::loading(&format!("Loading {}..", n.description))]
+            },
+        }
+    }
+
+    fn path(&self, input: &str) -> Vec<String> {
+        let nodes = self.nodes.borrow();
+
+        resolve_path(borrow_nodes(&nodes), input.to_string()).0.into_iter().
+            map(|n| n.shortcut.clone()).collect()
+    }
+
+    fn poll(&self) -> bool {
+        let finished = self.loader.borrow().poll();
+
+        if finished.is_empty() {
+            return false;
+        }
+
+        for (command, output) in finished {
+            let children: Vec<Node> = match output.map(|o|
+                    serde_json::from_str(&o).map_err(|e| e.to_string())) {
+                Ok (Ok (c)) => c,
+                Ok (Err(e)) | Err(e) => {
+                    *self.error.borrow_mut() =
+                        Some(format!("Can't load {}: {}", command, e));
+                    vec![]
+                },
+            };
+
+            fill_children(&mut self.nodes.borrow_mut(), &command, &children);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use node::Node;
+    use provider::{Action, Provider};
+    use super::{Menu, node_action};
+
+    fn test_menu() -> Menu {
+        Menu::new(vec![
+            Node {
+                shortcut: "t".to_string(),
+                description: "terminal".to_string(),
+                command: Some("xterm".to_string()),
+                ..Default::default()
+            },
+            Node {
+                shortcut: "l".to_string(),
+                description: "lock".to_string(),
+                builtin: Some("lock".to_string()),
+                icon: Some("system-lock-screen".to_string()),
+                classes: vec!["danger".to_string()],
+                ..Default::default()
+            },
+            Node {
+                shortcut: "s".to_string(),
+                description: "ssh".to_string(),
+                children_command: Some("true".to_string()),
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn it_lists_nodes_as_items() {
+        let items = test_menu().query("");
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1].title, "l");
+        assert_eq!(items[1].action, Some(Action::Builtin("lock".to_string())));
+        assert_eq!(items[1].classes, vec!["danger"]);
+        assert_eq!(items[1].icon, Some("system-lock-screen".to_string()));
+        assert_eq!(items[2].completion, Some("s".to_string()));
+        assert!(!items[0].immediate);
+    }
+
+    #[test]
+    fn it_marks_complete_shortcut_immediate() {
+        let items = test_menu().query("t");
+
+        assert_eq!(items.len(), 1);
+        assert!(items[0].immediate);
+        assert_eq!(items[0].action, Some(Action::Exec("xterm".to_string())));
+    }
+
+    #[test]
+    fn it_runs_node_commands_without_a_shell_unless_asked() {
+        let node = |shell| Node {
+            command: Some("feh --bg-fill '*.png'".to_string()),
+            shell,
+            ..Default::default()
+        };
+
+        assert_eq!(node_action(&node(false)),
+                   Some(Action::Exec("feh --bg-fill '*.png'".to_string())));
+        assert_eq!(node_action(&node(true)),
+                   Some(Action::Spawn("feh --bg-fill '*.png'".to_string())));
+    }
+
+    #[test]
+    fn it_shows_loading_for_dynamic_children() {
+        let items = test_menu().query("s");
+
+        assert_eq!(items.len(), 1);
+        assert!(items[0].loading);
+        assert_eq!(items[0].action, None);
+    }
+}
@@ -0,0 +1,48 @@
+pub mod apps;
+pub mod audio;
+pub mod bluetooth;
+pub mod bookmarks;
+pub mod calc;
+pub mod clipboard;
+pub mod color;
+pub mod containers;
+pub mod convert;
+pub mod dictionary;
+pub mod displays;
+pub mod drives;
+pub mod emoji;
+pub mod files;
+pub mod keyboard;
+pub mod kube;
+pub mod macros;
+pub mod man;
+pub mod menu;
+pub mod notice;
+pub mod pass;
+pub mod pinned;
+pub mod power;
+pub mod processes;
+pub mod projects;
+pub mod qr;
+pub mod recent;
+pub mod recent_files;
+pub mod repos;
+pub mod run;
+pub mod search;
+pub mod snippets;
+pub mod ssh;
+pub mod stats;
+pub mod steam;
+pub mod timers;
+pub mod tmux;
+pub mod todo;
+pub mod translate;
+pub mod trash;
+pub mod units;
+pub mod update;
+pub mod vms;
+pub mod vpn;
+pub mod wallpapers;
+pub mod weather;
+pub mod wifi;
+pub mod windows;
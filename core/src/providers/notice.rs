@@ -0,0 +1,18 @@
+use provider::{Item, Provider};
+
+// A fixed message above every result, like why safe mode is on.
+pub struct Notice {
+    text: String,
+}
+
+impl Notice {
+    pub fn new(text: &str) -> Notice {
+        Notice { text: text.to_string() }
+    }
+}
+
+impl Provider for Notice {
+    fn query(&self, _: &str) -> Vec<Item> {
+        vec![Item::message(&self.text)]
+    }
+}
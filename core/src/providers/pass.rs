@@ -0,0 +1,47 @@
+use fuzzy;
+use pass::{self, Config};
+use provider::{self, Action, Item, Provider};
+
+// pass entries after the prefix, e.g. "pass git". Enter copies the
+// password, or types it with type_it set; rows only ever show names.
+pub struct Pass {
+    config: Config,
+}
+
+impl Pass {
+    pub fn new(config: Config) -> Pass {
+        Pass { config }
+    }
+
+    fn item(&self, entry: &str) -> Item {
+        let (description, command) = if self.config.type_it {
+            ("type password", pass::type_command(entry))
+        } else {
+            ("copy password", pass::copy_command(entry))
+        };
+
+        Item {
+            icon: Some("dialog-password".to_string()),
+            ..Item::new(entry, description, Action::Spawn(command))
+        }
+    }
+}
+
+impl Provider for Pass {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        // Listed fresh each time, since it's only file names
+        let entries = pass::entries(&pass::store_dir());
+
+        let mut found: Vec<(usize, &String)> = entries.iter().
+            filter_map(|e| fuzzy::score(filter, e).map(|s| (s, e))).
+            collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, e)| self.item(e)).collect()
+    }
+}
@@ -0,0 +1,48 @@
+use node::Node;
+use pins::{self, Pin};
+use provider::{Item, Provider};
+use providers::recent::describe;
+
+// With nothing typed, lists the pinned favorites in a "Pinned" section at
+// the very top: nodes pinned in the config, then ones pinned with Ctrl+P.
+pub struct Pinned {
+    file: String,
+    flagged: Vec<Pin>,
+}
+
+impl Pinned {
+    pub fn new(config: &pins::Config, menu: &[Node]) -> Pinned {
+        Pinned {
+            file: config.file.clone(),
+            flagged: pins::flagged(menu),
+        }
+    }
+}
+
+impl Provider for Pinned {
+    fn query(&self, input: &str) -> Vec<Item> {
+        if !input.is_empty() {
+            return vec![];
+        }
+
+        let mut all = self.flagged.clone();
+        for p in pins::load(&self.file) {
+            if !all.iter().any(|a| a.action == p.action) {
+                all.push(p);
+            }
+        }
+
+        if all.is_empty() {
+            return vec![];
+        }
+
+        let mut ret = vec![Item::section("Pinned")];
+
+        ret.extend(all.into_iter().map(|p| Item {
+            classes: vec!["pinned".to_string()],
+            ..Item::new(&p.title, &describe(&p.action), p.action)
+        }));
+
+        ret
+    }
+}
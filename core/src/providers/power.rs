@@ -0,0 +1,84 @@
+use provider::{self, Action, Item, Provider};
+use session::{self, PowerConfig};
+
+const ACTIONS: &[(&str, &str, &str)] = &[
+    (session::LOCK,      "Lock",      "system-lock-screen"),
+    (session::LOGOUT,    "Log out",   "system-log-out"),
+    (session::SUSPEND,   "Suspend",   "system-suspend"),
+    (session::HIBERNATE, "Hibernate", "system-suspend-hibernate"),
+    (session::REBOOT,    "Reboot",    "system-reboot"),
+    (session::POWEROFF,  "Power off", "system-shutdown"),
+];
+
+// Locking, logging out and power actions after the prefix, e.g. "power re".
+// All but locking go through the confirm step unless turned off.
+pub struct Power {
+    config: PowerConfig,
+}
+
+impl Power {
+    pub fn new(config: PowerConfig) -> Power {
+        Power { config }
+    }
+
+    fn item(&self, builtin: &str, title: &str, icon: &str) -> Item {
+        let action = if self.config.confirm && builtin != session::LOCK {
+            provider::confirm(&self.config.prefix, builtin)
+        } else {
+            Action::Builtin(builtin.to_string())
+        };
+
+        Item {
+            icon: Some(icon.to_string()),
+            ..Item::new(title, "power", action)
+        }
+    }
+}
+
+impl Provider for Power {
+    fn query(&self, input: &str) -> Vec<Item> {
+        if let Some(what) = provider::confirming(&self.config.prefix, input) {
+            return ACTIONS.iter().filter(|a| a.0 == what).
+                map(|&(builtin, title, icon)| Item {
+                    icon: Some(icon.to_string()),
+                    ..Item::new(&format!("Really {}?", title.to_lowercase()),
+                                "power", Action::Builtin(builtin.to_string()))
+                }).collect();
+        }
+
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f.to_lowercase(),
+            None    => return vec![],
+        };
+
+        ACTIONS.iter().filter(|a| a.1.to_lowercase().contains(&filter)).
+            map(|&(builtin, title, icon)| self.item(builtin, title, icon)).
+            collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use provider::{Action, Provider};
+    use session::PowerConfig;
+    use super::Power;
+
+    #[test]
+    fn it_confirms_before_rebooting() {
+        let power = Power::new(PowerConfig {
+            prefix: "power".to_string(),
+            confirm: true,
+        });
+
+        let items = power.query("power reb");
+        assert_eq!(items[0].action,
+                   Some(Action::Query("power! reboot".to_string())));
+
+        let items = power.query("power! reboot");
+        assert_eq!(items[0].title, "Really reboot?");
+        assert_eq!(items[0].action,
+                   Some(Action::Builtin("reboot".to_string())));
+
+        assert_eq!(power.query("power lo").len(), 2);
+    }
+}
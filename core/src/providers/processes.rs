@@ -0,0 +1,84 @@
+use fuzzy;
+use processes::{self, Config, Process};
+use provider::{self, Action, Item, Provider};
+
+// Running processes after the prefix, e.g. "kill fire". Enter sends
+// SIGTERM and the alternate key SIGKILL. Another user's process asks first:
+// picking it goes to "kill! <pid>", where the kill goes through pkexec.
+pub struct Processes {
+    config: Config,
+}
+
+impl Processes {
+    pub fn new(config: Config) -> Processes {
+        Processes { config }
+    }
+
+    fn item(&self, process: &Process, own: bool) -> Item {
+        let description = format!("{}  {}.{}%", process.pid, process.cpu / 10,
+                                  process.cpu % 10);
+
+        if !own {
+            let confirm = provider::confirm(&self.config.prefix,
+                                            &process.pid.to_string());
+
+            return Item {
+                alternate: Some(confirm.clone()),
+                classes: vec!["foreign".to_string()],
+                ..Item::new(&process.name, &description, confirm)
+            };
+        }
+
+        let kill = |signal| Action::Spawn(
+            processes::kill_command(process.pid, signal, true));
+
+        Item {
+            alternate: Some(kill("KILL")),
+            ..Item::new(&process.name, &description, kill("TERM"))
+        }
+    }
+
+    fn confirm(&self, pid: &str) -> Vec<Item> {
+        let process = match pid.parse::<u32>().ok().and_then(|p| {
+            processes::list().into_iter().find(|x| x.pid == p)
+        }) {
+            Some(p) => p,
+            None    => return vec![Item::message(
+                &format!("No process {}", pid))],
+        };
+
+        let kill = |signal| Action::Spawn(
+            processes::kill_command(process.pid, signal, false));
+
+        vec![Item {
+            alternate: Some(kill("KILL")),
+            classes: vec!["foreign".to_string()],
+            ..Item::new(&format!("Really kill {} ({})?", process.name,
+                                 process.pid),
+                        &format!("uid {}", process.uid), kill("TERM"))
+        }]
+    }
+}
+
+impl Provider for Processes {
+    fn query(&self, input: &str) -> Vec<Item> {
+        if let Some(pid) = provider::confirming(&self.config.prefix, input) {
+            return self.confirm(pid);
+        }
+
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let uid = processes::current_uid();
+
+        // Busiest first, so keep that order among equally good matches
+        let mut found: Vec<(usize, Process)> = processes::list().into_iter().
+            filter_map(|p| fuzzy::score(filter, &p.name).map(|s| (s, p))).
+            collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.iter().map(|(_, p)| self.item(p, Some(p.uid) == uid)).collect()
+    }
+}
@@ -0,0 +1,35 @@
+use icons;
+use projects::{self, Project};
+use provider::{Action, Item, Provider};
+
+pub const PREFIX: &str = "project";
+
+// "project" lists the configured projects and "project blog" narrows them
+// down; Enter opens the selected one.
+pub struct Projects {
+    projects: Vec<Project>,
+}
+
+impl Projects {
+    pub fn new(projects: Vec<Project>) -> Projects {
+        Projects { projects }
+    }
+}
+
+impl Provider for Projects {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match input.strip_prefix(PREFIX) {
+            Some(f) => f.trim(),
+            None    => return vec![],
+        };
+
+        let tool = projects::ipc_tool();
+
+        self.projects.iter().filter(|p| p.name.starts_with(filter)).
+            map(|p| Item {
+                icon: Some(icons::FOLDER.to_string()),
+                ..Item::new(&p.name, &format!("{} windows", p.windows.len()),
+                            Action::Spawn(projects::launch_command(p, tool)))
+            }).collect()
+    }
+}
@@ -0,0 +1,53 @@
+use std::cell::RefCell;
+
+use provider::{self, Action, Item, Provider};
+use qr::{self, Config};
+
+type Code = Result<Vec<Vec<bool>>, String>;
+
+// The text after the prefix as a QR code, e.g. "qr https://example.com",
+// for reading with a phone. Enter copies the text.
+pub struct Qr {
+    config: Config,
+    // The last text encoded, kept exactly since case matters in a URL
+    last: RefCell<Option<(String, Code)>>,
+}
+
+impl Qr {
+    pub fn new(config: Config) -> Qr {
+        Qr {
+            config,
+            last: RefCell::new(None),
+        }
+    }
+
+    fn encode(&self, text: &str) -> Code {
+        let mut last = self.last.borrow_mut();
+
+        match *last {
+            Some((ref t, ref c)) if t == text => return c.clone(),
+            _                                 => {},
+        };
+
+        let code = qr::encode(text);
+        *last = Some((text.to_string(), code.clone()));
+        code
+    }
+}
+
+impl Provider for Qr {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let text = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(w) if !w.is_empty() => w,
+            _                        => return vec![],
+        };
+
+        match self.encode(text) {
+            Ok (c) => vec![Item {
+                bitmap: Some(c),
+                ..Item::new(text, "QR code", Action::Copy(text.to_string()))
+            }],
+            Err(e) => vec![Item::message(&e)],
+        }
+    }
+}
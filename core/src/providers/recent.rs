@@ -0,0 +1,105 @@
+use history::{self, Entry};
+use provider::{Action, Item, Provider};
+
+// The newest distinct launches that can be launched again, newest first.
+pub fn recent(entries: &[Entry], limit: usize) -> Vec<&Entry> {
+    let mut ret: Vec<&Entry> = vec![];
+
+    for e in entries.iter().rev().filter(|e| e.action.is_some()) {
+        if ret.len() == limit {
+            break;
+        }
+
+        if !ret.iter().any(|r| r.action == e.action) {
+            ret.push(e);
+        }
+    }
+
+    ret
+}
+
+// The command line (or builtin name) as the description of a relaunch row.
+pub fn describe(action: &Action) -> String {
+    match *action {
+        Action::Spawn(ref c)   => c.clone(),
+        Action::Exec(ref p)    => p.clone(),
+        Action::Builtin(ref b) => b.clone(),
+        _                      => String::new(),
+    }
+}
+
+// With nothing typed, lists the last few launches from the history log in
+// a "Recent" section above the menu.
+pub struct Recent {
+    file: String,
+    limit: usize,
+}
+
+impl Recent {
+    pub fn new(history: &history::Config) -> Recent {
+        Recent {
+            file: history.file.clone(),
+            limit: history.recent,
+        }
+    }
+}
+
+impl Provider for Recent {
+    fn query(&self, input: &str) -> Vec<Item> {
+        if !input.is_empty() || self.limit == 0 {
+            return vec![];
+        }
+
+        let entries = history::load(&self.file);
+        let recent = recent(&entries, self.limit);
+
+        if recent.is_empty() {
+            return vec![];
+        }
+
+        let mut ret = vec![Item::section("Recent")];
+
+        ret.extend(recent.into_iter().filter_map(|e| {
+            e.action.as_ref().map(|a| Item {
+                classes: vec!["recent".to_string()],
+                ..Item::new(&e.title, &describe(a), a.clone())
+            })
+        }));
+
+        // Everything after this is the regular menu
+        ret.push(Item::section("All"));
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use history::Entry;
+    use provider::Action;
+    use super::recent;
+
+    fn entry(title: &str, command: Option<&str>) -> Entry {
+        Entry {
+            time: 0,
+            keystrokes: 1,
+            title: title.to_string(),
+            action: command.map(|c| Action::Spawn(c.to_string())),
+        }
+    }
+
+    #[test]
+    fn it_lists_recent_launches() {
+        let entries = vec![
+            entry("f", Some("firefox")),
+            entry("t", Some("xterm")),
+            entry("old", None),
+            entry("f", Some("firefox")),
+        ];
+
+        let titles: Vec<&str> = recent(&entries, 5).iter().
+            map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["f", "t"]);
+
+        assert_eq!(recent(&entries, 1).len(), 1);
+    }
+}
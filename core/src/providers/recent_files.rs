@@ -0,0 +1,82 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use desktop::shell_quote;
+use fuzzy;
+use icons;
+use provider::{self, Action, Item, Provider};
+use recent_files::{self, Config, RecentFile};
+
+// How long the recent list is reused while typing
+const READ_TTL_SECS: u64 = 10;
+
+// Most rows listed before the filter narrows them down
+const LIMIT: usize = 30;
+
+// Recently used files after the prefix, e.g. "recent invoice", newest
+// first. Enter opens one with xdg-open.
+pub struct RecentFiles {
+    config: Config,
+    read: RefCell<Option<(Instant, Vec<RecentFile>)>>,
+}
+
+impl RecentFiles {
+    pub fn new(config: Config) -> RecentFiles {
+        RecentFiles {
+            config,
+            read: RefCell::new(None),
+        }
+    }
+
+    fn files(&self) -> Vec<RecentFile> {
+        let mut read = self.read.borrow_mut();
+        let fresh = read.as_ref().is_some_and(|(at, _)| {
+            at.elapsed() < Duration::from_secs(READ_TTL_SECS)
+        });
+
+        if !fresh {
+            *read = Some((Instant::now(), recent_files::files()));
+        }
+
+        read.as_ref().map(|(_, f)| f.clone()).unwrap_or_default()
+    }
+}
+
+fn item(file: &RecentFile) -> Item {
+    let path = Path::new(&file.path);
+    let name = path.file_name().map_or_else(|| path.to_string_lossy(),
+                                            |n| n.to_string_lossy());
+
+    let description = match file.application {
+        Some(ref a) => format!("{} - {}", a, file.path),
+        None        => file.path.clone(),
+    };
+
+    Item {
+        icon: Some(icons::for_path(path).to_string()),
+        ..Item::new(&name, &description, Action::Spawn(
+            format!("xdg-open {}", shell_quote(&file.path))))
+    }
+}
+
+impl Provider for RecentFiles {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let files = self.files();
+
+        // Newest first among equally good matches
+        let mut found: Vec<(usize, &RecentFile)> = files.iter().
+            filter_map(|f| {
+                let name = Path::new(&f.path).file_name()?.to_string_lossy();
+                fuzzy::score(filter, &name).map(|s| (s, f))
+            }).collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().take(LIMIT).map(|(_, f)| item(f)).collect()
+    }
+}
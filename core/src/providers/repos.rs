@@ -0,0 +1,64 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use fuzzy;
+use icons;
+use provider::{self, Action, Item, Provider};
+use repos::{self, Config};
+
+// How long a scan is reused while typing
+const SCAN_TTL_SECS: u64 = 30;
+
+// Repositories and workspace files under the roots after the prefix, e.g.
+// "proj blog". Enter opens the project in the editor.
+pub struct Repos {
+    config: Config,
+    scanned: RefCell<Option<(Instant, Vec<PathBuf>)>>,
+}
+
+impl Repos {
+    pub fn new(config: Config) -> Repos {
+        Repos {
+            config,
+            scanned: RefCell::new(None),
+        }
+    }
+
+    fn projects(&self) -> Vec<PathBuf> {
+        let mut scanned = self.scanned.borrow_mut();
+        let fresh = scanned.as_ref().is_some_and(|(at, _)| {
+            at.elapsed() < Duration::from_secs(SCAN_TTL_SECS)
+        });
+
+        if !fresh {
+            *scanned = Some((Instant::now(), repos::projects(&self.config)));
+        }
+
+        scanned.as_ref().map(|(_, p)| p.clone()).unwrap_or_default()
+    }
+}
+
+impl Provider for Repos {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let projects = self.projects();
+
+        let mut found: Vec<(usize, &PathBuf, String)> = projects.iter().
+            filter_map(|p| {
+                let name = p.file_stem()?.to_string_lossy().into_owned();
+                fuzzy::score(filter, &name).map(|s| (s, p, name))
+            }).collect();
+
+        found.sort_by_key(|&(score, _, _)| score);
+        found.into_iter().map(|(_, path, name)| Item {
+            icon: Some(icons::for_path(path).to_string()),
+            ..Item::new(&name, &path.to_string_lossy(), Action::Spawn(
+                repos::open_command(&self.config.editor, path)))
+        }).collect()
+    }
+}
@@ -0,0 +1,188 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use icons;
+use node::is_executable;
+use provider::{Action, Item, Provider};
+
+// Listing every binary makes for a slow redraw and nobody scrolls that far.
+const RESULT_LIMIT: usize = 50;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+fn default_prefix() -> String {
+    "!".to_string()
+}
+
+struct Scan {
+    // Modification time of each PATH directory when it was scanned. Adding
+    // or removing a binary touches its directory, so a change means the
+    // scan is stale.
+    stamps: Vec<(PathBuf, Option<SystemTime>)>,
+    binaries: Vec<String>,
+}
+
+// Executables on $PATH, like dmenu_run: "!fire" lists firefox and Enter
+// runs it, passing along anything typed after the name.
+pub struct Run {
+    config: Config,
+    scan: RefCell<Option<Scan>>,
+}
+
+impl Run {
+    pub fn new(config: Config) -> Run {
+        Run {
+            config,
+            scan: RefCell::new(None),
+        }
+    }
+}
+
+fn path_dirs() -> Vec<PathBuf> {
+    match env::var_os("PATH") {
+        Some(p) => env::split_paths(&p).collect(),
+        None    => vec![],
+    }
+}
+
+fn stamps(dirs: &[PathBuf]) -> Vec<(PathBuf, Option<SystemTime>)> {
+    dirs.iter().map(|d| {
+        (d.clone(), fs::metadata(d).and_then(|m| m.modified()).ok())
+    }).collect()
+}
+
+// Sorted, de-duplicated names of the executables in the given directories.
+pub fn scan_dirs(dirs: &[PathBuf]) -> Vec<String> {
+    let mut names = BTreeSet::new();
+
+    for dir in dirs {
+        let entries = match fs::read_dir(dir) {
+            Ok (e) => e,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            if !is_executable(&entry.path()) {
+                continue;
+            }
+
+            if let Ok(name) = entry.file_name().into_string() {
+                names.insert(name);
+            }
+        }
+    }
+
+    names.into_iter().collect()
+}
+
+// Names starting with the filter come first, then ones containing it.
+pub fn matching<'a>(binaries: &'a [String], filter: &str) -> Vec<&'a String> {
+    let (mut ret, rest): (Vec<&String>, Vec<&String>) = binaries.iter().
+        filter(|b| b.contains(filter)).
+        partition(|b| b.starts_with(filter));
+
+    ret.extend(rest);
+    ret.truncate(RESULT_LIMIT);
+    ret
+}
+
+impl Provider for Run {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let rest = match input.strip_prefix(self.config.prefix.as_str()) {
+            Some(r) => r,
+            None    => return vec![],
+        };
+
+        let mut words = rest.trim_start().splitn(2, ' ');
+        let filter = words.next().unwrap_or("");
+        let args = words.next().unwrap_or("").trim();
+
+        let dirs = path_dirs();
+        let current = stamps(&dirs);
+
+        let mut scan = self.scan.borrow_mut();
+
+        let stale = match *scan {
+            Some(ref s) => s.stamps != current,
+            None        => true,
+        };
+
+        if stale {
+            *scan = Some(Scan {
+                stamps: current,
+                binaries: scan_dirs(&dirs),
+            });
+        }
+
+        let binaries = match *scan {
+            Some(ref s) => &s.binaries,
+            None        => return vec![],
+        };
+
+        matching(binaries, filter).into_iter().map(|b| {
+            let command = if args.is_empty() {
+                b.clone()
+            } else {
+                format!("{} {}", b, args)
+            };
+
+            Item {
+                icon: Some(icons::SHELL.to_string()),
+                ..Item::new(b, "command", Action::Spawn(command))
+            }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::{self, File};
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use std::process;
+    use super::{matching, scan_dirs};
+
+    fn binary(dir: &Path, name: &str, mode: u32) {
+        let path = dir.join(name);
+        File::create(&path).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).
+            unwrap();
+    }
+
+    #[test]
+    fn it_scans_and_dedupes_executables() {
+        let root = env::temp_dir().
+            join(format!("blaunch-run-test.{}", process::id()));
+        let a = root.join("a");
+        let b = root.join("b");
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+
+        binary(&a, "vim", 0o755);
+        binary(&a, "notes.txt", 0o644);
+        binary(&b, "vim", 0o755);
+        binary(&b, "htop", 0o755);
+
+        let found = scan_dirs(&[a, b, root.join("missing")]);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(found, vec!["htop", "vim"]);
+    }
+
+    #[test]
+    fn it_lists_prefix_matches_first() {
+        let binaries: Vec<String> = vec!["gvim", "vi", "vim"].into_iter().
+            map(|b| b.to_string()).collect();
+
+        assert_eq!(matching(&binaries, "vi"), vec!["vi", "vim", "gvim"]);
+    }
+}
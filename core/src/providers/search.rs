@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+
+use icons::Favicons;
+use provider::{Action, Item, Provider};
+use search::{self, Config};
+use suggest::{Suggester, Suggestions};
+
+// Web searches on the configured engine keywords, with the site's icon and,
+// when a suggest endpoint is set, suggestions fetched as you type.
+pub struct Search {
+    config: Config,
+    favicons: Favicons,
+    suggester: Option<Suggester>,
+    // The query suggestions were last asked for, and what came back
+    requested: RefCell<String>,
+    suggestions: RefCell<Vec<String>>,
+}
+
+impl Search {
+    pub fn new(config: Config) -> Search {
+        let suggester = config.suggest.as_ref().map(|u| Suggester::new(u));
+
+        Search {
+            config,
+            favicons: Favicons::new(),
+            suggester,
+            requested: RefCell::new(String::new()),
+            suggestions: RefCell::new(vec![]),
+        }
+    }
+
+    fn suggest(&self, query: &str) {
+        let suggester = match self.suggester {
+            Some(ref s) => s,
+            None        => return,
+        };
+
+        if *self.requested.borrow() == query {
+            return;
+        }
+
+        *self.requested.borrow_mut() = query.to_string();
+        self.suggestions.borrow_mut().clear();
+        suggester.request(query);
+    }
+}
+
+impl Provider for Search {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let found = match search::parse(&self.config, input) {
+            Some(s) => s,
+            None    => {
+                self.suggest("");
+                return vec![];
+            },
+        };
+
+        if found.query.is_empty() {
+            self.suggest("");
+            return vec![Item::message(&format!("Search {}", found.engine))];
+        }
+
+        self.suggest(found.query);
+
+        let icon = self.favicons.icon(found.url);
+        let item = |query: &str, description: &str| Item {
+            icon: Some(icon.clone()),
+            ..Item::new(query, description,
+                        Action::Spawn(search::command(found.url, query)))
+        };
+
+        let description = format!("search {}", found.engine);
+        let mut items = vec![item(found.query, &description)];
+
+        items.extend(self.suggestions.borrow().iter().
+            filter(|s| s.as_str() != found.query).
+            map(|s| item(s, "suggestion")));
+
+        items
+    }
+
+    fn poll(&self) -> bool {
+        let suggestions = match self.suggester.as_ref().and_then(|s| s.poll()) {
+            Some(Suggestions::Items(i)) => i,
+            _                           => return false,
+        };
+
+        *self.suggestions.borrow_mut() = suggestions;
+        true
+    }
+}
@@ -0,0 +1,43 @@
+use desktop;
+use provider::{Action, Item, Provider};
+use snippets::{self, Config};
+
+// Snippets by name after the prefix, e.g. ";addr". Enter copies the
+// expansion, or types it when the config says so.
+pub struct Snippets {
+    config: Config,
+}
+
+impl Snippets {
+    pub fn new(config: Config) -> Snippets {
+        Snippets { config }
+    }
+
+    fn item(&self, name: &str, text: &str) -> Item {
+        let action = if self.config.type_it {
+            Action::Spawn(desktop::type_command(text))
+        } else {
+            Action::Copy(text.to_string())
+        };
+
+        // Only the first line of a long expansion fits the description
+        let preview = text.lines().next().unwrap_or("");
+
+        Item {
+            icon: Some("edit-paste".to_string()),
+            ..Item::new(name, preview, action)
+        }
+    }
+}
+
+impl Provider for Snippets {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let query = match input.strip_prefix(self.config.prefix.as_str()) {
+            Some(q) => q.trim(),
+            None    => return vec![],
+        };
+
+        snippets::search(&self.config, query).into_iter().
+            map(|(name, text)| self.item(name, text)).collect()
+    }
+}
@@ -0,0 +1,54 @@
+use std::cell::RefCell;
+
+use cli;
+use desktop::shell_quote;
+use fuzzy;
+use icons;
+use provider::{self, Action, Item, Provider};
+use ssh::{self, Config};
+
+// Hosts from the ssh config and known_hosts after the prefix, e.g.
+// "ssh nas", each opening a terminal connected to it. The files are read
+// the first time they're needed.
+pub struct Ssh {
+    config: Config,
+    hosts: RefCell<Option<Vec<String>>>,
+}
+
+impl Ssh {
+    pub fn new(config: Config) -> Ssh {
+        Ssh {
+            config,
+            hosts: RefCell::new(None),
+        }
+    }
+
+    fn item(&self, host: &str) -> Item {
+        let command = cli::in_terminal(&self.config.terminal,
+                                       &format!("ssh {}", shell_quote(host)));
+
+        Item {
+            icon: Some(icons::SHELL.to_string()),
+            ..Item::new(host, "ssh", Action::Spawn(command))
+        }
+    }
+}
+
+impl Provider for Ssh {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let mut hosts = self.hosts.borrow_mut();
+        let hosts = hosts.get_or_insert_with(ssh::hosts);
+
+        let mut found: Vec<(usize, &String)> = hosts.iter().
+            filter_map(|h| fuzzy::score(filter, h).map(|s| (s, h))).
+            collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, h)| self.item(h)).collect()
+    }
+}
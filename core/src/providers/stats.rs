@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use history::{self, Entry};
+use provider::{Item, Provider};
+
+pub const PREFIX: &str = "stats";
+
+const WEEK: u64 = 7 * 24 * 60 * 60;
+
+// Rough guess at what a launch saves over digging through app menus or
+// typing the command into a terminal.
+const SECONDS_SAVED_PER_LAUNCH: u64 = 5;
+
+const TOP_LIMIT: usize = 5;
+
+#[derive(PartialEq, Debug)]
+pub struct Summary {
+    pub launches: usize,
+    pub keystrokes_per_launch: f64,
+    pub seconds_saved: u64,
+    pub top: Vec<(String, usize)>,
+}
+
+// Summarizes the entries from the week leading up to `now`.
+pub fn summarize(entries: &[Entry], now: u64) -> Summary {
+    let week: Vec<&Entry> = entries.iter().
+        filter(|e| e.time <= now && now - e.time < WEEK).collect();
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for e in &week {
+        *counts.entry(&e.title).or_insert(0) += 1;
+    }
+
+    let mut top: Vec<(String, usize)> = counts.into_iter().
+        map(|(t, c)| (t.to_string(), c)).collect();
+    top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    top.truncate(TOP_LIMIT);
+
+    let keystrokes: usize = week.iter().map(|e| e.keystrokes).sum();
+
+    Summary {
+        launches: week.len(),
+        keystrokes_per_launch: if week.is_empty() {
+            0.0
+        } else {
+            keystrokes as f64 / week.len() as f64
+        },
+        seconds_saved: week.len() as u64 * SECONDS_SAVED_PER_LAUNCH,
+        top,
+    }
+}
+
+fn row(title: &str, description: &str) -> Item {
+    Item {
+        title: title.to_string(),
+        description: description.to_string(),
+        ..Default::default()
+    }
+}
+
+// Typing "stats" shows the past week of the history log.
+pub struct Stats {
+    file: String,
+}
+
+impl Stats {
+    pub fn new(history: &history::Config) -> Stats {
+        Stats { file: history.file.clone() }
+    }
+}
+
+impl Provider for Stats {
+    fn query(&self, input: &str) -> Vec<Item> {
+        if input.trim() != PREFIX {
+            return vec![];
+        }
+
+        let summary = summarize(&history::load(&self.file), history::now());
+
+        let mut ret = vec![
+            row("launches this week", &summary.launches.to_string()),
+            row("keystrokes per launch",
+                &format!("{:.1}", summary.keystrokes_per_launch)),
+            row("time saved",
+                &format!("~{} min", summary.seconds_saved / 60)),
+        ];
+
+        ret.extend(summary.top.iter().
+            map(|&(ref t, c)| row(t, &format!("{} launches", c))));
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use history::Entry;
+    use super::summarize;
+
+    fn entry(time: u64, keystrokes: usize, title: &str) -> Entry {
+        Entry { time, keystrokes, title: title.to_string(), action: None }
+    }
+
+    #[test]
+    fn it_summarizes_the_past_week() {
+        let now = 1_000_000;
+        let entries = vec![
+            entry(now - 8 * 24 * 60 * 60, 9, "old"),
+            entry(now - 60, 2, "terminal"),
+            entry(now - 30, 4, "firefox"),
+            entry(now - 10, 3, "terminal"),
+        ];
+
+        let summary = summarize(&entries, now);
+
+        assert_eq!(summary.launches, 3);
+        assert_eq!(summary.keystrokes_per_launch, 3.0);
+        assert_eq!(summary.seconds_saved, 15);
+        assert_eq!(summary.top, vec![
+            ("terminal".to_string(), 2),
+            ("firefox".to_string(), 1),
+        ]);
+    }
+}
@@ -0,0 +1,45 @@
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+use steam::{self, Config, Game};
+
+// Installed Steam games after the prefix, e.g. "game portal". Enter has
+// Steam start the game.
+pub struct Steam {
+    config: Config,
+}
+
+impl Steam {
+    pub fn new(config: Config) -> Steam {
+        Steam { config }
+    }
+}
+
+fn item(game: &Game) -> Item {
+    Item {
+        // The icon Steam installs with its desktop shortcuts
+        icon: Some(format!("steam_icon_{}", game.id)),
+        ..Item::new(&game.name, "steam game",
+                    Action::Spawn(steam::launch_command(game)))
+    }
+}
+
+impl Provider for Steam {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let games = match steam::root(&self.config) {
+            Some(root) => steam::games(&root),
+            None       => return vec![Item::message("Steam isn't installed")],
+        };
+
+        let mut found: Vec<(usize, &Game)> = games.iter().
+            filter_map(|g| fuzzy::score(filter, &g.name).map(|s| (s, g))).
+            collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, g)| item(g)).collect()
+    }
+}
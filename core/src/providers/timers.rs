@@ -0,0 +1,118 @@
+use std::cell::Cell;
+
+use daemon;
+use provider::{self, Action, Item, Provider};
+use timers::{self, Config};
+
+const ICON: &str = "alarm";
+
+// "timer 10m tea" starts a countdown and "timer stopwatch" a stopwatch;
+// the prefix alone lists what's running, and picking one cancels it. The
+// daemon sends the notifications, so without one timers wait until it
+// starts.
+pub struct Timers {
+    config: Config,
+    // The second the listing was last drawn for, while it's showing
+    listed: Cell<Option<u64>>,
+}
+
+impl Timers {
+    pub fn new(config: Config) -> Timers {
+        Timers {
+            config,
+            listed: Cell::new(None),
+        }
+    }
+
+    fn start_item(&self, title: &str, seconds: Option<u64>, label: &str)
+        -> Item {
+        let description = if daemon::running() {
+            "start"
+        } else {
+            "start, goes off once blaunch --daemon runs"
+        };
+
+        Item {
+            icon: Some(ICON.to_string()),
+            ..Item::new(title, description, Action::Builtin(
+                timers::start_builtin(seconds, label)))
+        }
+    }
+
+    fn listing(&self) -> Vec<Item> {
+        let now = timers::now();
+        self.listed.set(Some(now));
+
+        let found = timers::list();
+
+        if found.is_empty() {
+            return vec![Item::message("Type a duration like 10m, or \
+                                       stopwatch")];
+        }
+
+        found.iter().map(|t| {
+            let (title, description) = match t.due() {
+                Some(d) => (format!("Cancel {}", t.name()),
+                            format!("{} left",
+                                    timers::format_duration(
+                                        d.saturating_sub(now)))),
+                None    => (format!("Stop {}", t.name()),
+                            format!("running {}",
+                                    timers::format_duration(
+                                        now.saturating_sub(t.started)))),
+            };
+
+            Item {
+                icon: Some(ICON.to_string()),
+                ..Item::new(&title, &description,
+                            Action::Builtin(timers::cancel_builtin(t.id)))
+            }
+        }).collect()
+    }
+}
+
+impl Provider for Timers {
+    fn query(&self, input: &str) -> Vec<Item> {
+        self.listed.set(None);
+
+        let rest = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        if rest.is_empty() {
+            return self.listing();
+        }
+
+        let (first, label) = rest.split_once(' ').unwrap_or((rest, ""));
+        let label = label.trim();
+
+        if first == "stopwatch" {
+            let title = match label {
+                "" => "Start a stopwatch".to_string(),
+                l  => format!("Start a stopwatch: {}", l),
+            };
+
+            return vec![self.start_item(&title, None, label)];
+        }
+
+        let seconds = match timers::parse_duration(first) {
+            Some(s) => s,
+            None    => return vec![Item::message(
+                "Type a duration like 10m, 1h30m or 90s")],
+        };
+
+        let length = timers::format_duration(seconds);
+        let title = match label {
+            "" => format!("Start a {} timer", length),
+            l  => format!("Start a {} timer: {}", length, l),
+        };
+
+        vec![self.start_item(&title, Some(seconds), label)]
+    }
+
+    // Counts the listing down while it's showing
+    fn poll(&self) -> bool {
+        self.listed.get().is_some_and(|at| at != timers::now())
+    }
+}
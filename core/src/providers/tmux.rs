@@ -0,0 +1,72 @@
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+use tmux::{self, Config, Window};
+
+// tmux sessions and windows after the prefix, e.g. "tmux build". Each
+// session is listed before its windows; Enter attaches a terminal to it,
+// or switches the client when blaunch itself runs inside tmux.
+pub struct Tmux {
+    config: Config,
+}
+
+impl Tmux {
+    pub fn new(config: Config) -> Tmux {
+        Tmux { config }
+    }
+
+    fn item(&self, title: &str, description: &str, target: &str) -> Item {
+        Item {
+            icon: Some("utilities-terminal".to_string()),
+            ..Item::new(title, description, Action::Spawn(
+                tmux::attach_command(&self.config.terminal, target)))
+        }
+    }
+}
+
+impl Provider for Tmux {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let windows = match tmux::windows() {
+            Ok (w) => w,
+            Err(e) => return vec![Item::message(&e)],
+        };
+
+        let mut found: Vec<(usize, Item)> = vec![];
+        let mut sessions: Vec<&str> = vec![];
+
+        for w in windows.iter() {
+            if !sessions.contains(&w.session.as_str()) {
+                sessions.push(&w.session);
+
+                let count = windows.iter().
+                    filter(|o| o.session == w.session).count();
+                let state = if w.attached { ", attached" } else { "" };
+
+                if let Some(score) = fuzzy::score(filter, &w.session) {
+                    found.push((score, self.item(
+                        &w.session,
+                        &format!("session, {} windows{}", count, state),
+                        &w.session)));
+                }
+            }
+
+            let title = window_title(w);
+
+            if let Some(score) = fuzzy::score(filter, &title) {
+                found.push((score, self.item(&title, "window", &w.target())));
+            }
+        }
+
+        // Stable, so sessions stay ahead of their windows on equal scores
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+fn window_title(window: &Window) -> String {
+    format!("{}:{} {}", window.session, window.index, window.name)
+}
@@ -0,0 +1,61 @@
+use paths::expand_home;
+use provider::{self, Action, Item, Provider};
+use todo::{self, Config};
+
+// Pending tasks from todo.txt after the prefix, e.g. "todo taxes". The
+// typed text can be added as a new task with the first row; picking a task
+// marks it done.
+pub struct Todo {
+    config: Config,
+}
+
+impl Todo {
+    pub fn new(config: Config) -> Todo {
+        Todo {
+            config,
+        }
+    }
+}
+
+impl Provider for Todo {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let text = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let file = expand_home(&self.config.file);
+        let mut items = vec![];
+
+        if !text.is_empty() {
+            items.push(Item {
+                icon: Some("list-add".to_string()),
+                ..Item::new(&format!("Add task: {}", text), "todo",
+                            Action::Spawn(todo::add_command(&file, text)))
+            });
+        }
+
+        let filter = text.to_lowercase();
+
+        items.extend(todo::tasks(&self.config).iter().
+            filter(|t| t.text.to_lowercase().contains(&filter)).
+            map(|t| {
+                let description = match t.priority {
+                    Some(p) => format!("mark done, priority {}", p),
+                    None    => "mark done".to_string(),
+                };
+
+                Item {
+                    icon: Some("checkbox".to_string()),
+                    ..Item::new(&t.text, &description,
+                                Action::Spawn(todo::done_command(&file, t)))
+                }
+            }));
+
+        if items.is_empty() {
+            items.push(Item::message("Type a task to add it"));
+        }
+
+        items
+    }
+}
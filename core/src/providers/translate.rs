@@ -0,0 +1,117 @@
+use std::cell::RefCell;
+use std::time::Duration;
+
+use network;
+use provider::{Action, Item, Provider};
+use translate::{self, Config, Finished, Translation, Translator};
+
+// Translations are kept while the same text is typed again
+const CACHE_SECS: u64 = 600;
+
+// Translates the text after the prefix and language, e.g. "tr de good
+// morning", showing the translation inline. Enter copies it.
+pub struct Translate {
+    config: Config,
+    translator: Translator,
+    monitor: network::Monitor,
+    // The request a worker was last started for
+    requested: RefCell<String>,
+    // Translations that arrived since the last poll, until they're shown.
+    // The registry caches them from there.
+    arrived: RefCell<Vec<Finished>>,
+}
+
+impl Translate {
+    pub fn new(config: Config) -> Translate {
+        Translate {
+            translator: Translator::new(config.clone()),
+            config,
+            monitor: network::Monitor::new(),
+            requested: RefCell::new(String::new()),
+            arrived: RefCell::new(vec![]),
+        }
+    }
+
+    fn cancel(&self) {
+        self.requested.borrow_mut().clear();
+        self.translator.cancel();
+    }
+}
+
+fn item(lang: &str, translation: &Translation) -> Item {
+    let description = match translation.from {
+        Some(ref f) => format!("translated from {} to {}", f, lang),
+        None        => format!("translated to {}", lang),
+    };
+
+    Item {
+        icon: Some("preferences-desktop-locale".to_string()),
+        ..Item::new(&translation.text, &description,
+                    Action::Copy(translation.text.clone()))
+    }
+}
+
+impl Provider for Translate {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let request = match translate::parse(&self.config.prefix, input) {
+            Some(r) => r,
+            None    => {
+                self.cancel();
+                return vec![];
+            },
+        };
+
+        if request.text.is_empty() {
+            self.cancel();
+            return vec![Item::message(&format!("Translate to {}",
+                                               request.lang))];
+        }
+
+        let key = request.key();
+
+        let arrived = {
+            let mut arrived = self.arrived.borrow_mut();
+            arrived.iter().position(|f| f.0 == key).
+                map(|i| arrived.remove(i).1)
+        };
+
+        match arrived {
+            Some(Ok (t)) => return vec![item(&request.lang, &t)],
+            Some(Err(e)) => return vec![Item::message(&e)],
+            None         => {},
+        };
+
+        if !self.monitor.is_online() {
+            self.cancel();
+            return vec![Item::message(&network::offline_description(
+                "Can't translate"))];
+        }
+
+        if *self.requested.borrow() != key {
+            *self.requested.borrow_mut() = key;
+            self.translator.request(&request);
+        }
+
+        vec![Item::loading("Translating..")]
+    }
+
+    fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(CACHE_SECS)
+    }
+
+    fn poll(&self) -> bool {
+        let finished = self.translator.poll();
+
+        if finished.is_empty() {
+            return false;
+        }
+
+        // So it's translated again once the cached one expires
+        if finished.iter().any(|f| *self.requested.borrow() == f.0) {
+            self.requested.borrow_mut().clear();
+        }
+
+        *self.arrived.borrow_mut() = finished;
+        true
+    }
+}
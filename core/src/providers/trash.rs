@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use fuzzy;
+use icons;
+use provider::{self, Action, Item, Provider};
+use trash::{self, Config, Entry};
+
+// What the confirm step is for
+const EMPTY: &str = "empty";
+
+// Most rows listed before the filter narrows them down
+const LIMIT: usize = 30;
+
+// Trashed files after the prefix, e.g. "trash report", newest first. Enter
+// puts one back where it was deleted from. The first row empties the
+// trash, after a confirm step at "trash! empty".
+pub struct Trash {
+    config: Config,
+}
+
+impl Trash {
+    pub fn new(config: Config) -> Trash {
+        Trash { config }
+    }
+}
+
+fn item(dir: &Path, entry: &Entry) -> Item {
+    let path = Path::new(&entry.path);
+    let name = path.file_name().map_or_else(|| path.to_string_lossy(),
+                                            |n| n.to_string_lossy());
+    let icon = Some(icons::for_path(path).to_string());
+    let deleted = entry.deleted.replacen('T', " ", 1);
+
+    // Restoring would fail with something else in the way
+    if path.exists() {
+        return Item {
+            icon,
+            description: format!("{} is taken", entry.path),
+            ..Item::message(&name)
+        };
+    }
+
+    Item {
+        icon,
+        ..Item::new(&name, &format!("{}, deleted {}", entry.path, deleted),
+                    Action::Spawn(trash::restore_command(dir, entry)))
+    }
+}
+
+impl Provider for Trash {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let dir = trash::dir();
+
+        if provider::confirming(&self.config.prefix, input) == Some(EMPTY) {
+            return vec![Item {
+                icon: Some("user-trash-full".to_string()),
+                classes: vec!["danger".to_string()],
+                ..Item::new("Really empty the trash?", "trash",
+                            Action::Spawn(trash::empty_command(&dir)))
+            }];
+        }
+
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let entries = trash::entries(&dir);
+
+        if entries.is_empty() {
+            return vec![Item {
+                icon: Some("user-trash".to_string()),
+                ..Item::message("The trash is empty")
+            }];
+        }
+
+        let mut items = vec![];
+
+        if filter.is_empty() {
+            items.push(Item {
+                icon: Some("user-trash-full".to_string()),
+                ..Item::new("Empty the trash",
+                            &format!("{} items", entries.len()),
+                            provider::confirm(&self.config.prefix, EMPTY))
+            });
+        }
+
+        // Newest first among equally good matches
+        let mut found: Vec<(usize, &Entry)> = entries.iter().
+            filter_map(|e| {
+                let name = Path::new(&e.path).file_name()?.to_string_lossy();
+                fuzzy::score(filter, &name).map(|s| (s, e))
+            }).collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        items.extend(found.into_iter().take(LIMIT).map(|(_, e)| item(&dir, e)));
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use provider::{Action, Provider};
+    use trash::Config;
+    use super::Trash;
+
+    #[test]
+    fn it_confirms_before_emptying_the_trash() {
+        let trash = Trash::new(Config { prefix: "trash".to_string() });
+
+        let items = trash.query("trash! empty");
+        assert_eq!(items[0].title, "Really empty the trash?");
+        assert!(matches!(items[0].action, Some(Action::Spawn(_))));
+
+        assert!(trash.query("trash! other").iter().all(|i| {
+            i.title != "Really empty the trash?"
+        }));
+    }
+}
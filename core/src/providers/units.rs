@@ -0,0 +1,105 @@
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+use units::{self, Config, Unit};
+
+// How long a unit listing is reused while typing
+const LIST_TTL_SECS: u64 = 5;
+
+const VERBS: &[&str] = &["start", "stop", "restart"];
+
+// systemd units after the prefix, e.g. "sv sync". Picking a unit goes to
+// "sv <unit> " with start, stop, restart and status, and status shows
+// systemctl's output in the results.
+pub struct Units {
+    config: Config,
+    listed: RefCell<Option<(Instant, Vec<Unit>)>>,
+}
+
+impl Units {
+    pub fn new(config: Config) -> Units {
+        Units {
+            config,
+            listed: RefCell::new(None),
+        }
+    }
+
+    fn units(&self) -> Result<Vec<Unit>, String> {
+        let mut listed = self.listed.borrow_mut();
+        let fresh = listed.as_ref().is_some_and(|(at, _)| {
+            at.elapsed() < Duration::from_secs(LIST_TTL_SECS)
+        });
+
+        if !fresh {
+            *listed = Some((Instant::now(), units::list()?));
+        }
+
+        Ok(listed.as_ref().map(|(_, u)| u.clone()).unwrap_or_default())
+    }
+
+    fn path(&self, unit: &Unit, rest: &str) -> String {
+        format!("{} {} {}", self.config.prefix, unit.unit, rest)
+    }
+
+    fn unit_item(&self, unit: &Unit) -> Item {
+        let scope = if unit.user { "user" } else { "system" };
+        let next = self.path(unit, "");
+
+        Item {
+            completion: Some(next.clone()),
+            classes: vec![unit.active.clone()],
+            ..Item::new(&unit.unit,
+                        &format!("{} {} ({})", scope, unit.sub, unit.active),
+                        Action::Query(next))
+        }
+    }
+
+    fn verbs(&self, unit: &Unit, filter: &str) -> Vec<Item> {
+        if filter == "status" {
+            return units::status(unit).lines().map(Item::message).collect();
+        }
+
+        let mut items: Vec<Item> = VERBS.iter().
+            filter(|v| v.starts_with(filter)).
+            map(|v| Item::new(v, &unit.unit,
+                              Action::Spawn(units::command(unit, v)))).
+            collect();
+
+        if "status".starts_with(filter) {
+            items.push(Item::new("status", &unit.unit,
+                                 Action::Query(self.path(unit, "status"))));
+        }
+
+        items
+    }
+}
+
+impl Provider for Units {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_rest(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let units = match self.units() {
+            Ok (u) => u,
+            Err(e) => return vec![Item::message(&e)],
+        };
+
+        // "<unit> <verb>" once a unit's been picked
+        if let Some((name, verb)) = filter.split_once(' ') {
+            if let Some(unit) = units.iter().find(|u| u.unit == name) {
+                return self.verbs(unit, verb.trim());
+            }
+        }
+
+        let mut found: Vec<(usize, &Unit)> = units.iter().
+            filter_map(|u| fuzzy::score(filter, &u.unit).map(|s| (s, u))).
+            collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, u)| self.unit_item(u)).collect()
+    }
+}
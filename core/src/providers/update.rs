@@ -0,0 +1,41 @@
+use std::cell::RefCell;
+
+use desktop::shell_quote;
+use icons;
+use provider::{Action, Item, Provider};
+use update::{Checker, Config};
+
+// A row above the menu when a newer release is out, opening its changelog.
+pub struct Update {
+    checker: RefCell<Checker>,
+}
+
+impl Update {
+    pub fn new(config: Config, current: &str) -> Update {
+        Update { checker: RefCell::new(Checker::new(config, current)) }
+    }
+}
+
+impl Provider for Update {
+    fn query(&self, input: &str) -> Vec<Item> {
+        if !input.is_empty() {
+            return vec![];
+        }
+
+        match self.checker.borrow().available() {
+            Some(r) => vec![Item {
+                icon: Some(icons::WEB.to_string()),
+                classes: vec!["update".to_string()],
+                ..Item::new(&format!("blaunch {} available", r.version),
+                            "view changelog",
+                            Action::Spawn(format!("xdg-open {}",
+                                                  shell_quote(&r.url))))
+            }],
+            None    => vec![],
+        }
+    }
+
+    fn poll(&self) -> bool {
+        self.checker.borrow_mut().poll()
+    }
+}
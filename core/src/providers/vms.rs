@@ -0,0 +1,74 @@
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+use vms::{self, Config, Vm};
+
+// libvirt machines after the prefix, e.g. "vm deb", with their state.
+// Picking one goes to "vm <name> " with start for a stopped machine and
+// shutdown and console for a running one.
+pub struct Vms {
+    config: Config,
+}
+
+impl Vms {
+    pub fn new(config: Config) -> Vms {
+        Vms { config }
+    }
+
+    fn path(&self, vm: &Vm) -> String {
+        format!("{} {} ", self.config.prefix, vm.name)
+    }
+
+    fn vm_item(&self, vm: &Vm) -> Item {
+        let next = self.path(vm);
+
+        Item {
+            icon: Some("computer".to_string()),
+            completion: Some(next.clone()),
+            classes: vec![vm.state.replace(' ', "-")],
+            ..Item::new(&vm.name, &vm.state, Action::Query(next))
+        }
+    }
+
+    fn verbs(&self, vm: &Vm, filter: &str) -> Vec<Item> {
+        let actions = if vm.running() {
+            vec![
+                ("shutdown", vms::command(&self.config, vm, "shutdown")),
+                ("console", vms::console_command(&self.config, vm)),
+            ]
+        } else {
+            vec![("start", vms::command(&self.config, vm, "start"))]
+        };
+
+        actions.into_iter().filter(|&(v, _)| v.starts_with(filter)).
+            map(|(v, command)| Item::new(v, &vm.name, Action::Spawn(command))).
+            collect()
+    }
+}
+
+impl Provider for Vms {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_rest(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let vms = match vms::list(&self.config) {
+            Ok (v) => v,
+            Err(e) => return vec![Item::message(&e)],
+        };
+
+        // "<name> <verb>" once a machine's been picked
+        if let Some((name, verb)) = filter.split_once(' ') {
+            if let Some(vm) = vms.iter().find(|v| v.name == name) {
+                return self.verbs(vm, verb.trim());
+            }
+        }
+
+        let mut found: Vec<(usize, &Vm)> = vms.iter().
+            filter_map(|v| fuzzy::score(filter, &v.name).map(|s| (s, v))).
+            collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, v)| self.vm_item(v)).collect()
+    }
+}
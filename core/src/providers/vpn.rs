@@ -0,0 +1,53 @@
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+use vpn::{self, Config, Vpn};
+
+// VPN connections after the prefix, e.g. "vpn office", with whether each is
+// connected. Enter brings a VPN up or down, as a job so progress shows.
+pub struct Vpns {
+    config: Config,
+}
+
+impl Vpns {
+    pub fn new(config: Config) -> Vpns {
+        Vpns { config }
+    }
+
+    fn item(&self, vpn: &Vpn) -> Item {
+        let (state, icon, classes) = if vpn.active {
+            ("connected", "network-vpn", vec!["active".to_string()])
+        } else {
+            ("disconnected", "network-vpn-disconnected", vec![])
+        };
+
+        Item {
+            icon: Some(icon.to_string()),
+            classes,
+            wait: true,
+            ..Item::new(&vpn.name, &format!("{} ({})", state, vpn.kind()),
+                        Action::Spawn(vpn::toggle_command(&self.config, vpn)))
+        }
+    }
+}
+
+impl Provider for Vpns {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let vpns = vpn::vpns(&self.config);
+
+        if vpns.is_empty() {
+            return vec![Item::message("No VPN connections configured")];
+        }
+
+        let mut found: Vec<(usize, &Vpn)> = vpns.iter().
+            filter_map(|v| fuzzy::score(filter, &v.name).map(|s| (s, v))).
+            collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, v)| self.item(v)).collect()
+    }
+}
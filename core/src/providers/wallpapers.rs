@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use desktop::{self, WallpaperConfig};
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+
+// Images in the wallpapers directory after the prefix, e.g. "wall beach",
+// with a thumbnail unless previews are turned off. Enter sets the
+// wallpaper. The directory is listed fresh each time so new images show up.
+pub struct Wallpapers {
+    config: WallpaperConfig,
+}
+
+impl Wallpapers {
+    pub fn new(config: WallpaperConfig) -> Wallpapers {
+        Wallpapers { config }
+    }
+}
+
+impl Provider for Wallpapers {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let environment = desktop::detect_environment();
+        let wallpapers = desktop::wallpapers(&self.config.directory);
+
+        let mut found: Vec<(usize, &PathBuf, String)> = wallpapers.iter().
+            filter_map(|p| {
+                let name = desktop::wallpaper_name(p);
+                fuzzy::score(filter, &name).map(|s| (s, p, name))
+            }).collect();
+
+        found.sort_by_key(|&(score, _, _)| score);
+        found.into_iter().map(|(_, path, name)| {
+            let command = desktop::set_wallpaper_command(&self.config,
+                                                         environment, path);
+
+            Item {
+                preview: if self.config.previews {
+                    Some(path.to_string_lossy().into_owned())
+                } else {
+                    None
+                },
+                ..Item::new(&name, "wallpaper", Action::Spawn(command))
+            }
+        }).collect()
+    }
+}
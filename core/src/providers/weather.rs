@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+use std::time::Duration;
+
+use desktop::shell_quote;
+use network;
+use provider::{self, Action, Item, Provider};
+use weather::{self, Config, Fetched, Forecaster, Report};
+
+// The weather after the prefix, e.g. "weather lisbon", or for the
+// configured city with just the prefix: the conditions as the title and
+// the details and forecast under it. Enter opens the full report.
+pub struct Weather {
+    config: Config,
+    forecaster: Forecaster,
+    monitor: network::Monitor,
+    // The city a worker is fetching for, until its report arrives
+    requested: RefCell<String>,
+    // Reports that arrived since the last poll, until they're shown. The
+    // registry caches them from there.
+    arrived: RefCell<Vec<Fetched>>,
+}
+
+impl Weather {
+    pub fn new(config: Config) -> Weather {
+        Weather {
+            forecaster: Forecaster::new(config.clone()),
+            config,
+            monitor: network::Monitor::new(),
+            requested: RefCell::new(String::new()),
+            arrived: RefCell::new(vec![]),
+        }
+    }
+
+    fn cancel(&self) {
+        self.requested.borrow_mut().clear();
+        self.forecaster.cancel();
+    }
+
+    fn item(&self, city: &str, report: &Report) -> Item {
+        let (degrees, speed) = if self.config.imperial {
+            ("°F", "mph")
+        } else {
+            ("°C", "km/h")
+        };
+
+        let title = format!("{}{}, {}", report.temperature, degrees,
+                            report.conditions);
+
+        let mut body = format!("Feels like {}{}, humidity {}%, wind {} {} {}",
+                               report.feels_like, degrees, report.humidity,
+                               report.wind, speed, report.wind_direction);
+
+        for d in report.days.iter() {
+            body.push_str(&format!("\n{}: {} to {}{}", d.date, d.low, d.high,
+                                   degrees));
+        }
+
+        let place = if report.place.is_empty() { city } else { &report.place };
+        let page = weather::fill(&self.config.page, city);
+
+        Item {
+            icon: Some(weather::icon(&report.conditions).to_string()),
+            body: Some(body),
+            ..Item::new(&title, place, Action::Spawn(
+                format!("xdg-open {}", shell_quote(&page))))
+        }
+    }
+}
+
+impl Provider for Weather {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let city = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(c) => c,
+            None    => {
+                self.cancel();
+                return vec![];
+            },
+        };
+
+        let city = if city.is_empty() { &self.config.city } else { city };
+
+        let arrived = {
+            let mut arrived = self.arrived.borrow_mut();
+            arrived.iter().position(|f| f.0 == city).
+                map(|i| arrived.remove(i).1)
+        };
+
+        match arrived {
+            Some(Ok (r)) => return vec![self.item(city, &r)],
+            Some(Err(e)) => return vec![Item::message(&e)],
+            None         => {},
+        };
+
+        if !self.monitor.is_online() {
+            self.cancel();
+            return vec![Item::message(&network::offline_description(
+                "Can't fetch the weather"))];
+        }
+
+        if *self.requested.borrow() != city {
+            *self.requested.borrow_mut() = city.to_string();
+            self.forecaster.request(city);
+        }
+
+        vec![Item::loading("Fetching the weather..")]
+    }
+
+    fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.config.cache_minutes * 60)
+    }
+
+    fn poll(&self) -> bool {
+        let fetched = self.forecaster.poll();
+
+        if fetched.is_empty() {
+            return false;
+        }
+
+        // So it's fetched again once the cached report expires
+        if fetched.iter().any(|f| *self.requested.borrow() == f.0) {
+            self.requested.borrow_mut().clear();
+        }
+
+        *self.arrived.borrow_mut() = fetched;
+        true
+    }
+}
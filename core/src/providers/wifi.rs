@@ -0,0 +1,88 @@
+use std::cell::RefCell;
+
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+use wifi::{self, Config, Scanner};
+
+const WIRELESS: &str = "802-11-wireless";
+
+// Wi-Fi networks and other saved connections after the prefix, e.g.
+// "wifi home". Enter joins a network or brings a connection up or down,
+// as a job so nmcli's progress shows.
+pub struct Wifi {
+    config: Config,
+    scanner: RefCell<Scanner>,
+}
+
+impl Wifi {
+    pub fn new(config: Config) -> Wifi {
+        Wifi {
+            config,
+            scanner: RefCell::new(Scanner::new()),
+        }
+    }
+}
+
+fn job(title: &str, description: &str, icon: &str, command: String)
+    -> Item {
+    Item {
+        icon: Some(icon.to_string()),
+        wait: true,
+        ..Item::new(title, description, Action::Spawn(command))
+    }
+}
+
+impl Provider for Wifi {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let mut scanner = self.scanner.borrow_mut();
+
+        let scan = match scanner.scan() {
+            Some(Ok (s)) => s.clone(),
+            Some(Err(e)) => return vec![Item::message(e)],
+            None         => return vec![
+                Item::loading("Scanning for networks..")],
+        };
+
+        let matches = |name: &str| fuzzy::score(filter, name).is_some();
+        let mut items = vec![];
+
+        for n in scan.networks.iter().filter(|n| matches(&n.ssid)) {
+            let lock = if n.secured { "secured, " } else { "" };
+            let description = format!("{}{}%", lock, n.signal);
+            let saved = scan.connections.iter().any(|c| c.name == n.ssid);
+
+            items.push(if n.active {
+                job(&format!("Disconnect from {}", n.ssid), &description,
+                    "network-wireless-connected",
+                    wifi::disconnect_command(&n.ssid))
+            } else {
+                job(&n.ssid, &description, "network-wireless",
+                    wifi::connect_command(&n.ssid, saved))
+            });
+        }
+
+        // Wired, VPN and so on; Wi-Fi connections are listed above
+        for c in scan.connections.iter().filter(|c| {
+            c.kind != WIRELESS && matches(&c.name)
+        }) {
+            let verb = if c.active { "Disconnect" } else { "Connect" };
+            items.push(job(&format!("{} {}", verb, c.name), &c.kind,
+                           "network-workgroup", wifi::toggle_command(c)));
+        }
+
+        if scanner.scanning() {
+            items.push(Item::loading("Scanning.."));
+        }
+
+        items
+    }
+
+    fn poll(&self) -> bool {
+        self.scanner.borrow_mut().poll()
+    }
+}
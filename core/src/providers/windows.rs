@@ -0,0 +1,53 @@
+use fuzzy;
+use provider::{self, Action, Item, Provider};
+use windows::{self, Config, Window};
+
+// Open windows after the prefix, best match on title or class first.
+// Enter brings the window to the front.
+pub struct Windows {
+    config: Config,
+}
+
+impl Windows {
+    pub fn new(config: Config) -> Windows {
+        Windows { config }
+    }
+}
+
+fn item(window: &Window) -> Item {
+    Item {
+        // Class names mostly double as the application's icon name
+        icon: Some(window.class.to_lowercase()),
+        ..Item::new(&window.title, &window.class,
+                    Action::Activate(window.id.clone()))
+    }
+}
+
+impl Provider for Windows {
+    fn query(&self, input: &str) -> Vec<Item> {
+        let filter = match provider::prefix_filter(input, &self.config.prefix) {
+            Some(f) => f,
+            None    => return vec![],
+        };
+
+        let open = match windows::list() {
+            Ok (w) => w,
+            Err(e) => return vec![Item::message(&e)],
+        };
+
+        let mut found: Vec<(usize, &Window)> = open.iter().
+            filter(|w| w.class != "blaunch").
+            filter_map(|w| {
+                let score = match (fuzzy::score(filter, &w.title),
+                                   fuzzy::score(filter, &w.class)) {
+                    (Some(t), Some(c)) => t.min(c),
+                    (t, c)             => t.or(c)?,
+                };
+
+                Some((score, w))
+            }).collect();
+
+        found.sort_by_key(|&(score, _)| score);
+        found.into_iter().map(|(_, w)| item(w)).collect()
+    }
+}
@@ -0,0 +1,88 @@
+// QR codes for text through qrencode, as a grid of dark and light modules
+// for the frontend to draw.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use config::Tools;
+
+// Light modules around the code; scanners need a quiet zone to find it
+const MARGIN: &str = "2";
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["qrencode"]
+    }
+}
+
+fn default_prefix() -> String {
+    "qr".to_string()
+}
+
+// `qrencode -t ASCII` writes each module as two characters, "##" for dark
+// and spaces for light. Lines are padded out to the widest one, since
+// trailing spaces may be missing.
+pub fn parse_ascii(output: &str) -> Vec<Vec<bool>> {
+    let mut rows: Vec<Vec<bool>> = output.lines().map(|l| {
+        l.chars().step_by(2).map(|c| c == '#').collect()
+    }).collect();
+
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+
+    for r in rows.iter_mut() {
+        r.resize(width, false);
+    }
+
+    rows
+}
+
+// The text goes in on stdin so it can't be taken for an option.
+pub fn encode(text: &str) -> Result<Vec<Vec<bool>>, String> {
+    let mut child = Command::new("qrencode").
+        args(["-t", "ASCII", "-m", MARGIN, "-o", "-"]).
+        stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).
+        spawn().map_err(|e| format!("Can't run qrencode: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).
+            map_err(|e| format!("Can't run qrencode: {}", e))?;
+    }
+
+    let output = child.wait_with_output().
+        map_err(|e| format!("Can't run qrencode: {}", e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().
+            to_string());
+    }
+
+    let cells = parse_ascii(&String::from_utf8_lossy(&output.stdout));
+
+    if cells.is_empty() {
+        return Err("qrencode didn't draw anything".to_string());
+    }
+
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_ascii;
+
+    #[test]
+    fn it_parses_qrencode_ascii() {
+        let output = "      \n  ##  ##\n  ####\n";
+
+        assert_eq!(parse_ascii(output), vec![
+            vec![false, false, false, false],
+            vec![false, true, false, true],
+            vec![false, true, true, false],
+        ]);
+    }
+}
@@ -0,0 +1,312 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+use provider;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_step")]
+    pub step: u32,
+    // Shortcuts straight to one setting, like "bright 50" or "vol +5". An
+    // empty prefix turns the shortcut off.
+    #[serde(default = "default_brightness_prefix")]
+    pub brightness_prefix: String,
+    #[serde(default = "default_volume_prefix")]
+    pub volume_prefix: String,
+}
+
+fn default_prefix() -> String {
+    "qs".to_string()
+}
+
+fn default_step() -> u32 {
+    5
+}
+
+fn default_brightness_prefix() -> String {
+    "bright".to_string()
+}
+
+fn default_volume_prefix() -> String {
+    "vol".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Setting {
+    Brightness,
+    Volume,
+}
+
+const SETTINGS: [Setting; 2] = [Setting::Brightness, Setting::Volume];
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Change {
+    Set(u32),
+    Adjust(i32),
+}
+
+impl Change {
+    pub fn describe(&self) -> String {
+        match *self {
+            Change::Set(p)             => format!("set it to {}%", p),
+            Change::Adjust(d) if d < 0 => format!("lower it by {}%", -d),
+            Change::Adjust(d)          => format!("raise it by {}%", d),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct Query {
+    pub settings: Vec<Setting>,
+    // Applied to the first setting on Enter
+    pub change: Option<Change>,
+    // Why the typed amount can't be applied
+    pub invalid: Option<String>,
+}
+
+fn stdout_of(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn run(program: &str, args: &[&str]) -> io::Result<bool> {
+    Command::new(program).args(args).stdout(Stdio::null()).status().
+        map(|s| s.success())
+}
+
+// brightnessctl -m prints "device,class,current,percent%,max".
+fn parse_brightnessctl(output: &str) -> Option<u32> {
+    let field = output.trim().split(',').nth(3)?;
+    field.trim_end_matches('%').parse().ok()
+}
+
+// pactl prints "Volume: front-left: 32768 /  50% / -18.06 dB, ..." and we
+// take the first channel's percentage.
+fn parse_pactl_volume(output: &str) -> Option<u32> {
+    let field = output.split('/').nth(1)?;
+    field.trim().trim_end_matches('%').parse().ok()
+}
+
+// wpctl prints "Volume: 0.50" with an optional "[MUTED]" suffix.
+fn parse_wpctl_volume(output: &str) -> Option<u32> {
+    let field = output.split_whitespace().nth(1)?;
+    let volume: f64 = field.parse().ok()?;
+
+    Some((volume * 100.0).round() as u32)
+}
+
+impl Setting {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Setting::Brightness => "brightness",
+            Setting::Volume     => "volume",
+        }
+    }
+
+    pub fn level(&self) -> Option<u32> {
+        match *self {
+            Setting::Brightness =>
+                stdout_of("brightnessctl", &["-m"]).
+                    and_then(|o| parse_brightnessctl(&o)),
+            Setting::Volume =>
+                stdout_of("pactl",
+                          &["get-sink-volume", "@DEFAULT_SINK@"]).
+                    and_then(|o| parse_pactl_volume(&o)).
+                    or_else(|| stdout_of("wpctl",
+                        &["get-volume", "@DEFAULT_AUDIO_SINK@"]).
+                        and_then(|o| parse_wpctl_volume(&o))),
+        }
+    }
+
+    // Each tool spells relative amounts differently ("5%+" vs "+5%") so the
+    // caller passes the amount in brightnessctl, pactl and wpctl form.
+    fn apply(&self, brightness: &str, pactl: &str, wpctl: &str)
+        -> io::Result<()> {
+        let ok = match *self {
+            Setting::Brightness =>
+                run("brightnessctl", &["set", brightness])?,
+            Setting::Volume =>
+                run("pactl", &["set-sink-volume", "@DEFAULT_SINK@", pactl]).
+                    unwrap_or(false) ||
+                run("wpctl", &["set-volume", "@DEFAULT_AUDIO_SINK@", wpctl])?,
+        };
+
+        if ok {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("Can't change {}", self.name())))
+        }
+    }
+
+    // Brightness stops above zero, where some backlights switch off
+    fn minimum(&self) -> u32 {
+        match *self {
+            Setting::Brightness => 1,
+            Setting::Volume     => 0,
+        }
+    }
+
+    pub fn set(&self, percent: u32) -> io::Result<()> {
+        let value = format!("{}%", percent.min(100));
+        self.apply(&value, &value, &value)
+    }
+
+    pub fn adjust(&self, delta: i32) -> io::Result<()> {
+        let amount = delta.abs();
+        let sign = if delta < 0 { "-" } else { "+" };
+
+        self.apply(&format!("{}%{}", amount, sign),
+                   &format!("{}{}%", sign, amount),
+                   &format!("{}%{}", amount, sign))
+    }
+
+    pub fn change(&self, change: Change) -> io::Result<()> {
+        match change {
+            Change::Set(p)    => self.set(p),
+            Change::Adjust(d) => self.adjust(d),
+        }
+    }
+}
+
+// "30", "30%" or a relative "+5" / "-5".
+fn parse_change(setting: Setting, text: &str) -> Result<Change, String> {
+    let text = text.trim_end_matches('%');
+
+    let (sign, digits) = match text.chars().next() {
+        Some('+') => (1, &text[1..]),
+        Some('-') => (-1, &text[1..]),
+        _         => (0, text),
+    };
+
+    let amount: u32 = digits.parse().map_err(|_| format!(
+        "Type a {} level like 30, +5 or -5", setting.name()))?;
+
+    if amount > 100 {
+        return Err(format!("The {} goes up to 100%", setting.name()));
+    }
+
+    match sign {
+        0 if amount < setting.minimum() =>
+            Err(format!("The {} goes down to {}%", setting.name(),
+                        setting.minimum())),
+        0 => Ok(Change::Set(amount)),
+        s => Ok(Change::Adjust(s * amount as i32)),
+    }
+}
+
+fn query(settings: Vec<Setting>, amount: Option<&str>) -> Query {
+    let change = match (settings.first(), amount) {
+        (Some(s), Some(a)) => Some(parse_change(*s, a)),
+        _                  => None,
+    };
+
+    Query {
+        settings,
+        invalid: change.as_ref().and_then(|c| c.as_ref().err()).cloned(),
+        change: change.and_then(|c| c.ok()),
+    }
+}
+
+// "<prefix>" lists every setting, "<prefix> vol" narrows the list by name
+// and a trailing amount ("<prefix> vol 30") changes the first one. The
+// shortcut prefixes go straight to their setting: "vol 30", "bright -10".
+pub fn parse_input(config: &Config, value: &str) -> Option<Query> {
+    let shortcuts = [(&config.brightness_prefix, Setting::Brightness),
+                     (&config.volume_prefix, Setting::Volume)];
+
+    // An empty prefix turns a shortcut off
+    for &(prefix, setting) in shortcuts.iter().filter(|s| !s.0.is_empty()) {
+        if let Some(rest) = provider::prefix_filter(value, prefix) {
+            let amount = if rest.is_empty() { None } else { Some(rest) };
+            return Some(query(vec![setting], amount));
+        }
+    }
+
+    if config.prefix.is_empty() {
+        return None;
+    }
+
+    let rest = provider::prefix_filter(value, &config.prefix)?;
+
+    let mut filter = "";
+    let mut amount = None;
+
+    for word in rest.split_whitespace() {
+        if word.starts_with(|c: char| c.is_ascii_digit() || c == '+' ||
+                                      c == '-') {
+            amount = Some(word);
+        } else {
+            filter = word;
+        }
+    }
+
+    Some(query(SETTINGS.iter().cloned().
+        filter(|s| s.name().starts_with(filter)).collect(), amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Change, Config, Query, Setting, parse_input,
+                parse_brightnessctl, parse_pactl_volume, parse_wpctl_volume};
+
+    fn config() -> Config {
+        Config {
+            prefix: "qs".to_string(),
+            step: 5,
+            brightness_prefix: "bright".to_string(),
+            volume_prefix: "vol".to_string(),
+        }
+    }
+
+    #[test]
+    fn it_parses_quick_settings_input() {
+        assert_eq!(parse_input(&config(), "qs"), Some(Query {
+            settings: vec![Setting::Brightness, Setting::Volume],
+            change: None,
+            invalid: None,
+        }));
+        assert_eq!(parse_input(&config(), "qs vol 30%"), Some(Query {
+            settings: vec![Setting::Volume],
+            change: Some(Change::Set(30)),
+            invalid: None,
+        }));
+        assert_eq!(parse_input(&config(), "qsomething"), None);
+    }
+
+    #[test]
+    fn it_parses_setting_shortcuts() {
+        assert_eq!(parse_input(&config(), "vol 30"), Some(Query {
+            settings: vec![Setting::Volume],
+            change: Some(Change::Set(30)),
+            invalid: None,
+        }));
+        assert_eq!(parse_input(&config(), "bright -10%").unwrap().change,
+                   Some(Change::Adjust(-10)));
+        assert_eq!(parse_input(&config(), "bright").unwrap().change, None);
+        assert_eq!(parse_input(&config(), "volcano"), None);
+
+        for input in ["vol 150", "bright 0", "vol loud"].iter() {
+            let query = parse_input(&config(), input).unwrap();
+
+            assert_eq!(query.change, None);
+            assert!(query.invalid.is_some());
+        }
+    }
+
+    #[test]
+    fn it_parses_levels() {
+        assert_eq!(parse_brightnessctl("intel_backlight,backlight,60,25%,240"),
+                   Some(25));
+        assert_eq!(parse_pactl_volume(
+            "Volume: front-left: 32768 /  50% / -18.06 dB,   \
+             front-right: 32768 /  50% / -18.06 dB"), Some(50));
+        assert_eq!(parse_wpctl_volume("Volume: 0.45 [MUTED]"), Some(45));
+    }
+}
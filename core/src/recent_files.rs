@@ -0,0 +1,159 @@
+// Recently used files as GTK records them in recently-used.xbel, with the
+// application that last opened each one.
+
+use std::fs;
+use std::path::Path;
+
+use paths::expand_home;
+
+const XBEL: &str = "~/.local/share/recently-used.xbel";
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+fn default_prefix() -> String {
+    "recent".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RecentFile {
+    pub path: String,
+    pub application: Option<String>,
+    // ISO 8601, so it sorts as text
+    pub visited: String,
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").
+        replace("&apos;", "'").replace("&amp;", "&")
+}
+
+// Paths in file URIs are percent-encoded bytes.
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut ret = vec![];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = text.get(i + 1..i + 3).
+            and_then(|h| u8::from_str_radix(h, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                ret.push(b);
+                i += 3;
+            },
+            (b, _) => {
+                ret.push(b);
+                i += 1;
+            },
+        }
+    }
+
+    String::from_utf8_lossy(&ret).into_owned()
+}
+
+// The value of name="..." in a tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let needle = format!("{}=\"", name);
+
+    // Not the end of a longer name, like "modified" in "bookmark:modified"
+    let at = tag.match_indices(&needle).map(|(i, _)| i).find(|&i| {
+        tag[..i].chars().next_back().is_none_or(char::is_whitespace)
+    })?;
+
+    let start = at + needle.len();
+    let end = tag[start..].find('"')?;
+
+    Some(unescape(&tag[start..start + end]))
+}
+
+// The bookmarks for local files, most recently visited first. Each one's
+// application is the one that used it last.
+pub fn parse_xbel(xbel: &str) -> Vec<RecentFile> {
+    let mut files = vec![];
+
+    for bookmark in xbel.split("<bookmark ").skip(1) {
+        let tag = bookmark.split('>').next().unwrap_or("");
+
+        let path = match attribute(tag, "href") {
+            Some(ref h) if h.starts_with("file://") =>
+                percent_decode(&h["file://".len()..]),
+            _ => continue,
+        };
+
+        let body = bookmark.split("</bookmark>").next().unwrap_or("");
+
+        let application = body.split("<bookmark:application ").skip(1).
+            filter_map(|a| {
+                let tag = a.split('>').next().unwrap_or("");
+                Some((attribute(tag, "modified").unwrap_or_default(),
+                      attribute(tag, "name")?))
+            }).
+            max().map(|(_, name)| name);
+
+        let visited = attribute(tag, "visited").
+            or_else(|| attribute(tag, "modified")).unwrap_or_default();
+
+        files.push(RecentFile {
+            path,
+            application,
+            visited,
+        });
+    }
+
+    files.sort_by(|a, b| b.visited.cmp(&a.visited));
+    files
+}
+
+// Recent files that still exist.
+pub fn files() -> Vec<RecentFile> {
+    let xbel = fs::read_to_string(expand_home(XBEL)).unwrap_or_default();
+
+    parse_xbel(&xbel).into_iter().
+        filter(|f| Path::new(&f.path).exists()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecentFile, parse_xbel};
+
+    #[test]
+    fn it_parses_recently_used_xbel() {
+        let xbel = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks">
+  <bookmark href="file:///home/ada/Tax%20Return.pdf"
+            added="2017-01-01T10:00:00Z" modified="2017-01-02T10:00:00Z"
+            visited="2017-01-02T10:00:00Z">
+    <info><metadata owner="http://freedesktop.org"><bookmark:applications>
+      <bookmark:application name="Evince" exec="&apos;evince %u&apos;"
+                            modified="2017-01-01T10:00:00Z" count="1"/>
+      <bookmark:application name="Firefox" exec="&apos;firefox %u&apos;"
+                            modified="2017-01-02T10:00:00Z" count="1"/>
+    </bookmark:applications></metadata></info>
+  </bookmark>
+  <bookmark href="https://example.com/" visited="2017-01-03T10:00:00Z">
+  </bookmark>
+  <bookmark href="file:///home/ada/notes%26todo.txt"
+            visited="2017-01-03T10:00:00Z">
+  </bookmark>
+</xbel>"#;
+
+        assert_eq!(parse_xbel(xbel), vec![
+            RecentFile {
+                path: "/home/ada/notes&todo.txt".to_string(),
+                application: None,
+                visited: "2017-01-03T10:00:00Z".to_string(),
+            },
+            RecentFile {
+                path: "/home/ada/Tax Return.pdf".to_string(),
+                application: Some("Firefox".to_string()),
+                visited: "2017-01-02T10:00:00Z".to_string(),
+            },
+        ]);
+    }
+}
@@ -0,0 +1,129 @@
+// Git repositories and VS Code workspace files found under a few root
+// directories, for opening in an editor. Unlike the configured projects,
+// nothing has to be listed by hand.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use config::{Tools, program};
+use desktop::shell_quote;
+use paths::expand_home;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_roots")]
+    pub roots: Vec<String>,
+    // How many directories down from a root to look
+    #[serde(default = "default_depth")]
+    pub depth: usize,
+    // Command to open a project with, where {path} is its directory or
+    // workspace file
+    #[serde(default = "default_editor")]
+    pub editor: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec![program(&self.editor)]
+    }
+}
+
+fn default_prefix() -> String {
+    "proj".to_string()
+}
+
+fn default_roots() -> Vec<String> {
+    vec!["~/src".to_string(), "~/projects".to_string()]
+}
+
+fn default_depth() -> usize {
+    3
+}
+
+fn default_editor() -> String {
+    "code {path}".to_string()
+}
+
+// Repositories aren't searched further, so submodules and vendored
+// checkouts don't show up on their own.
+pub fn scan(root: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    if root.join(".git").exists() {
+        found.push(root.to_path_buf());
+        return;
+    }
+
+    let entries = match fs::read_dir(root) {
+        Ok (e) => e,
+        Err(_) => return,
+    };
+
+    let mut dirs = vec![];
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = entry.path();
+
+        if name.starts_with('.') {
+            continue;
+        }
+
+        if name.ends_with(".code-workspace") {
+            found.push(path);
+        } else if depth > 0 && entry.file_type().is_ok_and(|t| t.is_dir()) {
+            dirs.push(path);
+        }
+    }
+
+    for dir in dirs {
+        scan(&dir, depth - 1, found);
+    }
+}
+
+pub fn projects(config: &Config) -> Vec<PathBuf> {
+    let mut found = vec![];
+
+    for root in config.roots.iter() {
+        scan(Path::new(&expand_home(root)), config.depth, &mut found);
+    }
+
+    found.sort();
+    found.dedup();
+    found
+}
+
+pub fn open_command(editor: &str, path: &Path) -> String {
+    editor.replace("{path}", &shell_quote(&path.to_string_lossy()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use super::{open_command, scan};
+
+    #[test]
+    fn it_finds_repositories_and_workspaces() {
+        let root = std::env::temp_dir().join("blaunch-repos-test");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("blog/.git")).unwrap();
+        fs::create_dir_all(root.join("blog/vendor/lib/.git")).unwrap();
+        fs::create_dir_all(root.join("work/api/.git")).unwrap();
+        fs::create_dir_all(root.join("deep/a/b/c/.git")).unwrap();
+        fs::write(root.join("work/all.code-workspace"), "{}").unwrap();
+
+        let mut found = vec![];
+        scan(&root, 2, &mut found);
+        found.sort();
+
+        assert_eq!(found, vec![
+            root.join("blog"),
+            root.join("work/all.code-workspace"),
+            root.join("work/api"),
+        ]);
+        assert_eq!(open_command("code {path}", Path::new("/src/my blog")),
+                   "code '/src/my blog'");
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,161 @@
+// Flatpak and Snap applications. Both export .desktop files, but the export
+// directories aren't always in XDG_DATA_DIRS when blaunch is started from a
+// hotkey daemon or a systemd unit, so they're added here, and anything
+// without an exported entry comes from the package tools' own listings.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use paths::expand_home;
+use providers::apps::App;
+
+pub const FLATPAK: &str = "flatpak";
+pub const SNAP: &str = "snap";
+
+pub fn export_dirs() -> Vec<PathBuf> {
+    vec![
+        PathBuf::from(expand_home("~/.local/share/flatpak/exports/share")),
+        PathBuf::from("/var/lib/flatpak/exports/share"),
+        PathBuf::from("/var/lib/snapd/desktop"),
+    ]
+}
+
+// Which of the two an application comes from, going by how it's started.
+pub fn source(exec: &str) -> Option<&'static str> {
+    let mut words = exec.split_whitespace();
+
+    if words.clone().any(|w| w == "flatpak" || w.ends_with("/flatpak")) {
+        Some(FLATPAK)
+    } else if words.any(|w| w.starts_with("/snap/bin/")) {
+        Some(SNAP)
+    } else {
+        None
+    }
+}
+
+// `flatpak list --app --columns=application,name`, tab separated.
+pub fn parse_flatpaks(output: &str) -> Vec<App> {
+    output.lines().filter_map(|line| {
+        let mut fields = line.split('\t');
+        let id = fields.next()?.trim();
+        let name = fields.next().map(|n| n.trim()).unwrap_or(id);
+
+        if id.is_empty() {
+            return None;
+        }
+
+        Some(App {
+            name: name.to_string(),
+            exec: format!("flatpak run {}", id),
+            // Flatpak exports icons under the application id
+            icon: Some(id.to_string()),
+            terminal: false,
+        })
+    }).collect()
+}
+
+// `snap list`, leaving out the header and the bases and snapd itself,
+// which have nothing to run.
+pub fn parse_snaps(output: &str) -> Vec<App> {
+    output.lines().skip(1).filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let notes = fields.get(5).cloned().unwrap_or("");
+
+        if fields.is_empty() ||
+           notes.split(',').any(|n| ["base", "core", "snapd"].contains(&n)) {
+            return None;
+        }
+
+        Some(App {
+            name: fields[0].to_string(),
+            exec: format!("/snap/bin/{}", fields[0]),
+            icon: None,
+            terminal: false,
+        })
+    }).collect()
+}
+
+fn output(program: &str, args: &[&str]) -> String {
+    Command::new(program).args(args).output().
+        map(|o| String::from_utf8_lossy(&o.stdout).into_owned()).
+        unwrap_or_default()
+}
+
+// Whether an application from the package listings is already among the
+// ones read from .desktop files. Flatpak entries end with the application
+// id; snap entries start /snap/bin/<snap>, or /snap/bin/<snap>.<app> for
+// snaps with several applications.
+pub fn exported(app: &App, desktop: &[App]) -> bool {
+    let target = match app.exec.split_whitespace().last() {
+        Some(t) => t,
+        None    => return false,
+    };
+
+    let dotted = format!("{}.", target);
+
+    desktop.iter().any(|d| d.exec.split_whitespace().any(|w| {
+        w == target || w.starts_with(&dotted)
+    }))
+}
+
+// Installed applications that have no exported .desktop file. Either tool
+// missing just means nothing from it.
+pub fn unexported(desktop: &[App]) -> Vec<App> {
+    let mut apps = parse_flatpaks(&output(FLATPAK, &[
+        "list", "--app", "--columns=application,name"]));
+    apps.extend(parse_snaps(&output(SNAP, &["list"])));
+
+    apps.into_iter().filter(|a| !exported(a, desktop)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use providers::apps::App;
+    use super::{FLATPAK, SNAP, exported, parse_flatpaks, parse_snaps, source};
+
+    #[test]
+    fn it_parses_package_listings() {
+        let flatpaks = parse_flatpaks("org.gimp.GIMP\tGNU Image Manipulation \
+                                       Program\n");
+        assert_eq!(flatpaks[0].exec, "flatpak run org.gimp.GIMP");
+        assert_eq!(flatpaks[0].icon, Some("org.gimp.GIMP".to_string()));
+
+        let snaps = parse_snaps(
+            "Name    Version  Rev  Tracking  Publisher  Notes\n\
+             core22  2024     1    stable    canonical  base\n\
+             snapd   2.61     2    stable    canonical  snapd\n\
+             spotify 1.2      80   stable    spotify    -\n");
+        assert_eq!(snaps.len(), 1);
+        assert_eq!(snaps[0].exec, "/snap/bin/spotify");
+    }
+
+    #[test]
+    fn it_matches_exported_entries() {
+        let desktop = vec![
+            App {
+                name: "GIMP".to_string(),
+                exec: "/usr/bin/flatpak run --branch=stable \
+                       --command=gimp org.gimp.GIMP".to_string(),
+                ..App::default()
+            },
+            App {
+                name: "Firefox".to_string(),
+                exec: "env BAMF_DESKTOP_FILE_HINT=firefox.desktop \
+                       /snap/bin/firefox".to_string(),
+                ..App::default()
+            },
+        ];
+
+        assert_eq!(source(&desktop[0].exec), Some(FLATPAK));
+        assert_eq!(source(&desktop[1].exec), Some(SNAP));
+        assert_eq!(source("firefox"), None);
+
+        let gimp = &parse_flatpaks("org.gimp.GIMP\tGIMP")[0];
+        let spotify = &parse_snaps("Name\nspotify 1.2 80 stable x -")[0];
+        let firefox = &parse_snaps("Name\nfirefox 1 2 stable x -")[0];
+
+        assert!(exported(gimp, &desktop));
+        assert!(exported(firefox, &desktop));
+        assert!(!exported(spotify, &desktop));
+    }
+}
@@ -0,0 +1,171 @@
+// The screenshot builtin: a submenu taking a region, the focused window or
+// the whole screen, either copied or saved to a file. The capture waits a
+// moment before starting so the launcher is gone from the screen; a daemon
+// is only hidden, a one-off launcher has quit by then.
+
+use std::env;
+
+use desktop::shell_quote;
+use node::{on_path, Node};
+use paths::expand_home;
+
+pub const SCREENSHOT: &str = "screenshot";
+
+// Long enough for the launcher window to unmap
+const DELAY: &str = "sleep 0.3";
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    // "grim", "maim" or "scrot"; the first one installed when not set
+    pub backend: Option<String>,
+    #[serde(default = "default_directory")]
+    pub directory: String,
+}
+
+fn default_directory() -> String {
+    "~/Pictures/Screenshots".to_string()
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            backend: None,
+            directory: default_directory(),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Backend {
+    Grim,
+    Maim,
+    Scrot,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Area {
+    Region,
+    Window,
+    Screen,
+}
+
+// grim on Wayland; maim, then scrot on X11.
+pub fn backend(config: &Config) -> Backend {
+    match config.backend.as_deref() {
+        Some("grim")  => return Backend::Grim,
+        Some("maim")  => return Backend::Maim,
+        Some("scrot") => return Backend::Scrot,
+        _             => {},
+    };
+
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        Backend::Grim
+    } else if on_path("maim") {
+        Backend::Maim
+    } else {
+        Backend::Scrot
+    }
+}
+
+pub fn tool(config: &Config) -> &'static str {
+    match backend(config) {
+        Backend::Grim  => "grim",
+        Backend::Maim  => "maim",
+        Backend::Scrot => "scrot",
+    }
+}
+
+// Sway's focused window as a grim geometry
+const SWAY_FOCUSED: &str = "swaymsg -t get_tree | jq -r '.. | \
+    select(.focused?) | .rect | \"\\(.x),\\(.y) \\(.width)x\\(.height)\"'";
+
+// The capture, writing a PNG to the file, or to stdout for "-".
+fn capture(backend: Backend, area: Area, file: &str) -> String {
+    match (backend, area) {
+        (Backend::Grim, Area::Region)  =>
+            format!("grim -g \"$(slurp)\" {}", file),
+        (Backend::Grim, Area::Window)  =>
+            format!("grim -g \"$({})\" {}", SWAY_FOCUSED, file),
+        (Backend::Grim, Area::Screen)  => format!("grim {}", file),
+        (Backend::Maim, Area::Region)  => format!("maim -s {}", file),
+        (Backend::Maim, Area::Window)  =>
+            format!("maim -i \"$(xdotool getactivewindow)\" {}", file),
+        (Backend::Maim, Area::Screen)  => format!("maim {}", file),
+        (Backend::Scrot, Area::Region) => format!("scrot -o -s {}", file),
+        (Backend::Scrot, Area::Window) => format!("scrot -o -u {}", file),
+        (Backend::Scrot, Area::Screen) => format!("scrot -o {}", file),
+    }
+}
+
+// Copies a screenshot as an image. scrot can't write to stdout, so it goes
+// through a temporary file.
+pub fn copy_command(backend: Backend, area: Area) -> String {
+    let shot = match backend {
+        Backend::Grim  => format!("{} | wl-copy -t image/png",
+                                  capture(backend, area, "-")),
+        Backend::Maim  => format!("{} | xclip -selection clipboard \
+                                   -t image/png", capture(backend, area, "-")),
+        Backend::Scrot => format!("f=$(mktemp --suffix=.png) && {} && \
+                                   xclip -selection clipboard -t image/png \
+                                   \"$f\"; rm -f \"$f\"",
+                                  capture(backend, area, "\"$f\"")),
+    };
+
+    format!("{}; {}", DELAY, shot)
+}
+
+// Saves a timestamped PNG in the directory.
+pub fn save_command(backend: Backend, area: Area, directory: &str)
+    -> String {
+    let directory = shell_quote(directory);
+    let file = format!("{}/\"$(date +%Y-%m-%d-%H%M%S)\".png", directory);
+
+    format!("{}; mkdir -p {} && {}", DELAY, directory,
+            capture(backend, area, &file))
+}
+
+// The submenu under the builtin.
+pub fn nodes(config: &Config) -> Vec<Node> {
+    let backend = backend(config);
+    let directory = expand_home(&config.directory);
+    let areas = [
+        ("r", "region", Area::Region),
+        ("w", "window", Area::Window),
+        ("f", "full screen", Area::Screen),
+    ];
+
+    let mut nodes = vec![];
+
+    for &(key, name, area) in areas.iter() {
+        nodes.push(Node {
+            shortcut: key.to_string(),
+            description: format!("save {}", name),
+            command: Some(save_command(backend, area, &directory)),
+            shell: true,
+            ..Default::default()
+        });
+        nodes.push(Node {
+            shortcut: format!("c{}", key),
+            description: format!("copy {}", name),
+            command: Some(copy_command(backend, area)),
+            shell: true,
+            ..Default::default()
+        });
+    }
+
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Area, Backend, copy_command, save_command};
+
+    #[test]
+    fn it_builds_screenshot_commands() {
+        assert_eq!(copy_command(Backend::Grim, Area::Region),
+                   "sleep 0.3; grim -g \"$(slurp)\" - | wl-copy -t image/png");
+        assert_eq!(save_command(Backend::Maim, Area::Screen, "/tmp/shots"),
+                   "sleep 0.3; mkdir -p '/tmp/shots' && \
+                    maim '/tmp/shots'/\"$(date +%Y-%m-%d-%H%M%S)\".png");
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use config::Tools;
+use desktop::shell_quote;
+use suggest::fill_query;
+
+// Search engines by keyword, each a URL with a {query} placeholder:
+// {"engines": {"ddg": "https://duckduckgo.com/?q={query}"}} makes
+// "ddg rust gtk" open that search in the browser.
+#[derive(Deserialize, Clone, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub engines: HashMap<String, String>,
+    // OpenSearch suggest endpoint, also with a {query} placeholder, whose
+    // suggestions are listed under the search
+    pub suggest: Option<String>,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["xdg-open"]
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Search<'a> {
+    pub engine: &'a str,
+    pub url: &'a str,
+    pub query: &'a str,
+}
+
+// The engine a query starts with, and what's being searched for. The
+// keyword has to be followed by a space so "ddg" doesn't catch "ddgr".
+pub fn parse<'a>(config: &'a Config, input: &'a str) -> Option<Search<'a>> {
+    let (keyword, query) = match input.find(' ') {
+        Some(i) => (&input[..i], input[i + 1..].trim()),
+        None    => return None,
+    };
+
+    config.engines.get_key_value(keyword).map(|(engine, url)| Search {
+        engine,
+        url,
+        query,
+    })
+}
+
+pub fn command(url: &str, query: &str) -> String {
+    format!("xdg-open {}", shell_quote(&fill_query(url, query)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, Search, command, parse};
+
+    #[test]
+    fn it_parses_engine_keywords() {
+        let mut config = Config::default();
+        config.engines.insert("ddg".to_string(),
+                              "https://duckduckgo.com/?q={query}".to_string());
+
+        assert_eq!(parse(&config, "ddg rust gtk"), Some(Search {
+            engine: "ddg",
+            url: "https://duckduckgo.com/?q={query}",
+            query: "rust gtk",
+        }));
+        assert_eq!(parse(&config, "ddg ").map(|s| s.query), Some(""));
+        assert_eq!(parse(&config, "ddg"), None);
+        assert_eq!(parse(&config, "ddgr x"), None);
+    }
+
+    #[test]
+    fn it_opens_the_filled_in_url() {
+        assert_eq!(command("https://example.com/?q={query}", "it's"),
+                   "xdg-open 'https://example.com/?q=it%27s'");
+    }
+}
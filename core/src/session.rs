@@ -0,0 +1,187 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use config::Tools;
+use processes;
+
+pub const LOCK: &str = "lock";
+pub const CAFFEINE: &str = "caffeine";
+pub const LOGOUT: &str = "logout";
+pub const SUSPEND: &str = "suspend";
+pub const HIBERNATE: &str = "hibernate";
+pub const REBOOT: &str = "reboot";
+pub const POWEROFF: &str = "poweroff";
+
+#[derive(Deserialize, Clone)]
+pub struct PowerConfig {
+    #[serde(default = "default_power_prefix")]
+    pub prefix: String,
+    // Ask before anything but locking
+    #[serde(default = "default_true")]
+    pub confirm: bool,
+}
+
+impl Tools for PowerConfig {
+    fn tools(&self) -> Vec<&str> {
+        vec!["gdbus", "systemctl"]
+    }
+}
+
+fn default_power_prefix() -> String {
+    "power".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// Calls a logind method on the system bus, returning whether it worked.
+fn logind(object: &str, method: &str, args: &[&str]) -> bool {
+    let status = Command::new("gdbus").args([
+        "call", "--system",
+        "--dest", "org.freedesktop.login1",
+        "--object-path", object,
+        "--method", method,
+    ]).args(args).stdout(Stdio::null()).status();
+
+    matches!(status, Ok (ref s) if s.success())
+}
+
+// Runs a fallback tool, which fails if the tool does.
+fn run(program: &str, args: &[&str]) -> io::Result<()> {
+    let status = Command::new(program).args(args).status()?;
+
+    if !status.success() {
+        return Err(io::Error::other(format!("{} {} failed ({})", program,
+                                            args.join(" "), status)));
+    }
+
+    Ok(())
+}
+
+// Locks the current session through logind, falling back to loginctl which
+// talks to the same interface.
+pub fn lock() -> io::Result<()> {
+    if logind("/org/freedesktop/login1/session/auto",
+              "org.freedesktop.login1.Session.Lock", &[]) {
+        return Ok(());
+    }
+
+    run("loginctl", &["lock-session"])
+}
+
+pub fn logout() -> io::Result<()> {
+    if logind("/org/freedesktop/login1/session/auto",
+              "org.freedesktop.login1.Session.Terminate", &[]) {
+        return Ok(());
+    }
+
+    let session = env::var("XDG_SESSION_ID").unwrap_or_default();
+    run("loginctl", &["terminate-session", &session])
+}
+
+// Suspend, hibernate, reboot or poweroff through logind, or systemctl
+// which has a verb for each.
+pub fn power(action: &str) -> io::Result<()> {
+    let method = match action {
+        SUSPEND   => "Suspend",
+        HIBERNATE => "Hibernate",
+        REBOOT    => "Reboot",
+        _         => "PowerOff",
+    };
+
+    // The argument lets polkit ask for a password if it needs one
+    if logind("/org/freedesktop/login1",
+              &format!("org.freedesktop.login1.Manager.{}", method),
+              &["true"]) {
+        return Ok(());
+    }
+
+    run("systemctl", &[action])
+}
+
+fn caffeine_pid_file() -> PathBuf {
+    let dir = env::var("XDG_RUNTIME_DIR").unwrap_or("/tmp".to_string());
+    PathBuf::from(dir).join("blaunch-caffeine.pid")
+}
+
+// Whether a /proc/<pid>/cmdline is the systemd-inhibit caffeine starts.
+pub fn is_inhibitor(cmdline: &[u8]) -> bool {
+    let args: Vec<&[u8]> = cmdline.split(|b| *b == 0).collect();
+
+    args.first().is_some_and(|a| a.ends_with(b"systemd-inhibit")) &&
+        args.contains(&&b"--who=blaunch"[..]) &&
+        args.contains(&&b"--why=caffeine"[..])
+}
+
+// A stale pid file is left behind if the inhibitor was killed elsewhere,
+// and the pid may have gone to another process since. Without a runtime
+// dir the file is in /tmp where anyone could have written it. So the pid
+// only counts while it's our own inhibitor.
+fn caffeine_pid() -> Option<u32> {
+    let raw = fs::read_to_string(caffeine_pid_file()).ok()?;
+    let pid: u32 = raw.trim().parse().ok()?;
+
+    let owner = fs::metadata(format!("/proc/{}", pid)).ok()?.uid();
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+
+    if Some(owner) == processes::current_uid() && is_inhibitor(&cmdline) {
+        Some(pid)
+    } else {
+        None
+    }
+}
+
+pub fn caffeine_active() -> bool {
+    caffeine_pid().is_some()
+}
+
+// An idle inhibitor only lasts as long as the process holding the logind
+// inhibitor lock, so caffeine runs a long-lived systemd-inhibit in the
+// background and remembers its pid to turn it off again.
+pub fn toggle_caffeine() -> io::Result<()> {
+    if let Some(pid) = caffeine_pid() {
+        run("kill", &[&pid.to_string()])?;
+        return fs::remove_file(caffeine_pid_file());
+    }
+
+    let mut child = Command::new("systemd-inhibit").args([
+        "--what=idle", "--who=blaunch", "--why=caffeine",
+        "sleep", "infinity",
+    ]).spawn()?;
+
+    fs::write(caffeine_pid_file(), child.id().to_string())?;
+
+    // Reap it once it's killed, so a daemon toggling caffeine doesn't
+    // collect zombies. Without a daemon we exit and init takes it over.
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_inhibitor, run};
+
+    #[test]
+    fn it_recognizes_the_caffeine_inhibitor() {
+        assert!(is_inhibitor(b"/usr/bin/systemd-inhibit\0--what=idle\0\
+                               --who=blaunch\0--why=caffeine\0sleep\0\
+                               infinity\0"));
+        assert!(!is_inhibitor(b"systemd-inhibit\0--who=other\0\
+                                --why=caffeine\0sleep\0infinity\0"));
+        assert!(!is_inhibitor(b"sleep\0--who=blaunch\0--why=caffeine\0"));
+        assert!(!is_inhibitor(b""));
+    }
+
+    #[test]
+    fn it_fails_when_the_fallback_tool_fails() {
+        assert!(run("true", &[]).is_ok());
+        assert!(run("false", &[]).is_err());
+        assert!(run("blaunch-no-such-tool", &[]).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+// Snapshots of the result list for the clipboard: as text, one row per
+// line, or as an image of what's on screen.
+
+// Each row's columns side by side.
+pub fn text(rows: &[Vec<String>]) -> String {
+    rows.iter().map(|r| {
+        r.iter().map(|c| c.trim()).filter(|c| !c.is_empty()).
+            collect::<Vec<&str>>().join("  ")
+    }).collect::<Vec<String>>().join("\n")
+}
+
+// Cairo's ARGB32 pixels, native-endian words with the color premultiplied
+// by alpha, as the straight RGBA bytes a pixbuf takes.
+pub fn rgba(data: &[u8], width: usize, height: usize, stride: usize)
+    -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(width * height * 4);
+
+    for row in data.chunks(stride).take(height) {
+        for pixel in row[..width * 4].chunks(4) {
+            let word = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2],
+                                           pixel[3]]);
+            let alpha = word >> 24;
+
+            // Fully transparent pixels have no color left to recover
+            let straight = |shift: u32| {
+                (((word >> shift) & 0xff) * 255 + alpha / 2).
+                    checked_div(alpha).unwrap_or(0)
+            };
+
+            pixels.extend([straight(16) as u8, straight(8) as u8,
+                           straight(0) as u8, alpha as u8]);
+        }
+    }
+
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rgba, text};
+
+    #[test]
+    fn it_renders_rows_as_text() {
+        let rows = vec![
+            vec!["Firefox".to_string(), "web browser".to_string()],
+            vec![" 42 ".to_string(), String::new()],
+            vec![],
+        ];
+
+        assert_eq!(text(&rows), "Firefox  web browser\n42\n");
+    }
+
+    #[test]
+    fn it_converts_cairo_pixels() {
+        let pixel = |argb: u32| argb.to_ne_bytes().to_vec();
+
+        // Half transparent red, then opaque blue, then padding
+        let mut data = pixel(0x80800000);
+        data.extend(pixel(0xff0000ff));
+        data.extend([0; 4]);
+
+        assert_eq!(rgba(&data, 2, 1, 12),
+                   vec![255, 0, 0, 128, 0, 0, 255, 255]);
+        assert_eq!(rgba(&pixel(0), 1, 1, 4), vec![0, 0, 0, 0]);
+    }
+}
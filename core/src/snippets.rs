@@ -0,0 +1,111 @@
+// Named text snippets from the config, expanded by picking them after the
+// prefix, like ";addr".
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer};
+
+use config::Tools;
+use desktop;
+use fuzzy;
+
+#[derive(Clone)]
+pub struct Config {
+    pub prefix: String,
+    // Type the expansion into the window that had focus before the
+    // launcher, instead of copying it
+    pub type_it: bool,
+    pub snippets: BTreeMap<String, String>,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        if self.type_it { vec![desktop::typing_tool()] } else { vec![] }
+    }
+}
+
+fn default_prefix() -> String {
+    ";".to_string()
+}
+
+// The section is either just the snippets, {"addr": "123 Main St"}, or
+// the snippets with options, {"type_it": true, "snippets": {...}}.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Section {
+    Options {
+        #[serde(default = "default_prefix")]
+        prefix: String,
+        #[serde(default)]
+        type_it: bool,
+        snippets: BTreeMap<String, String>,
+    },
+    Snippets(BTreeMap<String, String>),
+}
+
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Config, D::Error> {
+        Ok(match Section::deserialize(d)? {
+            Section::Options { prefix, type_it, snippets } => Config {
+                prefix,
+                type_it,
+                snippets,
+            },
+            Section::Snippets(snippets) => Config {
+                prefix: default_prefix(),
+                type_it: false,
+                snippets,
+            },
+        })
+    }
+}
+
+// Snippets whose name matches, best first. The expansion is searched too,
+// after the names.
+pub fn search<'a>(config: &'a Config, query: &str) -> Vec<(&'a str, &'a str)> {
+    let mut found: Vec<((bool, usize), &str, &str)> = config.snippets.iter().
+        filter_map(|(name, text)| {
+            let score = match fuzzy::score(query, name) {
+                Some(s) => (false, s),
+                None    => (true, fuzzy::score(query, text)?),
+            };
+
+            Some((score, name.as_str(), text.as_str()))
+        }).collect();
+
+    found.sort_by_key(|&(score, _, _)| score);
+    found.into_iter().map(|(_, name, text)| (name, text)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use super::{Config, search};
+
+    #[test]
+    fn it_reads_both_config_forms() {
+        let plain: Config = serde_json::from_str(
+            r#"{"addr": "123 Main St", "sig": "Thanks"}"#).unwrap();
+
+        assert_eq!(plain.prefix, ";");
+        assert_eq!(plain.snippets["addr"], "123 Main St");
+
+        let options: Config = serde_json::from_str(
+            r#"{"type_it": true, "snippets": {"sig": "Thanks"}}"#).unwrap();
+
+        assert!(options.type_it);
+        assert_eq!(options.snippets["sig"], "Thanks");
+    }
+
+    #[test]
+    fn it_matches_names_before_expansions() {
+        let config: Config = serde_json::from_str(
+            r#"{"sig": "Regards, Ada", "addr": "123 Main St, Adams"}"#).
+            unwrap();
+
+        assert_eq!(search(&config, "ad"), vec![
+            ("addr", "123 Main St, Adams"),
+            ("sig", "Regards, Ada"),
+        ]);
+    }
+}
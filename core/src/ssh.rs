@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+use std::fs;
+
+use paths::expand_home;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_terminal")]
+    pub terminal: String,
+}
+
+fn default_prefix() -> String {
+    "ssh".to_string()
+}
+
+fn default_terminal() -> String {
+    "xterm".to_string()
+}
+
+// Host aliases from ~/.ssh/config. Patterns like "*.example.com" aren't
+// hosts you can connect to, so they're left out.
+pub fn parse_config(contents: &str) -> Vec<String> {
+    let mut hosts = vec![];
+
+    for line in contents.lines() {
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some(k) if k.eq_ignore_ascii_case("host") => {},
+            _                                         => continue,
+        };
+
+        hosts.extend(words.filter(|h| !h.contains(['*', '?', '!'])).
+            map(|h| h.to_string()));
+    }
+
+    hosts
+}
+
+// Host names from known_hosts. Hashed entries can't be read back, and
+// "[host]:port" ones need a port ssh wouldn't know about, so both are
+// skipped.
+pub fn parse_known_hosts(contents: &str) -> Vec<String> {
+    let mut hosts = vec![];
+
+    for line in contents.lines() {
+        let names = match line.split_whitespace().next() {
+            Some(n) if !n.starts_with(['#', '|', '@']) => n,
+            _                                          => continue,
+        };
+
+        hosts.extend(names.split(',').filter(|h| !h.starts_with('[')).
+            map(|h| h.to_string()));
+    }
+
+    hosts
+}
+
+// Configured aliases first, then everything else that's been connected to.
+pub fn hosts() -> Vec<String> {
+    let read = |path: &str| fs::read_to_string(expand_home(path)).
+        unwrap_or_default();
+
+    let mut seen = HashSet::new();
+
+    parse_config(&read("~/.ssh/config")).into_iter().
+        chain(parse_known_hosts(&read("~/.ssh/known_hosts"))).
+        filter(|h| seen.insert(h.clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_config, parse_known_hosts};
+
+    #[test]
+    fn it_reads_config_hosts() {
+        let config = "Host *\n    ForwardAgent no\n\
+                      Host box nas\n    User brian\n\
+                      host *.internal\n\
+                      Match host x\n";
+
+        assert_eq!(parse_config(config), vec!["box", "nas"]);
+    }
+
+    #[test]
+    fn it_reads_known_hosts() {
+        let known = "github.com,140.82.112.3 ssh-ed25519 AAAA\n\
+                     |1|abc=|def= ssh-rsa AAAA\n\
+                     [box]:2222 ssh-ed25519 AAAA\n\
+                     # comment\n\
+                     @cert-authority *.corp ssh-rsa AAAA\n";
+
+        assert_eq!(parse_known_hosts(known),
+                   vec!["github.com", "140.82.112.3"]);
+    }
+}
@@ -0,0 +1,170 @@
+// Installed Steam games, from the library folders and the app manifests
+// Steam keeps in each of them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use config::Tools;
+use paths::expand_home;
+use vdf::{self, Value};
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // Steam's own directory; the usual places are tried when it's not set
+    pub root: Option<String>,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["steam"]
+    }
+}
+
+fn default_prefix() -> String {
+    "game".to_string()
+}
+
+// Where Steam lives for native, older native and Flatpak installs
+const ROOTS: &[&str] = &[
+    "~/.local/share/Steam",
+    "~/.steam/steam",
+    "~/.var/app/com.valvesoftware.Steam/.local/share/Steam",
+];
+
+// Runtimes and redistributables that get app manifests like games do
+const TOOLS: &[&str] = &["Proton", "Steam Linux Runtime", "Steamworks"];
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Game {
+    pub id: String,
+    pub name: String,
+}
+
+// Library paths from libraryfolders.vdf. Newer files have a block per
+// library with a "path" key; older ones map numbers straight to paths.
+pub fn parse_library_folders(doc: &Value) -> Vec<PathBuf> {
+    let folders = match doc.get("libraryfolders") {
+        Some(f) => f,
+        None    => return vec![],
+    };
+
+    folders.pairs().iter().
+        filter(|(key, _)| key.parse::<u32>().is_ok()).
+        filter_map(|(_, value)| match *value {
+            Value::Str(ref path) => Some(PathBuf::from(path)),
+            Value::Map(_)        => value.get("path").
+                and_then(Value::as_str).map(PathBuf::from),
+        }).collect()
+}
+
+// A game from an appmanifest_<id>.acf, if it's a game rather than a tool.
+pub fn parse_manifest(doc: &Value) -> Option<Game> {
+    let state = doc.get("AppState")?;
+    let id = state.get("appid")?.as_str()?;
+    let name = state.get("name")?.as_str()?;
+
+    if TOOLS.iter().any(|t| name.starts_with(t)) {
+        return None;
+    }
+
+    Some(Game {
+        id: id.to_string(),
+        name: name.to_string(),
+    })
+}
+
+fn read(path: &Path) -> Option<Value> {
+    fs::read_to_string(path).ok().and_then(|t| vdf::parse(&t).ok())
+}
+
+pub fn root(config: &Config) -> Option<PathBuf> {
+    match config.root {
+        Some(ref r) => Some(PathBuf::from(expand_home(r))),
+        None        => ROOTS.iter().map(|r| PathBuf::from(expand_home(r))).
+            find(|r| r.join("steamapps").is_dir()),
+    }
+}
+
+pub fn games(root: &Path) -> Vec<Game> {
+    let mut libraries = vec![root.to_path_buf()];
+
+    if let Some(doc) = read(&root.join("steamapps/libraryfolders.vdf")) {
+        for library in parse_library_folders(&doc) {
+            if !libraries.contains(&library) {
+                libraries.push(library);
+            }
+        }
+    }
+
+    let mut games = vec![];
+
+    for library in libraries {
+        let entries = match fs::read_dir(library.join("steamapps")) {
+            Ok (e) => e,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            if !name.starts_with("appmanifest_") || !name.ends_with(".acf") {
+                continue;
+            }
+
+            if let Some(game) = read(&entry.path()).
+                and_then(|d| parse_manifest(&d)) {
+                games.push(game);
+            }
+        }
+    }
+
+    games.sort_by_key(|g| g.name.to_lowercase());
+    games.dedup();
+    games
+}
+
+pub fn launch_command(game: &Game) -> String {
+    format!("steam steam://rungameid/{}", game.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use vdf::parse;
+    use super::{Game, parse_library_folders, parse_manifest};
+
+    #[test]
+    fn it_reads_library_folders() {
+        let new = parse("\"libraryfolders\" {\n\
+                         \"0\" { \"path\" \"/home/b/.local/share/Steam\" }\n\
+                         \"1\" { \"path\" \"/mnt/games\" \"apps\" {} }\n\
+                         }").unwrap();
+        let old = parse("\"LibraryFolders\" {\n\
+                         \"TimeNextStatsReport\" \"1\"\n\
+                         \"1\" \"/mnt/games\"\n\
+                         }").unwrap();
+
+        assert_eq!(parse_library_folders(&new), vec![
+            PathBuf::from("/home/b/.local/share/Steam"),
+            PathBuf::from("/mnt/games"),
+        ]);
+        assert_eq!(parse_library_folders(&old),
+                   vec![PathBuf::from("/mnt/games")]);
+    }
+
+    #[test]
+    fn it_skips_tools() {
+        let game = parse("\"AppState\" { \"appid\" \"620\" \
+                          \"name\" \"Portal 2\" }").unwrap();
+        let tool = parse("\"AppState\" { \"appid\" \"1493710\" \
+                          \"name\" \"Proton Experimental\" }").unwrap();
+
+        assert_eq!(parse_manifest(&game), Some(Game {
+            id: "620".to_string(),
+            name: "Portal 2".to_string(),
+        }));
+        assert_eq!(parse_manifest(&tool), None);
+    }
+}
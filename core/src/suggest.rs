@@ -0,0 +1,167 @@
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use serde_json;
+
+use network;
+
+// Wait this long after the last keystroke before hitting the network.
+const DEBOUNCE_MS: u64 = 250;
+const FETCH_TIMEOUT_SECS: &str = "3";
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Suggestions {
+    Items(Vec<String>),
+    Offline,
+}
+
+// Fetches OpenSearch suggestions on a worker thread. Every request bumps a
+// generation counter; a worker only delivers its results if no newer
+// request (or cancel) happened in the meantime, so stale suggestions never
+// overwrite fresh ones.
+pub struct Suggester {
+    url: String,
+    generation: Arc<AtomicUsize>,
+    monitor: network::Monitor,
+    sender: Sender<(usize, Suggestions)>,
+    receiver: Receiver<(usize, Suggestions)>,
+}
+
+impl Suggester {
+    // `url` is an OpenSearch suggest endpoint containing a {query}
+    // placeholder, e.g. "https://duckduckgo.com/ac/?type=list&q={query}".
+    pub fn new(url: &str) -> Suggester {
+        let (sender, receiver) = channel();
+
+        Suggester {
+            url: url.to_string(),
+            generation: Arc::new(AtomicUsize::new(0)),
+            monitor: network::Monitor::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn request(&self, query: &str) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+        if query.trim().is_empty() {
+            return;
+        }
+
+        let url = fill_query(&self.url, query);
+        let current = self.generation.clone();
+        let monitor = self.monitor.clone();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(DEBOUNCE_MS));
+
+            if current.load(Ordering::SeqCst) != generation {
+                return;
+            }
+
+            let result = if monitor.is_online() {
+                Suggestions::Items(fetch(&url))
+            } else {
+                Suggestions::Offline
+            };
+
+            if current.load(Ordering::SeqCst) == generation {
+                let _ = sender.send((generation, result));
+            }
+        });
+    }
+
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    // Called from the UI loop. Returns the newest results that are still
+    // current, if any arrived since the last poll.
+    pub fn poll(&self) -> Option<Suggestions> {
+        let mut latest = None;
+
+        while let Ok((generation, result)) = self.receiver.try_recv() {
+            if generation == self.generation.load(Ordering::SeqCst) {
+                latest = Some(result);
+            }
+        }
+
+        latest
+    }
+}
+
+fn fetch(url: &str) -> Vec<String> {
+    let output = Command::new("curl").
+        args(["-s", "-f", "--max-time", FETCH_TIMEOUT_SECS, url]).output();
+
+    match output {
+        Ok (ref o) if o.status.success() =>
+            parse_suggestions(&String::from_utf8_lossy(&o.stdout)),
+        _ => vec![],
+    }
+}
+
+// OpenSearch suggestion responses look like ["query", ["one", "two"], ...].
+pub fn parse_suggestions(body: &str) -> Vec<String> {
+    let value: serde_json::Value = match serde_json::from_str(body) {
+        Ok (v) => v,
+        Err(_) => return vec![],
+    };
+
+    let items = match value.get(1).and_then(|v| v.as_array()) {
+        Some(i) => i,
+        None    => return vec![],
+    };
+
+    items.iter().filter_map(|i| i.as_str()).map(|i| i.to_string()).collect()
+}
+
+pub fn url_encode(input: &str) -> String {
+    let mut ret = String::new();
+
+    for b in input.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' |
+            b'-' | b'_' | b'.' | b'~' => ret.push(b as char),
+            b' ' => ret.push('+'),
+            _    => ret.push_str(&format!("%{:02X}", b)),
+        }
+    }
+
+    ret
+}
+
+// Fills the {query} placeholder in a URL template with the encoded query.
+pub fn fill_query(template: &str, query: &str) -> String {
+    template.replace("{query}", &url_encode(query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fill_query, parse_suggestions, url_encode};
+
+    #[test]
+    fn it_parses_opensearch_suggestions() {
+        assert_eq!(parse_suggestions(r#"["rust", ["rust gtk", "rustup"]]"#),
+                   vec!["rust gtk", "rustup"]);
+    }
+
+    #[test]
+    fn it_ignores_malformed_suggestions() {
+        assert_eq!(parse_suggestions("<html>"), Vec::<String>::new());
+        assert_eq!(parse_suggestions(r#"{"a": 1}"#), Vec::<String>::new());
+    }
+
+    #[test]
+    fn it_url_encodes_queries() {
+        assert_eq!(url_encode("rust gtk&c++"), "rust+gtk%26c%2B%2B");
+        assert_eq!(fill_query("https://example.com/?q={query}&t=1", "a b"),
+                   "https://example.com/?q=a+b&t=1");
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use paths::expand_home;
+
+// GTK stylesheet for the launcher. It's re-applied whenever the file
+// changes, so themes can be edited with the launcher open. The result rows
+// carry the classes from the menu ("running", "done", "failed" and whatever
+// nodes list) for styling.
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_file")]
+    pub file: String,
+    // Quick settings for when a whole stylesheet is overkill. The
+    // stylesheet is applied over them, so it wins where both set something.
+    // The font is a Pango description like "Monospace 11".
+    pub font: Option<String>,
+    pub foreground: Option<String>,
+    pub background: Option<String>,
+    // Background of the selected result
+    pub selected: Option<String>,
+    // Pixels of padding around each result row
+    pub padding: Option<u32>,
+}
+
+fn default_file() -> String {
+    "~/.config/blaunch/style.css".to_string()
+}
+
+// Where the CSS for the inline settings is written for GTK to load
+const SETTINGS_FILE: &str = "~/.cache/blaunch/settings.css";
+
+// Styles that come before the inline settings, for the overlay layout. A
+// stylesheet can override them like anything else.
+const BASE_CSS: &str = "\
+window.overlay { background-color: rgba(0, 0, 0, 0.75); }
+window.overlay entry { font-size: 24pt; padding: 12px; }
+window.overlay .row { padding: 6px; }
+";
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            file: default_file(),
+            font: None,
+            foreground: None,
+            background: None,
+            selected: None,
+            padding: None,
+        }
+    }
+}
+
+// Splits a Pango font description into its family and point size, e.g.
+// "DejaVu Sans 11" into ("DejaVu Sans", Some(11)).
+pub fn parse_font(font: &str) -> (&str, Option<u32>) {
+    let font = font.trim();
+
+    match font.rfind(' ') {
+        Some(i) => match font[i + 1..].parse() {
+            Ok (size) => (font[..i].trim(), Some(size)),
+            Err(_)    => (font, None),
+        },
+        None => match font.parse() {
+            Ok (size) => ("", Some(size)),
+            Err(_)    => (font, None),
+        },
+    }
+}
+
+// The inline settings as CSS, empty if there aren't any.
+pub fn settings_css(config: &Config) -> String {
+    let mut window = vec![];
+
+    if let Some(ref f) = config.font {
+        let (family, size) = parse_font(f);
+
+        if !family.is_empty() {
+            window.push(format!("font-family: \"{}\";", family));
+        }
+
+        if let Some(s) = size {
+            window.push(format!("font-size: {}pt;", s));
+        }
+    }
+
+    if let Some(ref c) = config.foreground {
+        window.push(format!("color: {};", c));
+    }
+
+    if let Some(ref c) = config.background {
+        window.push(format!("background-color: {};", c));
+    }
+
+    let mut css = String::new();
+
+    if !window.is_empty() {
+        css.push_str(&format!("window {{ {} }}\n", window.join(" ")));
+    }
+
+    if let Some(ref c) = config.selected {
+        css.push_str(&format!(".selected {{ background-color: {}; }}\n", c));
+    }
+
+    if let Some(p) = config.padding {
+        css.push_str(&format!(".row {{ padding: {}px; }}\n", p));
+    }
+
+    css
+}
+
+// Writes the base styles and inline settings out for GTK, which only loads
+// CSS from files.
+pub fn write_settings(config: &Config) -> io::Result<PathBuf> {
+    let css = format!("{}{}", BASE_CSS, settings_css(config));
+    let path = PathBuf::from(expand_home(SETTINGS_FILE));
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(&path, css)?;
+    Ok(path)
+}
+
+// Notices when the stylesheet is written, created or removed, by polling its
+// modification time.
+pub struct Watcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    checked: bool,
+}
+
+impl Watcher {
+    pub fn new(file: &str) -> Watcher {
+        Watcher {
+            path: PathBuf::from(expand_home(file)),
+            modified: None,
+            checked: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Whether the file exists at all; without it there's no theme
+    pub fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+
+    // True the first time and whenever the file changed since the last
+    // check.
+    pub fn changed(&mut self) -> bool {
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).
+            ok();
+
+        if self.checked && modified == self.modified {
+            return false;
+        }
+
+        self.checked = true;
+        self.modified = modified;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+    use super::{Config, Watcher, parse_font, settings_css};
+
+    #[test]
+    fn it_notices_theme_changes() {
+        let path = env::temp_dir().
+            join(format!("blaunch-theme-test.{}.css", process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut watcher = Watcher::new(path.to_str().unwrap());
+        assert!(watcher.changed());
+        assert!(!watcher.exists());
+        assert!(!watcher.changed());
+
+        fs::write(&path, "label { color: red; }").unwrap();
+        assert!(watcher.changed());
+        assert!(watcher.exists());
+        assert!(!watcher.changed());
+
+        fs::remove_file(&path).unwrap();
+        assert!(watcher.changed());
+    }
+
+    #[test]
+    fn it_parses_fonts() {
+        assert_eq!(parse_font("DejaVu Sans 11"), ("DejaVu Sans", Some(11)));
+        assert_eq!(parse_font("Monospace"), ("Monospace", None));
+        assert_eq!(parse_font("12"), ("", Some(12)));
+    }
+
+    #[test]
+    fn it_writes_settings_as_css() {
+        assert_eq!(settings_css(&Config::default()), "");
+
+        let config = Config {
+            font: Some("Monospace 11".to_string()),
+            background: Some("#282828".to_string()),
+            padding: Some(4),
+            ..Config::default()
+        };
+
+        assert_eq!(settings_css(&config),
+                   "window { font-family: \"Monospace\"; font-size: 11pt; \
+                    background-color: #282828; }\n\
+                    .row { padding: 4px; }\n");
+    }
+}
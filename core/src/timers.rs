@@ -0,0 +1,311 @@
+// Countdown timers and stopwatches. They're kept in a file in the runtime
+// directory, so a timer started from any launcher reaches the daemon, which
+// checks the file every second and sends a notification when one is due.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use config::Tools;
+
+// Builtins starting with this word start and cancel timers, like
+// "timer start 600 tea" or "timer cancel 3"
+pub const TIMER: &str = "timer";
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // Played when a timer goes off, like
+    // "paplay /usr/share/sounds/freedesktop/stereo/complete.oga"
+    pub sound: Option<String>,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["notify-send"]
+    }
+}
+
+fn default_prefix() -> String {
+    "timer".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Timer {
+    pub id: u64,
+    // Seconds since the epoch
+    pub started: u64,
+    // How long a countdown runs; None for a stopwatch
+    pub seconds: Option<u64>,
+    pub label: String,
+}
+
+impl Timer {
+    pub fn due(&self) -> Option<u64> {
+        self.seconds.map(|s| self.started + s)
+    }
+
+    pub fn name(&self) -> &str {
+        if self.label.is_empty() {
+            if self.seconds.is_some() { "Timer" } else { "Stopwatch" }
+        } else {
+            &self.label
+        }
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).
+        unwrap_or(0)
+}
+
+// "10m", "1h30m", "90s" or "1h 5m". A bare number is minutes. Zero isn't
+// a duration; a timer of length zero would be stored as a stopwatch.
+pub fn parse_duration(text: &str) -> Option<u64> {
+    if let Ok(minutes) = text.parse::<u64>() {
+        return if minutes == 0 { None } else { Some(minutes * 60) };
+    }
+
+    let mut total = 0;
+    let mut number = String::new();
+
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _   => return None,
+        };
+
+        total += number.parse::<u64>().ok()? * unit;
+        number.clear();
+    }
+
+    if !number.is_empty() || total == 0 {
+        return None;
+    }
+
+    Some(total)
+}
+
+// The two largest units, like "1h 5m" or "4m 30s".
+pub fn format_duration(seconds: u64) -> String {
+    let (h, m, s) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    match (h, m, s) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, 0) => format!("{}m", m),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, 0, _) => format!("{}h", h),
+        (h, m, _) => format!("{}h {}m", h, m),
+    }
+}
+
+// One timer per line: id, start, length (0 for a stopwatch) and label,
+// separated by tabs.
+pub fn parse(contents: &str) -> Vec<Timer> {
+    contents.lines().filter_map(|l| {
+        let mut fields = l.splitn(4, '\t');
+
+        let id = fields.next()?.parse().ok()?;
+        let started = fields.next()?.parse().ok()?;
+        let seconds = fields.next()?.parse().ok()?;
+
+        Some(Timer {
+            id,
+            started,
+            seconds: if seconds == 0 { None } else { Some(seconds) },
+            label: fields.next().unwrap_or("").to_string(),
+        })
+    }).collect()
+}
+
+fn path() -> PathBuf {
+    let dir = env::var("XDG_RUNTIME_DIR").unwrap_or("/tmp".to_string());
+    PathBuf::from(dir).join("blaunch-timers")
+}
+
+pub fn list() -> Vec<Timer> {
+    parse(&fs::read_to_string(path()).unwrap_or_default())
+}
+
+// Written to a temporary file first, so the daemon never reads half a file.
+fn save(timers: &[Timer]) -> io::Result<()> {
+    let contents: String = timers.iter().map(|t| {
+        format!("{}\t{}\t{}\t{}\n", t.id, t.started, t.seconds.unwrap_or(0),
+                t.label)
+    }).collect();
+
+    let path = path();
+    let temp = path.with_extension("tmp");
+
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, &path)
+}
+
+pub fn start_builtin(seconds: Option<u64>, label: &str) -> String {
+    format!("{} start {} {}", TIMER, seconds.unwrap_or(0), label).
+        trim_end().to_string()
+}
+
+pub fn cancel_builtin(id: u64) -> String {
+    format!("{} cancel {}", TIMER, id)
+}
+
+fn notify(title: &str, body: &str) -> io::Result<()> {
+    Command::new("notify-send").args(["-a", "blaunch", title, body]).
+        spawn().map(|_| ())
+}
+
+#[derive(PartialEq, Eq, Debug)]
+enum Request {
+    // Length in seconds, 0 for a stopwatch, and the label
+    Start(u64, String),
+    Cancel(u64),
+}
+
+fn parse_request(args: &str) -> Option<Request> {
+    let mut words = args.splitn(3, ' ');
+
+    match (words.next()?, words.next()?.parse().ok()?) {
+        ("start", seconds) => Some(Request::Start(seconds,
+            words.next().unwrap_or("").replace('\t', " "))),
+        ("cancel", id)     => Some(Request::Cancel(id)),
+        _                  => None,
+    }
+}
+
+fn apply(builtin: &str, args: &str) -> io::Result<()> {
+    match parse_request(args) {
+        Some(Request::Start(seconds, label)) => {
+            let mut timers = list();
+
+            timers.push(Timer {
+                id: timers.iter().map(|t| t.id + 1).max().unwrap_or(1),
+                started: now(),
+                seconds: if seconds == 0 { None } else { Some(seconds) },
+                label,
+            });
+
+            save(&timers)
+        },
+        Some(Request::Cancel(id)) => {
+            let (stopped, kept): (Vec<Timer>, Vec<Timer>) = list().
+                into_iter().partition(|t| t.id == id);
+
+            save(&kept)?;
+
+            // A stopwatch says how long it ran when it's stopped
+            for s in stopped.iter().filter(|t| t.seconds.is_none()) {
+                notify(s.name(),
+                       &format_duration(now().saturating_sub(s.started)))?;
+            }
+
+            Ok(())
+        },
+        None => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                   format!("Can't read {}", builtin))),
+    }
+}
+
+// Whether a timer builtin makes sense, without carrying it out, or None
+// for any other builtin.
+pub fn check(builtin: &str) -> Option<Result<(), String>> {
+    let args = builtin.strip_prefix(TIMER)?.strip_prefix(' ')?;
+
+    Some(parse_request(args).map(|_| ()).
+        ok_or_else(|| format!("Can't read {}", builtin)))
+}
+
+// Carries out a timer builtin, or None for any other builtin.
+pub fn run(builtin: &str) -> Option<io::Result<()>> {
+    let args = builtin.strip_prefix(TIMER)?.strip_prefix(' ')?;
+    Some(apply(builtin, args))
+}
+
+// Notifies about the timers that are due and drops them. The daemon calls
+// this every second.
+pub fn fire(config: Option<&Config>) -> io::Result<()> {
+    let now = now();
+    let (due, waiting): (Vec<Timer>, Vec<Timer>) = list().into_iter().
+        partition(|t| t.due().is_some_and(|d| d <= now));
+
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    // Dropped first, so a failing notification doesn't repeat every second
+    save(&waiting)?;
+
+    for t in due.iter() {
+        let length = format_duration(t.seconds.unwrap_or(0));
+        notify(t.name(), &format!("{} timer is done", length))?;
+    }
+
+    if let Some(sound) = config.and_then(|c| c.sound.as_ref()) {
+        Command::new("sh").arg("-c").arg(sound).spawn()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Timer, format_duration, parse, parse_duration, run};
+
+    #[test]
+    fn it_parses_durations() {
+        assert_eq!(parse_duration("10m"), Some(600));
+        assert_eq!(parse_duration("1h30m"), Some(5400));
+        assert_eq!(parse_duration("90s"), Some(90));
+        assert_eq!(parse_duration("5"), Some(300));
+        assert_eq!(parse_duration("0"), None);
+        assert_eq!(parse_duration("0m"), None);
+        assert_eq!(parse_duration("10x"), None);
+        assert_eq!(parse_duration("1h30"), None);
+        assert_eq!(parse_duration("tea"), None);
+    }
+
+    #[test]
+    fn it_rejects_malformed_timer_builtins() {
+        assert!(run("timer start abc").unwrap().is_err());
+        assert!(run("timer cancel").unwrap().is_err());
+        assert!(run("timers").is_none());
+    }
+
+    #[test]
+    fn it_formats_durations() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(270), "4m 30s");
+        assert_eq!(format_duration(600), "10m");
+        assert_eq!(format_duration(3900), "1h 5m");
+        assert_eq!(format_duration(7200), "2h");
+    }
+
+    #[test]
+    fn it_parses_the_timer_file() {
+        assert_eq!(parse("1\t1000\t600\tsteep tea\n2\t1100\t0\t\nbad\n"), vec![
+            Timer {
+                id: 1,
+                started: 1000,
+                seconds: Some(600),
+                label: "steep tea".to_string(),
+            },
+            Timer {
+                id: 2,
+                started: 1100,
+                seconds: None,
+                label: String::new(),
+            },
+        ]);
+    }
+}
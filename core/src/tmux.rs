@@ -0,0 +1,106 @@
+// tmux sessions and their windows.
+
+use std::env;
+use std::process::Command;
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_terminal")]
+    pub terminal: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["tmux"]
+    }
+}
+
+fn default_prefix() -> String {
+    "tmux".to_string()
+}
+
+fn default_terminal() -> String {
+    "xterm".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Window {
+    pub session: String,
+    pub index: String,
+    pub name: String,
+    pub attached: bool,
+}
+
+impl Window {
+    pub fn target(&self) -> String {
+        format!("{}:{}", self.session, self.index)
+    }
+}
+
+const FORMAT: &str =
+    "#{session_name}\t#{window_index}\t#{window_name}\t#{session_attached}";
+
+// Lines of `tmux list-windows -a -F FORMAT`.
+pub fn parse_windows(output: &str) -> Vec<Window> {
+    output.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+
+        if fields.len() < 4 {
+            return None;
+        }
+
+        Some(Window {
+            session: fields[0].to_string(),
+            index: fields[1].to_string(),
+            name: fields[2].to_string(),
+            attached: fields[3] != "0",
+        })
+    }).collect()
+}
+
+// Every window of every session. No server running just means there's
+// nothing to list.
+pub fn windows() -> Result<Vec<Window>, String> {
+    let output = Command::new("tmux").
+        args(["list-windows", "-a", "-F", FORMAT]).output().
+        map_err(|e| format!("Can't run tmux: {}", e))?;
+
+    Ok(parse_windows(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// Inside tmux the current client switches over; otherwise a terminal opens
+// attached to the target, which is a session or "session:window".
+pub fn attach_command(terminal: &str, target: &str) -> String {
+    if env::var_os("TMUX").is_some() {
+        format!("tmux switch-client -t {}", shell_quote(target))
+    } else {
+        cli::in_terminal(terminal, &format!("tmux attach-session -t {}",
+                                            shell_quote(target)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Window, parse_windows};
+
+    #[test]
+    fn it_parses_windows() {
+        let output = "main\t0\tvim\t1\nmain\t1\tzsh\t1\nbuild\t0\tmake\t0\n";
+        let windows = parse_windows(output);
+
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[2], Window {
+            session: "build".to_string(),
+            index: "0".to_string(),
+            name: "make".to_string(),
+            attached: false,
+        });
+        assert_eq!(windows[1].target(), "main:1");
+    }
+}
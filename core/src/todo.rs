@@ -0,0 +1,129 @@
+// Tasks from a todo.txt file: one task per line, "x " in front of done ones
+// and an optional "(A) " priority in front of pending ones.
+
+use std::fs;
+
+use desktop::shell_quote;
+use paths::expand_home;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_file")]
+    pub file: String,
+}
+
+fn default_prefix() -> String {
+    "todo".to_string()
+}
+
+fn default_file() -> String {
+    "~/todo.txt".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Task {
+    // 1-based, as awk counts them
+    pub line: usize,
+    // The whole line, to check it's still there before changing it
+    pub raw: String,
+    pub priority: Option<char>,
+    pub text: String,
+}
+
+fn priority(line: &str) -> Option<(char, &str)> {
+    let mut chars = line.chars();
+
+    match (chars.next(), chars.next(), chars.next(), chars.next()) {
+        (Some('('), Some(p), Some(')'), Some(' ')) if p.is_ascii_uppercase() =>
+            Some((p, &line[4..])),
+        _ => None,
+    }
+}
+
+// Pending tasks, highest priority first, then in file order.
+pub fn parse(contents: &str) -> Vec<Task> {
+    let mut tasks: Vec<Task> = contents.lines().enumerate().
+        filter(|&(_, l)| !l.trim().is_empty() && !l.starts_with("x ")).
+        map(|(i, l)| {
+            let (priority, text) = match priority(l) {
+                Some((p, t)) => (Some(p), t),
+                None         => (None, l),
+            };
+
+            Task {
+                line: i + 1,
+                raw: l.to_string(),
+                priority,
+                text: text.trim().to_string(),
+            }
+        }).
+        collect();
+
+    // None sorts before Some, so unprioritized tasks are moved last
+    tasks.sort_by_key(|t| (t.priority.is_none(), t.priority));
+    tasks
+}
+
+pub fn tasks(config: &Config) -> Vec<Task> {
+    parse(&fs::read_to_string(expand_home(&config.file)).unwrap_or_default())
+}
+
+pub fn add_command(file: &str, text: &str) -> String {
+    format!("printf '%s\\n' {} >> {}", shell_quote(text), shell_quote(file))
+}
+
+// Marks the task done with today's date, todo.txt style, dropping its
+// priority. The file is written to a temporary file and renamed over the
+// original, and only a line that still matches is changed, in case the file
+// was edited since it was listed.
+pub fn done_command(file: &str, task: &Task) -> String {
+    let file = shell_quote(file);
+    let temp = format!("{}.tmp", file);
+
+    format!("d=$(date +%Y-%m-%d) t={} awk 'NR == {} && $0 == ENVIRON[\"t\"] \
+             {{ sub(/^\\([A-Z]\\) /, \"\"); $0 = \"x \" ENVIRON[\"d\"] \" \" \
+             $0 }} 1' {} > {} && mv {} {}",
+            shell_quote(&task.raw), task.line, file, temp, temp, file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Task, done_command, parse};
+
+    #[test]
+    fn it_parses_pending_tasks_by_priority() {
+        let tasks = parse("call mom\n\
+                           x 2017-01-01 pay rent\n\
+                           (B) file taxes +home\n\
+                           \n\
+                           (A) fix bug @work\n");
+
+        let texts: Vec<(usize, Option<char>, &str)> = tasks.iter().
+            map(|t| (t.line, t.priority, t.text.as_str())).collect();
+
+        assert_eq!(texts, vec![
+            (5, Some('A'), "fix bug @work"),
+            (3, Some('B'), "file taxes +home"),
+            (1, None, "call mom"),
+        ]);
+    }
+
+    #[test]
+    fn it_builds_done_commands() {
+        let task = Task {
+            line: 3,
+            raw: "(B) file taxes".to_string(),
+            priority: Some('B'),
+            text: "file taxes".to_string(),
+        };
+
+        assert_eq!(done_command("/tmp/todo.txt", &task),
+                   "d=$(date +%Y-%m-%d) t='(B) file taxes' awk 'NR == 3 && \
+                    $0 == ENVIRON[\"t\"] { sub(/^\\([A-Z]\\) /, \"\"); $0 = \
+                    \"x \" ENVIRON[\"d\"] \" \" $0 } 1' '/tmp/todo.txt' > \
+                    '/tmp/todo.txt'.tmp && mv '/tmp/todo.txt'.tmp \
+                    '/tmp/todo.txt'");
+    }
+}
@@ -0,0 +1,224 @@
+// Translations typed as "<prefix> <lang> <text>", like "tr de good
+// morning", from a configurable command or a translation URL. Lookups run
+// on a worker thread so typing never waits on the network.
+
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use serde_json;
+
+use config::{Tools, program};
+use desktop::shell_quote;
+use provider;
+use suggest::url_encode;
+
+// Wait this long after the last keystroke before translating
+const DEBOUNCE_MS: u64 = 400;
+const FETCH_TIMEOUT_SECS: &str = "5";
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // Prints the translation of {query} into {lang}, like
+    // "trans -b :{lang} {query}"; the URL is used when not set
+    pub command: Option<String>,
+    // Returns Google's translate_a JSON for {query} in {lang}
+    #[serde(default = "default_url")]
+    pub url: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        match self.command {
+            Some(ref c) => vec![program(c)],
+            None        => vec!["curl"],
+        }
+    }
+}
+
+fn default_prefix() -> String {
+    "tr".to_string()
+}
+
+fn default_url() -> String {
+    "https://translate.googleapis.com/translate_a/single?client=gtx&sl=auto\
+     &tl={lang}&dt=t&q={query}".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Request {
+    pub lang: String,
+    pub text: String,
+}
+
+impl Request {
+    // Identifies the request in the cache and among worker results
+    pub fn key(&self) -> String {
+        format!("{} {}", self.lang, self.text)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Translation {
+    pub text: String,
+    // The detected source language, when the backend says
+    pub from: Option<String>,
+}
+
+// The language and text after the prefix. The text can be empty while the
+// language is still being typed.
+pub fn parse(prefix: &str, input: &str) -> Option<Request> {
+    let rest = provider::prefix_rest(input, prefix)?;
+
+    let (lang, text) = rest.split_once(' ').unwrap_or((rest, ""));
+
+    if lang.is_empty() {
+        return None;
+    }
+
+    Some(Request {
+        lang: lang.to_lowercase(),
+        text: text.trim().to_string(),
+    })
+}
+
+// translate_a responses look like [[["Guten Morgen", "good morning", ..],
+// ..], null, "en", ..], with one entry per sentence.
+pub fn parse_response(body: &str) -> Result<Translation, String> {
+    let value: serde_json::Value = serde_json::from_str(body).
+        map_err(|e| format!("Can't parse the translation: {}", e))?;
+
+    let sentences = value.get(0).and_then(|s| s.as_array()).
+        ok_or("No translation in the response")?;
+
+    let text: String = sentences.iter().
+        filter_map(|s| s.get(0).and_then(|t| t.as_str())).collect();
+
+    Ok(Translation {
+        text: text.trim().to_string(),
+        from: value.get(2).and_then(|f| f.as_str()).map(|f| f.to_string()),
+    })
+}
+
+fn fill(template: &str, request: &Request, quote: fn(&str) -> String)
+    -> String {
+    template.replace("{lang}", &quote(&request.lang)).
+        replace("{query}", &quote(&request.text))
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program).args(args).output().
+        map_err(|e| format!("Can't run {}: {}", program, e))?;
+
+    if !output.status.success() {
+        return Err("Translation failed".to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn translate(config: &Config, request: &Request)
+    -> Result<Translation, String> {
+    if let Some(ref c) = config.command {
+        let command = fill(c, request, shell_quote);
+
+        return run("sh", &["-c", &command]).map(|o| Translation {
+            text: o.trim().to_string(),
+            from: None,
+        });
+    }
+
+    let url = fill(&config.url, request, url_encode);
+
+    run("curl", &["-s", "-f", "--max-time", FETCH_TIMEOUT_SECS, &url]).
+        and_then(|b| parse_response(&b))
+}
+
+pub type Finished = (String, Result<Translation, String>);
+
+// Translates on a worker thread. Like the search suggester, each request
+// bumps a generation counter and a worker only starts if nothing newer was
+// asked for while it waited, so fast typing doesn't pile up lookups.
+pub struct Translator {
+    config: Config,
+    generation: Arc<AtomicUsize>,
+    sender: Sender<Finished>,
+    receiver: Receiver<Finished>,
+}
+
+impl Translator {
+    pub fn new(config: Config) -> Translator {
+        let (sender, receiver) = channel();
+
+        Translator {
+            config,
+            generation: Arc::new(AtomicUsize::new(0)),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn request(&self, request: &Request) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let config = self.config.clone();
+        let request = request.clone();
+        let current = self.generation.clone();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(DEBOUNCE_MS));
+
+            if current.load(Ordering::SeqCst) != generation {
+                return;
+            }
+
+            let _ = sender.send((request.key(), translate(&config, &request)));
+        });
+    }
+
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    // Finished translations keyed by request, oldest first. Older results
+    // are still worth caching, so they aren't dropped.
+    pub fn poll(&self) -> Vec<Finished> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Request, Translation, parse, parse_response};
+
+    #[test]
+    fn it_parses_translation_queries() {
+        assert_eq!(parse("tr", "tr DE good  morning "), Some(Request {
+            lang: "de".to_string(),
+            text: "good  morning".to_string(),
+        }));
+        assert_eq!(parse("tr", "tr fr"), Some(Request {
+            lang: "fr".to_string(),
+            text: String::new(),
+        }));
+        assert_eq!(parse("tr", "tr "), None);
+        assert_eq!(parse("tr", "tree"), None);
+    }
+
+    #[test]
+    fn it_parses_translation_responses() {
+        let body = r#"[[["Guten Morgen. ","good morning.",null],
+                        ["Wie geht's?","How are you?",null]],null,"en"]"#;
+
+        assert_eq!(parse_response(body), Ok(Translation {
+            text: "Guten Morgen. Wie geht's?".to_string(),
+            from: Some("en".to_string()),
+        }));
+        assert!(parse_response("{}").is_err());
+    }
+}
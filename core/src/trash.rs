@@ -0,0 +1,122 @@
+// The home trash, laid out by the freedesktop.org trash spec: trashed
+// files in Trash/files and, for each, a Trash/info/<name>.trashinfo saying
+// where it came from and when it was deleted.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use desktop::shell_quote;
+use paths;
+use recent_files::percent_decode;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+fn default_prefix() -> String {
+    "trash".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Entry {
+    // The file's name under Trash/files
+    pub name: String,
+    // Where it was deleted from
+    pub path: String,
+    // ISO 8601 local time, so it sorts as text
+    pub deleted: String,
+}
+
+pub fn dir() -> PathBuf {
+    paths::data_home().join("Trash")
+}
+
+// The Path and DeletionDate of a .trashinfo file. The path is
+// percent-encoded.
+pub fn parse_info(info: &str) -> Option<(String, String)> {
+    let mut lines = info.lines().map(|l| l.trim()).
+        skip_while(|l| *l != "[Trash Info]").skip(1).
+        take_while(|l| !l.starts_with('['));
+
+    let mut path = None;
+    let mut deleted = String::new();
+
+    for line in &mut lines {
+        if let Some(p) = line.strip_prefix("Path=") {
+            path = Some(percent_decode(p));
+        } else if let Some(d) = line.strip_prefix("DeletionDate=") {
+            deleted = d.to_string();
+        }
+    }
+
+    path.map(|p| (p, deleted))
+}
+
+// Everything in the trash that still has its info file, newest first.
+pub fn entries(dir: &Path) -> Vec<Entry> {
+    let read = match fs::read_dir(dir.join("files")) {
+        Ok (r) => r,
+        Err(_) => return vec![],
+    };
+
+    let mut entries: Vec<Entry> = read.filter_map(|e| e.ok()).filter_map(|e| {
+        let name = e.file_name().to_string_lossy().into_owned();
+        let info = dir.join("info").join(format!("{}.trashinfo", name));
+        let (path, deleted) = parse_info(&fs::read_to_string(info).ok()?)?;
+
+        // Relative paths are relative to the trash's own top directory,
+        // which for the home trash is never used in practice
+        if !path.starts_with('/') {
+            return None;
+        }
+
+        Some(Entry { name, path, deleted })
+    }).collect();
+
+    entries.sort_by(|a, b| b.deleted.cmp(&a.deleted));
+    entries
+}
+
+// Moves the file back and drops its info file. Nothing is overwritten if
+// something has taken its place since.
+pub fn restore_command(dir: &Path, entry: &Entry) -> String {
+    let original = Path::new(&entry.path);
+    let parent = original.parent().unwrap_or(Path::new("/"));
+    let trashed = dir.join("files").join(&entry.name);
+    let info = dir.join("info").join(format!("{}.trashinfo", entry.name));
+
+    let target = shell_quote(&entry.path);
+
+    format!("[ ! -e {0} ] && [ ! -L {0} ] && mkdir -p {1} && \
+             mv -n {2} {0} && rm -f {3}", target,
+            shell_quote(&parent.to_string_lossy()),
+            shell_quote(&trashed.to_string_lossy()),
+            shell_quote(&info.to_string_lossy()))
+}
+
+pub fn empty_command(dir: &Path) -> String {
+    let dir = |d: &str| shell_quote(&dir.join(d).to_string_lossy());
+
+    format!("find {} {} -mindepth 1 -delete; rm -f {}", dir("files"),
+            dir("info"), dir("directorysizes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_info;
+
+    #[test]
+    fn it_parses_trash_info() {
+        let info = "[Trash Info]\n\
+                    Path=/home/user/My%20Notes.txt\n\
+                    DeletionDate=2017-05-01T12:30:00\n";
+
+        assert_eq!(parse_info(info), Some((
+            "/home/user/My Notes.txt".to_string(),
+            "2017-05-01T12:30:00".to_string(),
+        )));
+        assert_eq!(parse_info("Path=/tmp/x\n"), None);
+    }
+}
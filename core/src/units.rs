@@ -0,0 +1,114 @@
+// systemd units, listed and controlled through systemctl.
+
+use std::process::Command;
+
+use serde_json;
+
+use config::Tools;
+use desktop::shell_quote;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["systemctl"]
+    }
+}
+
+fn default_prefix() -> String {
+    "sv".to_string()
+}
+
+#[derive(Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Unit {
+    pub unit: String,
+    pub active: String,
+    pub sub: String,
+    pub description: String,
+    // From the user manager rather than the system one
+    #[serde(skip)]
+    pub user: bool,
+}
+
+pub fn parse_units(json: &str, user: bool) -> Result<Vec<Unit>, String> {
+    let mut units: Vec<Unit> = serde_json::from_str(json).
+        map_err(|e| format!("Can't parse systemctl output: {}", e))?;
+
+    for u in units.iter_mut() {
+        u.user = user;
+    }
+
+    Ok(units)
+}
+
+fn systemctl(user: bool) -> Command {
+    let mut command = Command::new("systemctl");
+
+    if user {
+        command.arg("--user");
+    }
+
+    command
+}
+
+// User units first, since they're the ones most often poked at.
+pub fn list() -> Result<Vec<Unit>, String> {
+    let mut units = vec![];
+
+    for user in [true, false] {
+        let output = systemctl(user).
+            args(["list-units", "--all", "--output=json"]).output().
+            map_err(|e| format!("Can't run systemctl: {}", e))?;
+
+        units.extend(parse_units(&String::from_utf8_lossy(&output.stdout),
+                                 user)?);
+    }
+
+    Ok(units)
+}
+
+// The command line for start, stop or restart. System units ask polkit
+// for permission themselves.
+pub fn command(unit: &Unit, verb: &str) -> String {
+    let user = if unit.user { " --user" } else { "" };
+    format!("systemctl{} {} {}", user, verb, shell_quote(&unit.unit))
+}
+
+pub fn status(unit: &Unit) -> String {
+    let output = systemctl(unit.user).
+        args(["status", "--no-pager", "--lines=10", &unit.unit]).output();
+
+    match output {
+        Ok (o) => String::from_utf8_lossy(&o.stdout).into_owned(),
+        Err(e) => format!("Can't run systemctl: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Unit, command, parse_units};
+
+    #[test]
+    fn it_parses_units() {
+        let json = r#"[{"unit": "syncthing.service", "load": "loaded",
+                        "active": "active", "sub": "running",
+                        "description": "Syncthing"}]"#;
+
+        let unit = Unit {
+            unit: "syncthing.service".to_string(),
+            active: "active".to_string(),
+            sub: "running".to_string(),
+            description: "Syncthing".to_string(),
+            user: true,
+        };
+
+        assert_eq!(parse_units(json, true), Ok(vec![unit.clone()]));
+        assert_eq!(command(&unit, "restart"),
+                   "systemctl --user restart 'syncthing.service'");
+        assert!(parse_units("nope", false).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use std::process::Command;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{self, Value};
+
+use config::Tools;
+use network;
+
+const FETCH_TIMEOUT_SECS: &str = "10";
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    // Seconds between checks
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+    // A GitHub "latest release" API endpoint
+    #[serde(default = "default_url")]
+    pub url: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["curl"]
+    }
+}
+
+fn default_interval() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_url() -> String {
+    "https://api.github.com/repos/briansteffens/blaunch2/releases/latest".
+        to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Release {
+    pub version: String,
+    // Page with the release's changelog
+    pub url: String,
+}
+
+// Reads the version and page of a release from the GitHub API, dropping
+// the "v" tags usually start with.
+pub fn parse_release(json: &str) -> Result<Release, String> {
+    let value: Value = serde_json::from_str(json).
+        map_err(|e| format!("Can't parse release: {}", e))?;
+
+    let field = |name: &str| value[name].as_str().map(|s| s.to_string()).
+        ok_or_else(|| format!("Release has no {}", name));
+
+    let tag = field("tag_name")?;
+
+    Ok(Release {
+        version: tag.trim_start_matches('v').to_string(),
+        url: field("html_url")?,
+    })
+}
+
+fn version_parts(version: &str) -> Vec<u64> {
+    version.split('.').map(|p| p.parse().unwrap_or(0)).collect()
+}
+
+// Compares dotted versions part by part, so 0.10 is newer than 0.9.
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    version_parts(latest) > version_parts(current)
+}
+
+fn fetch(url: &str) -> Result<Release, String> {
+    let output = Command::new("curl").
+        args(["-s", "-f", "-L", "--max-time", FETCH_TIMEOUT_SECS]).
+        arg(url).output().
+        map_err(|e| format!("Can't run curl: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("Can't fetch {}", url));
+    }
+
+    parse_release(&String::from_utf8_lossy(&output.stdout))
+}
+
+// Asks for the latest release on a worker thread every `interval` seconds.
+// The UI polls it and keeps whichever release is newer than the running
+// version.
+pub struct Checker {
+    config: Config,
+    current: String,
+    last: Option<Instant>,
+    available: Option<Release>,
+    monitor: network::Monitor,
+    sender: Sender<Result<Release, String>>,
+    receiver: Receiver<Result<Release, String>>,
+}
+
+impl Checker {
+    pub fn new(config: Config, current: &str) -> Checker {
+        let (sender, receiver) = channel();
+
+        Checker {
+            config,
+            current: current.to_string(),
+            last: None,
+            available: None,
+            monitor: network::Monitor::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn available(&self) -> Option<&Release> {
+        self.available.as_ref()
+    }
+
+    // Starts a check when one is due and picks up finished ones. Returns
+    // true when the available release changed.
+    pub fn poll(&mut self) -> bool {
+        let interval = Duration::from_secs(self.config.interval);
+
+        // While offline the check stays due, so it runs once we're back
+        if self.last.is_none_or(|l| l.elapsed() >= interval) &&
+           self.monitor.is_online() {
+            self.last = Some(Instant::now());
+
+            let url = self.config.url.clone();
+            let sender = self.sender.clone();
+
+            thread::spawn(move || {
+                let _ = sender.send(fetch(&url));
+            });
+        }
+
+        let mut changed = false;
+
+        // A failed check is dropped quietly; the next one is an interval away
+        for release in self.receiver.try_iter().filter_map(|r| r.ok()) {
+            if is_newer(&release.version, &self.current) &&
+               self.available.as_ref() != Some(&release) {
+                self.available = Some(release);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Release, is_newer, parse_release};
+
+    #[test]
+    fn it_parses_releases() {
+        let json = r#"{"tag_name": "v0.4.0",
+                       "html_url": "https://example.com/releases/v0.4.0"}"#;
+
+        assert_eq!(parse_release(json), Ok(Release {
+            version: "0.4.0".to_string(),
+            url: "https://example.com/releases/v0.4.0".to_string(),
+        }));
+        assert!(parse_release("{}").is_err());
+    }
+
+    #[test]
+    fn it_compares_versions() {
+        assert!(is_newer("0.10.0", "0.9.1"));
+        assert!(is_newer("0.4", "0.3.9"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("0.1", "0.2.0"));
+    }
+}
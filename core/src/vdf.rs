@@ -0,0 +1,156 @@
+// Valve's KeyValues text format, as used by Steam's .vdf and .acf files:
+// quoted keys followed by either a quoted value or a braced block of more
+// pairs, with // comments.
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Value {
+    Str(String),
+    Map(Vec<(String, Value)>),
+}
+
+impl Value {
+    // The value under a key, ignoring case like Steam does. The first
+    // match wins.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match *self {
+            Value::Map(ref pairs) => pairs.iter().
+                find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v),
+            Value::Str(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::Str(ref s) => Some(s),
+            Value::Map(_)     => None,
+        }
+    }
+
+    pub fn pairs(&self) -> &[(String, Value)] {
+        match *self {
+            Value::Map(ref pairs) => pairs,
+            Value::Str(_)         => &[],
+        }
+    }
+}
+
+enum Token {
+    Str(String),
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '"' => {
+                let mut s = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('"')  => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(e)   => s.push(e),
+                            None      => break,
+                        },
+                        Some(c)    => s.push(c),
+                        None       => return Err("Unterminated string".
+                                                 to_string()),
+                    };
+                }
+
+                tokens.push(Token::Str(s));
+            },
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next().is_some_and(|c| c != '\n') {}
+            },
+            c if c.is_whitespace() => {},
+            // Unquoted words, which a few hand-written files have
+            c => {
+                let mut s = c.to_string();
+
+                while let Some(&n) = chars.peek() {
+                    if n.is_whitespace() || n == '{' || n == '}' ||
+                       n == '"' {
+                        break;
+                    }
+
+                    s.push(n);
+                    chars.next();
+                }
+
+                tokens.push(Token::Str(s));
+            },
+        };
+    }
+
+    Ok(tokens)
+}
+
+fn parse_pairs<I>(tokens: &mut I, nested: bool)
+    -> Result<Vec<(String, Value)>, String>
+    where I: Iterator<Item = Token> {
+    let mut pairs = vec![];
+
+    loop {
+        let key = match tokens.next() {
+            Some(Token::Str(k)) => k,
+            Some(Token::Close) if nested => return Ok(pairs),
+            None if !nested => return Ok(pairs),
+            _ => return Err("Expected a key".to_string()),
+        };
+
+        let value = match tokens.next() {
+            Some(Token::Str(v)) => Value::Str(v),
+            Some(Token::Open)   => Value::Map(parse_pairs(tokens, true)?),
+            _ => return Err(format!("Expected a value for \"{}\"", key)),
+        };
+
+        pairs.push((key, value));
+    }
+}
+
+// The whole document, as a map of its top-level pairs.
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut tokens = tokenize(text)?.into_iter();
+    parse_pairs(&mut tokens, false).map(Value::Map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Value, parse};
+
+    #[test]
+    fn it_parses_nested_blocks() {
+        let doc = parse("// written by Steam\n\
+                         \"AppState\"\n\
+                         {\n\
+                         \t\"appid\"\t\t\"620\"\n\
+                         \t\"name\"\t\t\"Portal \\\"2\\\"\"\n\
+                         \t\"UserConfig\"\n\
+                         \t{\n\
+                         \t\t\"language\"\t\"english\"\n\
+                         \t}\n\
+                         }\n").unwrap();
+
+        let state = doc.get("appstate").unwrap();
+
+        assert_eq!(state.get("appid").and_then(Value::as_str), Some("620"));
+        assert_eq!(state.get("name").and_then(Value::as_str),
+                   Some("Portal \"2\""));
+        assert_eq!(state.get("UserConfig").unwrap().pairs().len(), 1);
+    }
+
+    #[test]
+    fn it_rejects_broken_documents() {
+        assert!(parse("\"a\" { \"b\" \"c\"").is_err());
+        assert!(parse("\"a\" }").is_err());
+    }
+}
@@ -0,0 +1,104 @@
+// libvirt virtual machines through virsh.
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+use node::on_path;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // Where the machines live; virt-manager's are on the system connection
+    #[serde(default = "default_uri")]
+    pub uri: String,
+    // For the serial console when virt-viewer isn't installed
+    #[serde(default = "default_terminal")]
+    pub terminal: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["virsh"]
+    }
+}
+
+fn default_prefix() -> String {
+    "vm".to_string()
+}
+
+fn default_uri() -> String {
+    "qemu:///system".to_string()
+}
+
+fn default_terminal() -> String {
+    "xterm".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Vm {
+    pub name: String,
+    // As virsh words it: "running", "shut off", "paused" and so on
+    pub state: String,
+}
+
+impl Vm {
+    pub fn running(&self) -> bool {
+        self.state == "running"
+    }
+}
+
+// The table from `virsh list --all`, under its two header lines.
+pub fn parse_list(output: &str) -> Vec<Vm> {
+    output.lines().skip(2).filter_map(|line| {
+        let mut words = line.split_whitespace().skip(1);
+        let name = words.next()?;
+
+        Some(Vm {
+            name: name.to_string(),
+            state: words.collect::<Vec<&str>>().join(" "),
+        })
+    }).collect()
+}
+
+pub fn list(config: &Config) -> Result<Vec<Vm>, String> {
+    cli::output("virsh", &["-c", &config.uri, "list", "--all"]).
+        map(|o| parse_list(&o))
+}
+
+// The command line for start or shutdown.
+pub fn command(config: &Config, vm: &Vm, verb: &str) -> String {
+    format!("virsh -c {} {} {}", shell_quote(&config.uri), verb,
+            shell_quote(&vm.name))
+}
+
+// The graphical console with virt-viewer, or else the serial console in a
+// terminal.
+pub fn console_command(config: &Config, vm: &Vm) -> String {
+    if on_path("virt-viewer") {
+        format!("virt-viewer --connect {} {}", shell_quote(&config.uri),
+                shell_quote(&vm.name))
+    } else {
+        cli::in_terminal(&config.terminal, &command(config, vm, "console"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Vm, parse_list};
+
+    #[test]
+    fn it_parses_the_list() {
+        let output = " Id   Name     State\n\
+                      -------------------------\n \
+                      3    win10    running\n \
+                      -    debian   shut off\n\
+                      \n";
+
+        assert_eq!(parse_list(output), vec![
+            Vm { name: "win10".to_string(), state: "running".to_string() },
+            Vm { name: "debian".to_string(), state: "shut off".to_string() },
+        ]);
+        assert!(parse_list(output)[0].running());
+    }
+}
@@ -0,0 +1,184 @@
+// VPN connections from NetworkManager, and WireGuard tunnels brought up
+// with wg-quick outside of it.
+
+use std::fs;
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+use wifi::{self, Connection};
+
+const WG_QUICK_DIR: &str = "/etc/wireguard";
+
+// NetworkManager connection types that are VPNs
+const VPN_TYPES: &[&str] = &["vpn", "wireguard"];
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // wg-quick tunnels by name, for when /etc/wireguard can't be listed
+    // without root
+    #[serde(default)]
+    pub wireguard: Vec<String>,
+    // Runs wg-quick as root
+    #[serde(default = "default_sudo")]
+    pub sudo: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["nmcli", "wg-quick"]
+    }
+}
+
+fn default_prefix() -> String {
+    "vpn".to_string()
+}
+
+fn default_sudo() -> String {
+    "pkexec".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Backend {
+    // With NetworkManager's connection type
+    NetworkManager(String),
+    WgQuick,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Vpn {
+    pub name: String,
+    pub backend: Backend,
+    pub active: bool,
+}
+
+impl Vpn {
+    pub fn kind(&self) -> &str {
+        match self.backend {
+            Backend::NetworkManager(ref k) if k == "vpn" => "vpn",
+            Backend::NetworkManager(_)                    => "wireguard",
+            Backend::WgQuick                              => "wg-quick",
+        }
+    }
+}
+
+// `ip -brief link show type wireguard` starts each line with the
+// interface's name.
+pub fn parse_interfaces(output: &str) -> Vec<String> {
+    output.lines().filter_map(|l| l.split_whitespace().next()).
+        map(|i| i.to_string()).collect()
+}
+
+// NetworkManager's VPN connections, then the wg-quick tunnels it doesn't
+// know about. A tunnel is up when its interface exists.
+pub fn merge(connections: Vec<Connection>, tunnels: Vec<String>,
+             interfaces: &[String]) -> Vec<Vpn> {
+    let mut vpns: Vec<Vpn> = connections.into_iter().
+        filter(|c| VPN_TYPES.contains(&c.kind.as_str())).
+        map(|c| Vpn {
+            name: c.name,
+            backend: Backend::NetworkManager(c.kind),
+            active: c.active,
+        }).collect();
+
+    for t in tunnels {
+        if !vpns.iter().any(|v| v.name == t) {
+            vpns.push(Vpn {
+                active: interfaces.contains(&t),
+                name: t,
+                backend: Backend::WgQuick,
+            });
+        }
+    }
+
+    vpns
+}
+
+fn tunnels(config: &Config) -> Vec<String> {
+    let mut tunnels = config.wireguard.clone();
+
+    let listed = fs::read_dir(WG_QUICK_DIR).into_iter().flatten().
+        filter_map(|e| e.ok()).
+        filter_map(|e| e.file_name().to_str()?.strip_suffix(".conf").
+            map(|n| n.to_string()));
+
+    for t in listed {
+        if !tunnels.contains(&t) {
+            tunnels.push(t);
+        }
+    }
+
+    tunnels
+}
+
+pub fn vpns(config: &Config) -> Vec<Vpn> {
+    // Without NetworkManager there can still be wg-quick tunnels
+    let connections = cli::output("nmcli", &[
+        "-t", "-f", "NAME,TYPE,DEVICE", "connection", "show"]).
+        map(|o| wifi::parse_connections(&o)).unwrap_or_default();
+
+    let interfaces = cli::output("ip", &[
+        "-brief", "link", "show", "type", "wireguard"]).
+        map(|o| parse_interfaces(&o)).unwrap_or_default();
+
+    merge(connections, tunnels(config), &interfaces)
+}
+
+pub fn toggle_command(config: &Config, vpn: &Vpn) -> String {
+    match vpn.backend {
+        Backend::NetworkManager(ref kind) => wifi::toggle_command(&Connection {
+            name: vpn.name.clone(),
+            kind: kind.clone(),
+            active: vpn.active,
+        }),
+        Backend::WgQuick => format!("{} wg-quick {} {}", config.sudo,
+                                    if vpn.active { "down" } else { "up" },
+                                    shell_quote(&vpn.name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wifi::Connection;
+    use super::{Backend, Vpn, merge, parse_interfaces};
+
+    fn connection(name: &str, kind: &str, active: bool) -> Connection {
+        Connection {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            active,
+        }
+    }
+
+    #[test]
+    fn it_merges_vpn_sources() {
+        let interfaces = parse_interfaces(
+            "wg1              UNKNOWN        <POINTOPOINT,NOARP,UP>\n");
+
+        let vpns = merge(vec![
+            connection("home", "802-3-ethernet", true),
+            connection("office", "vpn", true),
+            connection("wg0", "wireguard", false),
+        ], vec!["wg0".to_string(), "wg1".to_string()], &interfaces);
+
+        assert_eq!(vpns, vec![
+            Vpn {
+                name: "office".to_string(),
+                backend: Backend::NetworkManager("vpn".to_string()),
+                active: true,
+            },
+            Vpn {
+                name: "wg0".to_string(),
+                backend: Backend::NetworkManager("wireguard".to_string()),
+                active: false,
+            },
+            Vpn {
+                name: "wg1".to_string(),
+                backend: Backend::WgQuick,
+                active: true,
+            },
+        ]);
+    }
+}
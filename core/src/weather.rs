@@ -0,0 +1,266 @@
+// Current conditions and the next days' forecast from a wttr.in style JSON
+// endpoint, fetched on a worker thread.
+
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{self, Value};
+
+use config::Tools;
+use suggest::url_encode;
+
+// Wait this long after the last keystroke before fetching
+const DEBOUNCE_MS: u64 = 500;
+const FETCH_TIMEOUT_SECS: &str = "8";
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    // Where "weather" on its own is for; wttr.in guesses from the IP
+    // address when it's not set
+    #[serde(default)]
+    pub city: String,
+    // Returns wttr.in's "format=j1" JSON for {city}
+    #[serde(default = "default_url")]
+    pub url: String,
+    // Opened on Enter
+    #[serde(default = "default_page")]
+    pub page: String,
+    // Show Fahrenheit and mph instead of Celsius and km/h
+    #[serde(default)]
+    pub imperial: bool,
+    // How long a report is shown before it's fetched again
+    #[serde(default = "default_cache_minutes")]
+    pub cache_minutes: u64,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["curl"]
+    }
+}
+
+fn default_prefix() -> String {
+    "weather".to_string()
+}
+
+fn default_url() -> String {
+    "https://wttr.in/{city}?format=j1".to_string()
+}
+
+fn default_page() -> String {
+    "https://wttr.in/{city}".to_string()
+}
+
+fn default_cache_minutes() -> u64 {
+    10
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Day {
+    pub date: String,
+    pub low: String,
+    pub high: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Report {
+    pub place: String,
+    pub conditions: String,
+    pub temperature: String,
+    pub feels_like: String,
+    pub humidity: String,
+    pub wind: String,
+    pub wind_direction: String,
+    pub days: Vec<Day>,
+}
+
+// The first element's "value", the way wttr.in wraps names.
+fn value(v: &Value, key: &str) -> String {
+    v.get(key).and_then(|a| a.get(0)).and_then(|a| a.get("value")).
+        and_then(|a| a.as_str()).unwrap_or("").trim().to_string()
+}
+
+fn text(v: &Value, key: &str) -> String {
+    v.get(key).and_then(|t| t.as_str()).unwrap_or("").to_string()
+}
+
+pub fn parse_report(body: &str, imperial: bool)
+    -> Result<Report, String> {
+    let json: Value = serde_json::from_str(body).
+        map_err(|e| format!("Can't parse the weather report: {}", e))?;
+
+    let current = json.get("current_condition").and_then(|c| c.get(0)).
+        ok_or("No current conditions in the weather report")?;
+
+    let (temp, feels, wind, high, low) = if imperial {
+        ("temp_F", "FeelsLikeF", "windspeedMiles", "maxtempF", "mintempF")
+    } else {
+        ("temp_C", "FeelsLikeC", "windspeedKmph", "maxtempC", "mintempC")
+    };
+
+    let place = json.get("nearest_area").and_then(|a| a.get(0)).
+        map(|a| value(a, "areaName")).unwrap_or_default();
+
+    let days = json.get("weather").and_then(|w| w.as_array()).
+        map(|w| w.iter().map(|d| Day {
+            date: text(d, "date"),
+            low: text(d, low),
+            high: text(d, high),
+        }).collect()).unwrap_or_default();
+
+    Ok(Report {
+        place,
+        conditions: value(current, "weatherDesc"),
+        temperature: text(current, temp),
+        feels_like: text(current, feels),
+        humidity: text(current, "humidity"),
+        wind: text(current, wind),
+        wind_direction: text(current, "winddir16Point"),
+        days,
+    })
+}
+
+// An icon theme name for the conditions.
+pub fn icon(conditions: &str) -> &'static str {
+    let conditions = conditions.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| conditions.contains(w));
+
+    if has(&["thunder"]) {
+        "weather-storm"
+    } else if has(&["snow", "sleet", "ice", "blizzard"]) {
+        "weather-snow"
+    } else if has(&["rain", "drizzle", "shower"]) {
+        "weather-showers"
+    } else if has(&["fog", "mist", "haze"]) {
+        "weather-fog"
+    } else if has(&["partly"]) {
+        "weather-few-clouds"
+    } else if has(&["cloud", "overcast"]) {
+        "weather-overcast"
+    } else {
+        "weather-clear"
+    }
+}
+
+pub fn fill(template: &str, city: &str) -> String {
+    template.replace("{city}", &url_encode(city))
+}
+
+pub fn fetch(config: &Config, city: &str) -> Result<Report, String> {
+    let url = fill(&config.url, city);
+
+    let output = Command::new("curl").
+        args(["-s", "-f", "--max-time", FETCH_TIMEOUT_SECS, &url]).output().
+        map_err(|e| format!("Can't run curl: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("Can't fetch the weather for {}", city));
+    }
+
+    parse_report(&String::from_utf8_lossy(&output.stdout), config.imperial)
+}
+
+pub type Fetched = (String, Result<Report, String>);
+
+// Fetches reports on a worker thread, one city at a time. Like the
+// translator, a request only goes out if no newer one was made while it
+// waited, so typing a city name doesn't fetch every prefix of it.
+pub struct Forecaster {
+    config: Config,
+    generation: Arc<AtomicUsize>,
+    sender: Sender<Fetched>,
+    receiver: Receiver<Fetched>,
+}
+
+impl Forecaster {
+    pub fn new(config: Config) -> Forecaster {
+        let (sender, receiver) = channel();
+
+        Forecaster {
+            config,
+            generation: Arc::new(AtomicUsize::new(0)),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn request(&self, city: &str) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let config = self.config.clone();
+        let city = city.to_string();
+        let current = self.generation.clone();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(DEBOUNCE_MS));
+
+            if current.load(Ordering::SeqCst) != generation {
+                return;
+            }
+
+            let report = fetch(&config, &city);
+            let _ = sender.send((city, report));
+        });
+    }
+
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    // Reports that arrived, keyed by city.
+    pub fn poll(&self) -> Vec<Fetched> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Day, icon, parse_report};
+
+    const REPORT: &str = r#"{
+        "current_condition": [{
+            "temp_C": "12", "temp_F": "54",
+            "FeelsLikeC": "10", "FeelsLikeF": "50",
+            "humidity": "81",
+            "windspeedKmph": "15", "windspeedMiles": "9",
+            "winddir16Point": "SW",
+            "weatherDesc": [{"value": "Light rain shower"}]
+        }],
+        "nearest_area": [{"areaName": [{"value": "Berlin"}]}],
+        "weather": [
+            {"date": "2017-05-01", "maxtempC": "14", "mintempC": "8",
+             "maxtempF": "57", "mintempF": "46"}
+        ]
+    }"#;
+
+    #[test]
+    fn it_parses_weather_reports() {
+        let report = parse_report(REPORT, false).unwrap();
+
+        assert_eq!(report.place, "Berlin");
+        assert_eq!(report.conditions, "Light rain shower");
+        assert_eq!(report.temperature, "12");
+        assert_eq!(report.wind, "15");
+        assert_eq!(report.days, vec![Day {
+            date: "2017-05-01".to_string(),
+            low: "8".to_string(),
+            high: "14".to_string(),
+        }]);
+
+        assert_eq!(parse_report(REPORT, true).unwrap().temperature, "54");
+        assert!(parse_report("{}", false).is_err());
+    }
+
+    #[test]
+    fn it_picks_weather_icons() {
+        assert_eq!(icon("Light rain shower"), "weather-showers");
+        assert_eq!(icon("Partly cloudy"), "weather-few-clouds");
+        assert_eq!(icon("Sunny"), "weather-clear");
+    }
+}
@@ -0,0 +1,246 @@
+// Wi-Fi networks and saved connections from NetworkManager, through nmcli.
+// Scanning takes a few seconds, so it runs on a worker thread.
+
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use cli;
+use config::Tools;
+use desktop::shell_quote;
+
+// A scan is reused for this long before the next query starts another
+const RESCAN_SECS: u64 = 30;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["nmcli"]
+    }
+}
+
+fn default_prefix() -> String {
+    "wifi".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Network {
+    pub ssid: String,
+    pub signal: u32,
+    pub secured: bool,
+    pub active: bool,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Connection {
+    pub name: String,
+    // NetworkManager's type, like "802-11-wireless" or "vpn"
+    pub kind: String,
+    pub active: bool,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Scan {
+    pub networks: Vec<Network>,
+    pub connections: Vec<Connection>,
+}
+
+// nmcli's terse output separates fields with ':' and escapes ':' and '\'
+// inside them with a backslash.
+pub fn split_terse(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => if let Some(n) = chars.next() {
+                fields.last_mut().unwrap().push(n);
+            },
+            ':'  => fields.push(String::new()),
+            c    => fields.last_mut().unwrap().push(c),
+        };
+    }
+
+    fields
+}
+
+// `nmcli -t -f IN-USE,SSID,SIGNAL,SECURITY dev wifi list`. Hidden networks
+// are left out and each network is listed once, at its strongest.
+pub fn parse_networks(output: &str) -> Vec<Network> {
+    let mut networks: Vec<Network> = vec![];
+
+    for line in output.lines() {
+        let fields = split_terse(line);
+
+        if fields.len() < 4 || fields[1].is_empty() {
+            continue;
+        }
+
+        let network = Network {
+            ssid: fields[1].clone(),
+            signal: fields[2].parse().unwrap_or(0),
+            secured: !fields[3].is_empty() && fields[3] != "--",
+            active: fields[0] == "*",
+        };
+
+        match networks.iter_mut().find(|n| n.ssid == network.ssid) {
+            Some(n) => {
+                n.active |= network.active;
+                n.signal = n.signal.max(network.signal);
+            },
+            None => networks.push(network),
+        };
+    }
+
+    networks.sort_by_key(|n| (!n.active, u32::MAX - n.signal));
+    networks
+}
+
+// `nmcli -t -f NAME,TYPE,DEVICE connection show`. Active connections have
+// a device.
+pub fn parse_connections(output: &str) -> Vec<Connection> {
+    output.lines().map(split_terse).filter(|f| f.len() >= 3).
+        map(|f| Connection {
+            name: f[0].clone(),
+            kind: f[1].clone(),
+            active: !f[2].is_empty(),
+        }).collect()
+}
+
+pub fn scan() -> Result<Scan, String> {
+    Ok(Scan {
+        networks: parse_networks(&cli::output("nmcli", &[
+            "-t", "-f", "IN-USE,SSID,SIGNAL,SECURITY",
+            "device", "wifi", "list", "--rescan", "yes"])?),
+        connections: parse_connections(&cli::output("nmcli", &[
+            "-t", "-f", "NAME,TYPE,DEVICE", "connection", "show"])?),
+    })
+}
+
+// Joining a network NetworkManager already has a connection for brings
+// that up, with its saved secrets; otherwise nmcli asks for a password
+// through the desktop's secret agent.
+pub fn connect_command(ssid: &str, saved: bool) -> String {
+    if saved {
+        format!("nmcli connection up id {}", shell_quote(ssid))
+    } else {
+        format!("nmcli device wifi connect {}", shell_quote(ssid))
+    }
+}
+
+pub fn disconnect_command(ssid: &str) -> String {
+    format!("nmcli connection down id {}", shell_quote(ssid))
+}
+
+pub fn toggle_command(connection: &Connection) -> String {
+    let verb = if connection.active { "down" } else { "up" };
+    format!("nmcli connection {} id {}", verb, shell_quote(&connection.name))
+}
+
+// Runs scans in the background. The UI polls it like the other background
+// work and shows a spinner until the first scan is in.
+pub struct Scanner {
+    scan: Option<Result<Scan, String>>,
+    scanned: Option<Instant>,
+    scanning: bool,
+    sender: Sender<Result<Scan, String>>,
+    receiver: Receiver<Result<Scan, String>>,
+}
+
+impl Default for Scanner {
+    fn default() -> Scanner {
+        Scanner::new()
+    }
+}
+
+impl Scanner {
+    pub fn new() -> Scanner {
+        let (sender, receiver) = channel();
+
+        Scanner {
+            scan: None,
+            scanned: None,
+            scanning: false,
+            sender,
+            receiver,
+        }
+    }
+
+    // The latest scan, starting a new one if it's getting old.
+    pub fn scan(&mut self) -> Option<&Result<Scan, String>> {
+        let stale = self.scanned.is_none_or(|at| {
+            at.elapsed() > Duration::from_secs(RESCAN_SECS)
+        });
+
+        if stale && !self.scanning {
+            self.scanning = true;
+            let sender = self.sender.clone();
+
+            thread::spawn(move || {
+                let _ = sender.send(scan());
+            });
+        }
+
+        self.scan.as_ref()
+    }
+
+    pub fn scanning(&self) -> bool {
+        self.scanning
+    }
+
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+
+        for scan in self.receiver.try_iter() {
+            self.scan = Some(scan);
+            self.scanned = Some(Instant::now());
+            self.scanning = false;
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Network, parse_connections, parse_networks, split_terse};
+
+    #[test]
+    fn it_splits_terse_output() {
+        assert_eq!(split_terse("*:my\\:net:70:WPA2"),
+                   vec!["*", "my:net", "70", "WPA2"]);
+    }
+
+    #[test]
+    fn it_parses_networks() {
+        let output = " :cafe:40:\n\
+                      *:home:70:WPA2\n \
+                      :home:85:WPA2\n \
+                      ::90:WPA2\n";
+
+        assert_eq!(parse_networks(output), vec![
+            Network {
+                ssid: "home".to_string(),
+                signal: 85,
+                secured: true,
+                active: true,
+            },
+            Network {
+                ssid: "cafe".to_string(),
+                signal: 40,
+                secured: false,
+                active: false,
+            },
+        ]);
+
+        let connections = parse_connections(
+            "home:802-11-wireless:wlan0\nwork:vpn:\n");
+        assert!(connections[0].active && !connections[1].active);
+    }
+}
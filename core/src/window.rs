@@ -0,0 +1,148 @@
+// How the launcher window identifies itself, so tiling window manager rules
+// can match it. With the defaults:
+//
+//   sway:  for_window [app_id="blaunch"] floating enable
+//   i3:    for_window [class="Blaunch"] floating enable
+//   bspwm: bspc rule -a Blaunch state=floating
+//
+// The role is also matchable in i3 ([window_role="launcher"]).
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    // Program name, used as the Wayland app_id and the WM_CLASS instance
+    #[serde(default = "default_name")]
+    pub name: String,
+    // WM_CLASS class
+    #[serde(default = "default_class")]
+    pub class: String,
+    #[serde(default = "default_role")]
+    pub role: String,
+    // Mark the window as a dialog, which most tiling WMs float by default
+    #[serde(default = "default_true")]
+    pub floating: bool,
+    // Launcher popup hints: no title bar, kept out of taskbars and pagers
+    // and above other windows
+    #[serde(default)]
+    pub decorated: bool,
+    #[serde(default = "default_true")]
+    pub skip_taskbar: bool,
+    #[serde(default = "default_true")]
+    pub skip_pager: bool,
+    #[serde(default = "default_true")]
+    pub keep_above: bool,
+    // Which monitor to center the window on: "pointer", "focus" (the one
+    // with the focused window) or a monitor index like "1"
+    #[serde(default = "default_monitor")]
+    pub monitor: String,
+    // Pixels to shift the window from the center of the monitor
+    #[serde(default)]
+    pub offset_x: i32,
+    #[serde(default)]
+    pub offset_y: i32,
+    // "window", "top-bar"/"bottom-bar" for a dmenu-style bar across the
+    // monitor with the results in a row, or "overlay" for a translucent
+    // fullscreen overlay with a large entry in the middle
+    #[serde(default = "default_layout")]
+    pub layout: String,
+    // Use the layer-shell overlay layer on wlroots compositors, when built
+    // with the layer-shell feature
+    #[serde(default = "default_true")]
+    pub layer_shell: bool,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Layout {
+    Window,
+    TopBar,
+    BottomBar,
+    Overlay,
+}
+
+pub fn parse_layout(layout: &str) -> Result<Layout, String> {
+    match layout {
+        "window"     => Ok(Layout::Window),
+        "top-bar"    => Ok(Layout::TopBar),
+        "bottom-bar" => Ok(Layout::BottomBar),
+        "overlay"    => Ok(Layout::Overlay),
+        l            => Err(format!("Unknown layout {}", l)),
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Placement {
+    Pointer,
+    Focus,
+    Monitor(i32),
+}
+
+pub fn parse_placement(monitor: &str) -> Result<Placement, String> {
+    match monitor {
+        "pointer" => Ok(Placement::Pointer),
+        "focus"   => Ok(Placement::Focus),
+        m         => m.parse().map(Placement::Monitor).
+            map_err(|_| format!("Unknown monitor {}", m)),
+    }
+}
+
+fn default_name() -> String {
+    "blaunch".to_string()
+}
+
+fn default_class() -> String {
+    "Blaunch".to_string()
+}
+
+fn default_role() -> String {
+    "launcher".to_string()
+}
+
+fn default_monitor() -> String {
+    "pointer".to_string()
+}
+
+fn default_layout() -> String {
+    "window".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            name: default_name(),
+            class: default_class(),
+            role: default_role(),
+            floating: true,
+            decorated: false,
+            skip_taskbar: true,
+            skip_pager: true,
+            keep_above: true,
+            monitor: default_monitor(),
+            offset_x: 0,
+            offset_y: 0,
+            layout: default_layout(),
+            layer_shell: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Layout, Placement, parse_layout, parse_placement};
+
+    #[test]
+    fn it_parses_placements() {
+        assert_eq!(parse_placement("pointer"), Ok(Placement::Pointer));
+        assert_eq!(parse_placement("focus"), Ok(Placement::Focus));
+        assert_eq!(parse_placement("1"), Ok(Placement::Monitor(1)));
+        assert!(parse_placement("left").is_err());
+    }
+
+    #[test]
+    fn it_parses_layouts() {
+        assert_eq!(parse_layout("window"), Ok(Layout::Window));
+        assert_eq!(parse_layout("bottom-bar"), Ok(Layout::BottomBar));
+        assert!(parse_layout("sidebar").is_err());
+    }
+}
@@ -0,0 +1,107 @@
+// Open windows on X11, read from the window manager's EWMH client list
+// through wmctrl, which also does the activating.
+
+use std::process::Command;
+
+use config::Tools;
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    // Window titles and classes are listed after this, e.g. "w term"
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+}
+
+impl Tools for Config {
+    fn tools(&self) -> Vec<&str> {
+        vec!["wmctrl"]
+    }
+}
+
+fn default_prefix() -> String {
+    "w".to_string()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Window {
+    // X window id in hex, as wmctrl prints it
+    pub id: String,
+    // WM_CLASS instance name, like "firefox"
+    pub class: String,
+    pub title: String,
+}
+
+// `wmctrl -lx` lines look like "0x03a00003  0 firefox.Firefox  host  Title",
+// the title being the rest of the line and possibly empty.
+pub fn parse_wmctrl(output: &str) -> Vec<Window> {
+    output.lines().filter_map(|line| {
+        let mut rest = line;
+        let mut fields = vec![];
+
+        // id, desktop, class and host, leaving the title in `rest`
+        for _ in 0..4 {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            fields.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+
+        if fields.iter().any(|f| f.is_empty()) {
+            return None;
+        }
+
+        Some(Window {
+            id: fields[0].to_string(),
+            class: fields[2].split('.').next().unwrap_or("").to_string(),
+            title: rest.trim().to_string(),
+        })
+    }).collect()
+}
+
+pub fn list() -> Result<Vec<Window>, String> {
+    let output = Command::new("wmctrl").arg("-lx").output().
+        map_err(|e| format!("Can't run wmctrl: {}", e))?;
+
+    if !output.status.success() {
+        return Err("wmctrl can't list windows".to_string());
+    }
+
+    Ok(parse_wmctrl(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// Switches to the window's desktop, raises and focuses it.
+pub fn activate(id: &str) -> Result<(), String> {
+    let status = Command::new("wmctrl").args(["-i", "-a", id]).status().
+        map_err(|e| format!("Can't run wmctrl: {}", e))?;
+
+    if !status.success() {
+        return Err(format!("wmctrl can't activate window {}", id));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Window, parse_wmctrl};
+
+    #[test]
+    fn it_parses_wmctrl_output() {
+        let output = "0x03a00003  0 firefox.Firefox  box Rust - Mozilla\n\
+                      0x01e00006 -1 xfce4-panel.Xfce4-panel  box \n\
+                      garbage\n";
+
+        assert_eq!(parse_wmctrl(output), vec![
+            Window {
+                id: "0x03a00003".to_string(),
+                class: "firefox".to_string(),
+                title: "Rust - Mozilla".to_string(),
+            },
+            Window {
+                id: "0x01e00006".to_string(),
+                class: "xfce4-panel".to_string(),
+                title: "".to_string(),
+            },
+        ]);
+    }
+}
@@ -0,0 +1,165 @@
+// Exports org.blaunch.Launcher on the session bus so window managers and
+// scripts can drive a running daemon. The bindings of this gtk version don't
+// cover GDBus, so this talks to gio directly. Everything runs on the GTK main
+// loop, so the handler is called on the UI thread like any other callback.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use gio_sys as gio;
+use glib_sys as glib;
+
+use blaunch_core::daemon::Request;
+
+pub const NAME: &str = "org.blaunch.Launcher";
+const PATH: &str = "/org/blaunch/Launcher";
+
+const INTROSPECTION: &str = "<node>
+  <interface name='org.blaunch.Launcher'>
+    <method name='Show'/>
+    <method name='ShowWithQuery'>
+      <arg type='s' name='query' direction='in'/>
+    </method>
+    <method name='ShowOnOutput'>
+      <arg type='s' name='output' direction='in'/>
+    </method>
+    <method name='Hide'/>
+    <method name='Reload'/>
+    <method name='ReloadTheme'/>
+  </interface>
+</node>";
+
+type Handler = std::boxed::Box<dyn Fn(Request)>;
+
+struct Service {
+    interface: *mut gio::GDBusInterfaceInfo,
+    vtable: gio::GDBusInterfaceVTable,
+    handler: Handler,
+}
+
+fn string_argument(parameters: *mut glib::GVariant) -> Option<String> {
+    unsafe {
+        if parameters.is_null() || glib::g_variant_n_children(parameters) < 1 {
+            return None;
+        }
+
+        let child = glib::g_variant_get_child_value(parameters, 0);
+        let value = CStr::from_ptr(glib::g_variant_get_string(
+            child, ptr::null_mut())).to_string_lossy().into_owned();
+        glib::g_variant_unref(child);
+
+        Some(value)
+    }
+}
+
+fn request(method: &CStr, parameters: *mut glib::GVariant) -> Option<Request> {
+    match method.to_str() {
+        Ok ("Show")          => Some(Request::Show {
+            output: None,
+            query: None,
+        }),
+        Ok ("ShowWithQuery") => Some(Request::Show {
+            output: None,
+            query: string_argument(parameters),
+        }),
+        Ok ("ShowOnOutput")  => Some(Request::Show {
+            output: string_argument(parameters),
+            query: None,
+        }),
+        Ok ("Hide")          => Some(Request::Hide { output: None }),
+        Ok ("Reload")        => Some(Request::Reload),
+        Ok ("ReloadTheme")   => Some(Request::ReloadTheme(None)),
+        _                    => None,
+    }
+}
+
+unsafe extern "C" fn method_call(_: *mut gio::GDBusConnection,
+                                 _: *const c_char, _: *const c_char,
+                                 _: *const c_char, method: *const c_char,
+                                 parameters: *mut glib::GVariant,
+                                 invocation: *mut gio::GDBusMethodInvocation,
+                                 user_data: glib::gpointer) {
+    let service = &*(user_data as *const Service);
+    let method = CStr::from_ptr(method);
+
+    // A panic can't unwind into gio, so one in the handler becomes an error
+    // reply instead
+    let handled = panic::catch_unwind(AssertUnwindSafe(|| {
+        request(method, parameters).map(|r| (service.handler)(r)).is_some()
+    }));
+
+    let (error, message) = match handled {
+        Ok (true)  => {
+            gio::g_dbus_method_invocation_return_value(invocation,
+                                                       ptr::null_mut());
+            return;
+        },
+        Ok (false) => ("org.freedesktop.DBus.Error.UnknownMethod",
+                       "Unknown method"),
+        Err(_)     => ("org.freedesktop.DBus.Error.Failed",
+                       "blaunch failed to handle the call"),
+    };
+
+    let error = CString::new(error).unwrap();
+    let message = CString::new(message).unwrap();
+    gio::g_dbus_method_invocation_return_dbus_error(
+        invocation, error.as_ptr(), message.as_ptr());
+}
+
+unsafe extern "C" fn bus_acquired(connection: *mut gio::GDBusConnection,
+                                  _: *const c_char,
+                                  user_data: glib::gpointer) {
+    let service = &*(user_data as *const Service);
+    let path = CString::new(PATH).unwrap();
+
+    let id = gio::g_dbus_connection_register_object(
+        connection, path.as_ptr(), service.interface, &service.vtable,
+        user_data, None, ptr::null_mut());
+
+    if id == 0 {
+        eprintln!("Can't export {} on D-Bus", PATH);
+    }
+}
+
+unsafe extern "C" fn name_lost(_: *mut gio::GDBusConnection,
+                               _: *const c_char, _: glib::gpointer) {
+    eprintln!("Can't own {} on the session bus; is another blaunch running?",
+              NAME);
+}
+
+// Claims the bus name and routes method calls to `handler` for the rest of
+// the process's life.
+pub fn serve<F>(handler: F) where F: Fn(Request) + 'static {
+    let xml = CString::new(INTROSPECTION).unwrap();
+    let name = CString::new(NAME).unwrap();
+    let interface = CString::new(NAME).unwrap();
+
+    unsafe {
+        let info = gio::g_dbus_node_info_new_for_xml(xml.as_ptr(),
+                                                     ptr::null_mut());
+        if info.is_null() {
+            panic!("Bad D-Bus introspection data");
+        }
+
+        // Lives as long as the process, like the registration itself
+        let service = std::boxed::Box::into_raw(std::boxed::Box::new(
+            Service {
+                interface: gio::g_dbus_node_info_lookup_interface(
+                    info, interface.as_ptr()),
+                vtable: gio::GDBusInterfaceVTable {
+                    method_call: Some(method_call),
+                    get_property: None,
+                    set_property: None,
+                    padding: [ptr::null_mut(); 8],
+                },
+                handler: std::boxed::Box::new(handler),
+            }));
+
+        gio::g_bus_own_name(gio::G_BUS_TYPE_SESSION, name.as_ptr(),
+                            gio::G_BUS_NAME_OWNER_FLAGS_NONE,
+                            Some(bus_acquired), None, Some(name_lost),
+                            service as glib::gpointer, None);
+    }
+}
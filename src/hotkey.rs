@@ -0,0 +1,137 @@
+// Grabs a global key chord on X11 so the daemon can toggle itself without
+// any window manager config. The grab runs on its own thread with its own
+// display connection and reports presses over a channel that the frontend
+// polls along with the socket. Wayland has no equivalent; bind
+// `blaunch --toggle` in the compositor instead.
+
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_long, c_uint, c_ulong};
+use std::ptr;
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+
+use blaunch_core::hotkey::{self, Modifier};
+
+enum Display {}
+
+#[repr(C)]
+struct XEvent {
+    kind: c_int,
+    pad: [c_long; 24],
+}
+
+#[repr(C)]
+struct XErrorEvent {
+    kind: c_int,
+    display: *mut Display,
+    resource_id: c_ulong,
+    serial: c_ulong,
+    error_code: u8,
+    request_code: u8,
+    minor_code: u8,
+}
+
+type ErrorHandler = Option<unsafe extern "C" fn(*mut Display,
+                                                *mut XErrorEvent) -> c_int>;
+
+#[link(name = "X11")]
+extern "C" {
+    fn XOpenDisplay(name: *const c_char) -> *mut Display;
+    fn XDefaultRootWindow(display: *mut Display) -> c_ulong;
+    fn XStringToKeysym(name: *const c_char) -> c_ulong;
+    fn XKeysymToKeycode(display: *mut Display, keysym: c_ulong) -> u8;
+    fn XGrabKey(display: *mut Display, keycode: c_int, modifiers: c_uint,
+                window: c_ulong, owner_events: c_int, pointer_mode: c_int,
+                keyboard_mode: c_int) -> c_int;
+    fn XSelectInput(display: *mut Display, window: c_ulong, mask: c_long)
+        -> c_int;
+    fn XNextEvent(display: *mut Display, event: *mut XEvent) -> c_int;
+    fn XSync(display: *mut Display, discard: c_int) -> c_int;
+    fn XSetErrorHandler(handler: ErrorHandler) -> ErrorHandler;
+}
+
+const KEY_PRESS: c_int = 2;
+const KEY_PRESS_MASK: c_long = 1;
+const GRAB_MODE_ASYNC: c_int = 1;
+
+const SHIFT_MASK  : c_uint = 1;
+const LOCK_MASK   : c_uint = 1 << 1;
+const CONTROL_MASK: c_uint = 1 << 2;
+const MOD1_MASK   : c_uint = 1 << 3;
+const MOD2_MASK   : c_uint = 1 << 4;
+const MOD4_MASK   : c_uint = 1 << 6;
+
+// The default handler exits the process, which is a harsh way to find out
+// another program already grabbed the chord.
+unsafe extern "C" fn on_error(_: *mut Display, e: *mut XErrorEvent) -> c_int {
+    eprintln!("Can't grab hotkey (X error {}); is it bound elsewhere?",
+              (*e).error_code);
+    0
+}
+
+fn mask(modifiers: &[Modifier]) -> c_uint {
+    modifiers.iter().fold(0, |m, modifier| m | match *modifier {
+        Modifier::Shift   => SHIFT_MASK,
+        Modifier::Control => CONTROL_MASK,
+        Modifier::Alt     => MOD1_MASK,
+        Modifier::Super   => MOD4_MASK,
+    })
+}
+
+// Starts listening for the chord. Every press sends a unit down the channel.
+pub fn grab(chord: &str) -> Result<Receiver<()>, String> {
+    let parsed = hotkey::parse(chord)?;
+    let keysym_name = CString::new(parsed.key.clone()).
+        map_err(|e| e.to_string())?;
+    let modifiers = mask(&parsed.modifiers);
+
+    let (sender, receiver) = channel();
+    let (ready_sender, ready) = channel();
+
+    thread::spawn(move || unsafe {
+        let display = XOpenDisplay(ptr::null());
+        if display.is_null() {
+            let _ = ready_sender.send(Err("Can't open X display".to_string()));
+            return;
+        }
+
+        let keysym = XStringToKeysym(keysym_name.as_ptr());
+        let keycode = XKeysymToKeycode(display, keysym);
+        if keysym == 0 || keycode == 0 {
+            let _ = ready_sender.send(Err(format!("Unknown key {}",
+                                                  parsed.key)));
+            return;
+        }
+
+        XSetErrorHandler(Some(on_error));
+
+        let root = XDefaultRootWindow(display);
+
+        // Grab with every combination of Caps Lock and Num Lock too, or the
+        // chord stops working whenever either is on
+        for extra in &[0, LOCK_MASK, MOD2_MASK, LOCK_MASK | MOD2_MASK] {
+            XGrabKey(display, keycode as c_int, modifiers | extra, root, 0,
+                     GRAB_MODE_ASYNC, GRAB_MODE_ASYNC);
+        }
+
+        XSelectInput(display, root, KEY_PRESS_MASK);
+        XSync(display, 0);
+
+        let _ = ready_sender.send(Ok(()));
+
+        let mut event = XEvent { kind: 0, pad: [0; 24] };
+        loop {
+            XNextEvent(display, &mut event);
+
+            if event.kind == KEY_PRESS && sender.send(()).is_err() {
+                return;
+            }
+        }
+    });
+
+    match ready.recv() {
+        Ok (Ok (_)) => Ok(receiver),
+        Ok (Err(e)) => Err(e),
+        Err(_)      => Err("Hotkey thread died".to_string()),
+    }
+}
@@ -0,0 +1,108 @@
+// The query box. Normally a one-line Entry; with multiline_entry set in the
+// config it's a wrapping TextView instead, so long shell commands can be
+// spread over lines (the newline key, Shift+Enter by default) and read over
+// before running them. The results area shrinks as it grows.
+
+use gdk::EventKey;
+use gtk::prelude::*;
+use gtk::{Entry, Inhibit, TextView, Widget, WrapMode};
+
+#[derive(Clone)]
+pub enum Input {
+    Line(Entry),
+    Lines(TextView),
+}
+
+impl Input {
+    pub fn new(multiline: bool) -> Input {
+        if !multiline {
+            return Input::Line(Entry::new());
+        }
+
+        let view = TextView::new();
+        view.set_wrap_mode(WrapMode::WordChar);
+        view.set_accepts_tab(false);
+        Input::Lines(view)
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        match *self {
+            Input::Line(_)  => false,
+            Input::Lines(_) => true,
+        }
+    }
+
+    pub fn widget(&self) -> Widget {
+        match *self {
+            Input::Line(ref e)  => e.clone().upcast(),
+            Input::Lines(ref v) => v.clone().upcast(),
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        match *self {
+            Input::Line(ref e)  => e.get_text(),
+            Input::Lines(ref v) => v.get_buffer().and_then(|b| {
+                b.get_text(&b.get_start_iter(), &b.get_end_iter(), false)
+            }),
+        }.unwrap_or_default()
+    }
+
+    pub fn set_text(&self, text: &str) {
+        match *self {
+            Input::Line(ref e)  => e.set_text(text),
+            Input::Lines(ref v) => if let Some(b) = v.get_buffer() {
+                b.set_text(text);
+            },
+        };
+    }
+
+    // Cursor position in characters
+    pub fn cursor(&self) -> usize {
+        let at = match *self {
+            Input::Line(ref e)  => e.get_position(),
+            Input::Lines(ref v) => v.get_buffer().
+                map_or(0, |b| b.get_property_cursor_position()),
+        };
+
+        at.max(0) as usize
+    }
+
+    pub fn set_cursor(&self, at: usize) {
+        match *self {
+            Input::Line(ref e)  => e.set_position(at as i32),
+            Input::Lines(ref v) => if let Some(b) = v.get_buffer() {
+                b.place_cursor(&b.get_iter_at_offset(at as i32));
+            },
+        };
+    }
+
+    pub fn move_to_end(&self) {
+        match *self {
+            Input::Line(ref e)  => e.set_position(-1),
+            Input::Lines(ref v) => if let Some(b) = v.get_buffer() {
+                b.place_cursor(&b.get_end_iter());
+            },
+        };
+    }
+
+    pub fn grab_focus(&self) {
+        self.widget().grab_focus();
+    }
+
+    pub fn connect_changed<F>(&self, f: F) where F: Fn() + 'static {
+        match *self {
+            Input::Line(ref e)  => {
+                e.connect_changed(move |_| f());
+            },
+            Input::Lines(ref v) => if let Some(b) = v.get_buffer() {
+                b.connect_changed(move |_| f());
+            },
+        };
+    }
+
+    pub fn connect_key_press_event<F>(&self, f: F)
+        where F: Fn(&EventKey) -> Inhibit + 'static {
+        self.widget().connect_key_press_event(move |_, e| f(e));
+    }
+}
@@ -0,0 +1,71 @@
+// Puts the launcher on the wlr layer-shell overlay layer, through the
+// gtk-layer-shell library, so wlroots compositors show it above everything
+// with keyboard focus instead of tiling it like a normal window. On X11, or
+// a compositor without the protocol, nothing changes.
+
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+
+use blaunch_core::window::Layout;
+use glib::translate::ToGlibPtr;
+use gtk::Window;
+use gtk_sys::GtkWindow;
+
+const LAYER_OVERLAY: c_int = 3;
+
+const EDGE_LEFT  : c_int = 0;
+const EDGE_RIGHT : c_int = 1;
+const EDGE_TOP   : c_int = 2;
+const EDGE_BOTTOM: c_int = 3;
+
+#[link(name = "gtk-layer-shell")]
+extern "C" {
+    fn gtk_layer_is_supported() -> c_int;
+    fn gtk_layer_init_for_window(window: *mut GtkWindow);
+    fn gtk_layer_set_namespace(window: *mut GtkWindow,
+                               name_space: *const c_char);
+    fn gtk_layer_set_layer(window: *mut GtkWindow, layer: c_int);
+    fn gtk_layer_set_keyboard_interactivity(window: *mut GtkWindow,
+                                            interactivity: c_int);
+    fn gtk_layer_set_anchor(window: *mut GtkWindow, edge: c_int,
+                            anchor_to_edge: c_int);
+}
+
+// Edges the window sticks to: none centers it, a bar spans one edge and the
+// overlay fills the output.
+fn anchors(layout: Layout) -> &'static [c_int] {
+    match layout {
+        Layout::Window    => &[],
+        Layout::TopBar    => &[EDGE_TOP, EDGE_LEFT, EDGE_RIGHT],
+        Layout::BottomBar => &[EDGE_BOTTOM, EDGE_LEFT, EDGE_RIGHT],
+        Layout::Overlay   => &[EDGE_TOP, EDGE_BOTTOM, EDGE_LEFT, EDGE_RIGHT],
+    }
+}
+
+// Has to run before the window is first shown. Returns false when layer
+// shell isn't available, leaving the window to the normal placement.
+pub fn init(window: &Window, name: &str, layout: Layout) -> bool {
+    let name = match CString::new(name) {
+        Ok (n) => n,
+        Err(_) => return false,
+    };
+
+    unsafe {
+        if gtk_layer_is_supported() == 0 {
+            return false;
+        }
+
+        let window: *mut GtkWindow = window.to_glib_none().0;
+
+        gtk_layer_init_for_window(window);
+        gtk_layer_set_namespace(window, name.as_ptr());
+        gtk_layer_set_layer(window, LAYER_OVERLAY);
+        gtk_layer_set_keyboard_interactivity(window, 1);
+
+        for edge in anchors(layout) {
+            gtk_layer_set_anchor(window, *edge, 1);
+        }
+    }
+
+    true
+}
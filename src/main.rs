@@ -1,114 +1,374 @@
+extern crate blaunch_core;
+extern crate cairo;
+extern crate gdk;
+extern crate gdk_pixbuf;
+extern crate gio_sys;
+extern crate glib;
+extern crate glib_sys;
 extern crate gtk;
-extern crate serde;
-extern crate serde_json;
+#[cfg(feature = "layer-shell")]
+extern crate gtk_sys;
+
+mod dbus;
+#[cfg(feature = "x11-hotkey")]
+mod hotkey;
+#[cfg(feature = "layer-shell")]
+mod layer_shell;
+mod input;
+
+use std::env;
+use std::io;
+use std::process::{self, Command};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use blaunch_core::{args, builtins, clipboard, cnf, daemon, doctor,
+                   dynamic, editing, fallback, headless, history, jobs,
+                   macros, node, notes, pins, print, quick, snapshot, theme,
+                   timers, window, windows};
+use blaunch_core::config::{self, Config};
+use blaunch_core::desktop::shell_quote;
+use blaunch_core::fallback::Fallback;
+use blaunch_core::hotkey::Modifier;
+use blaunch_core::keys::{self, Key};
+use blaunch_core::provider::{self, Action, Item, Registry};
+use blaunch_core::providers::apps::Apps;
+use blaunch_core::providers::audio::Audio;
+use blaunch_core::providers::bluetooth::Bluetooth;
+use blaunch_core::providers::bookmarks::Bookmarks;
+use blaunch_core::providers::calc::Calc;
+use blaunch_core::providers::clipboard::Clips;
+use blaunch_core::providers::color::Color;
+use blaunch_core::providers::containers::Containers;
+use blaunch_core::providers::convert::Convert;
+use blaunch_core::providers::dictionary::Dictionary;
+use blaunch_core::providers::displays::Displays;
+use blaunch_core::providers::drives::Drives;
+use blaunch_core::providers::emoji::Emoji;
+use blaunch_core::providers::files::Files;
+use blaunch_core::providers::keyboard::Keyboard;
+use blaunch_core::providers::kube::Kube;
+use blaunch_core::providers::macros::Macros;
+use blaunch_core::providers::man::Man;
+use blaunch_core::providers::menu::Menu;
+use blaunch_core::providers::notice::Notice;
+use blaunch_core::providers::pass::Pass;
+use blaunch_core::providers::pinned::Pinned;
+use blaunch_core::providers::power::Power;
+use blaunch_core::providers::processes::Processes;
+use blaunch_core::providers::projects::Projects;
+use blaunch_core::providers::qr::Qr;
+use blaunch_core::providers::recent::Recent;
+use blaunch_core::providers::recent_files::RecentFiles;
+use blaunch_core::providers::repos::Repos;
+use blaunch_core::providers::run::Run;
+use blaunch_core::providers::search::Search;
+use blaunch_core::providers::snippets::Snippets;
+use blaunch_core::providers::ssh::Ssh;
+use blaunch_core::providers::stats::Stats;
+use blaunch_core::providers::steam::Steam;
+use blaunch_core::providers::timers::Timers;
+use blaunch_core::providers::tmux::Tmux;
+use blaunch_core::providers::todo::Todo;
+use blaunch_core::providers::translate::Translate;
+use blaunch_core::providers::trash::Trash;
+use blaunch_core::providers::units::Units;
+use blaunch_core::providers::update::Update;
+use blaunch_core::providers::vms::Vms;
+use blaunch_core::providers::vpn::Vpns;
+use blaunch_core::providers::wallpapers::Wallpapers;
+use blaunch_core::providers::weather::Weather;
+use blaunch_core::providers::wifi::Wifi;
+use blaunch_core::providers::windows::Windows;
+use gtk::prelude::*;
+use gtk::{Label, Window, ScrolledWindow, WindowType, Box, Orientation,
+          Clipboard, Spinner, Image, CssProvider, StyleContext, EventBox,
+          PolicyType};
+use gdk_pixbuf::{Colorspace, Pixbuf};
+use input::Input;
 
-#[macro_use]
-extern crate serde_derive;
+const KEY_1: u32 = 49;
+const KEY_9: u32 = 57;
 
-use std::process::Command;
-use std::fs::File;
-use gtk::prelude::*;
-use gtk::{Entry, Label, Window, ScrolledWindow, WindowType, Box, Orientation};
+const CONFIG_PATH: &str = "/etc/blaunch.json";
 
-const KEY_ESCAPE: u32 = 65307;
-const KEY_ENTER : u32 = 65293;
+const PREVIEW_WIDTH : i32 = 64;
+const PREVIEW_HEIGHT: i32 = 36;
 
-#[derive(Deserialize, PartialEq, Eq, Debug, Clone)]
-struct Node {
-    shortcut: String,
-    description: String,
-    command: Option<String>,
-    children: Option<Vec<Node>>,
-}
+// Where a result's body text wraps
+const BODY_WIDTH_CHARS: i32 = 60;
+
+// The bar layouts: window height, entry width and the gap between results
+const BAR_HEIGHT : i32 = 28;
+const BAR_ENTRY  : i32 = 200;
+const BAR_SPACING: i32 = 12;
+
+// The overlay layout: width of the column in the middle and the space
+// above and below it
+const OVERLAY_WIDTH : i32 = 600;
+const OVERLAY_MARGIN: i32 = 120;
+
+// Results numbered for Alt+<digit>
+const QUICK_SELECT: usize = 9;
+
+const CLIPBOARD_POLL_MS: u32 = 1000;
+const TIMER_POLL_MS: u32 = 1000;
+
+// GTK_ICON_SIZE_MENU, and the same size in pixels for icons from files
+const ICON_SIZE_MENU: i32 = 1;
+const ICON_PIXELS   : i32 = 16;
 
-#[derive(Deserialize, Clone)]
-struct Config {
-    shell_prefix: String,
-    menu: Vec<Node>,
+// Color swatches are icon sized; GDK_COLORSPACE_RGB is the only colorspace
+const SWATCH_PIXELS : i32 = 16;
+const COLORSPACE_RGB: Colorspace = 0;
+
+// Size of one QR code module on screen
+const BITMAP_CELL_PIXELS: i32 = 6;
+
+fn clear_output(output: &Box) {
+    let labels = output.get_children();
+
+    for label in labels {
+        label.destroy();
+    }
 }
 
-fn borrow_nodes(nodes: &Vec<Node>) -> Vec<&Node> {
-    let mut ret = Vec::new();
+struct Row<'a> {
+    // Number for Alt+<digit>
+    index: Option<usize>,
+    icon: Option<&'a str>,
+    preview: Option<&'a str>,
+    left: &'a str,
+    right: &'a str,
+    body: Option<&'a str>,
+    swatch: Option<[u8; 4]>,
+    bitmap: Option<&'a [Vec<bool>]>,
+    classes: &'a [String],
+    loading: bool,
+    section: bool,
+    selected: bool,
+}
 
-    for n in nodes {
-        ret.push(n);
+impl<'a> Row<'a> {
+    fn new(left: &'a str, right: &'a str) -> Row<'a> {
+        Row {
+            index: None,
+            icon: None,
+            preview: None,
+            left,
+            right,
+            body: None,
+            swatch: None,
+            bitmap: None,
+            classes: &[],
+            loading: false,
+            section: false,
+            selected: false,
+        }
     }
+}
 
-    ret
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
 }
 
-#[derive(PartialEq, Eq, Debug)]
-enum Resolved<'a> {
-    Partial(Vec<&'a Node>),
-    Complete(&'a Node)
+// A square filled with the color, for color rows.
+fn swatch(rgba: [u8; 4]) -> Pixbuf {
+    let pixels = rgba.iter().cloned().cycle().
+        take((SWATCH_PIXELS * SWATCH_PIXELS * 4) as usize).collect();
+
+    Pixbuf::new_from_vec(pixels, COLORSPACE_RGB, true, 8, SWATCH_PIXELS,
+                         SWATCH_PIXELS, SWATCH_PIXELS * 4)
 }
 
-fn resolve<'a>(nodes: Vec<&'a Node>, command: String) -> Resolved {
-    if command.len() == 0 {
-        return Resolved::Partial(nodes);
+// Dark cells black and light ones white, each cell a few pixels square so
+// a QR code is big enough to scan off the screen.
+fn bitmap(cells: &[Vec<bool>]) -> Pixbuf {
+    let size = BITMAP_CELL_PIXELS as usize;
+    let width = cells.first().map_or(0, |r| r.len()) * size;
+    let mut pixels = Vec::with_capacity(width * cells.len() * size * 3);
+
+    for row in cells {
+        for _ in 0..size {
+            for &dark in row {
+                let value = if dark { 0 } else { 255 };
+                pixels.extend(std::iter::repeat_n(value, size * 3));
+            }
+        }
     }
 
-    let mut partial = vec![];
+    Pixbuf::new_from_vec(pixels, COLORSPACE_RGB, false, 8, width as i32,
+                         (cells.len() * size) as i32, (width * 3) as i32)
+}
+
+// Each row sits in an EventBox so it can take clicks and hovering. The
+// boxes are returned in order for the caller to connect.
+fn set_output_rows(output: &Box, rows: Vec<Row>) -> Vec<EventBox> {
+    clear_output(output);
+
+    let mut events = vec![];
+
+    for row in rows {
+        let event = EventBox::new();
+        output.add(&event);
+
+        let outer = Box::new(Orientation::Horizontal, 0);
+        event.add(&outer);
+
+        if let Some(style) = outer.get_style_context() {
+            style.add_class("row");
+
+            for class in row.classes {
+                style.add_class(class);
+            }
+
+            if row.selected {
+                style.add_class("selected");
+            }
+        }
+
+        if let Some(n) = row.index {
+            let index = Label::new(None);
+            index.set_markup(&format!("<small>{}</small>", n));
+            index.set_width_chars(2);
+            outer.add(&index);
 
-    for n in nodes {
-        if n.shortcut.as_str().starts_with(command.as_str()) {
-            partial.push(n);
+            if let Some(style) = index.get_style_context() {
+                style.add_class("index");
+            }
         }
 
-        if !command.as_str().starts_with(n.shortcut.as_str()) {
-            continue;
+        if row.loading {
+            let spinner = Spinner::new();
+            spinner.start();
+            outer.add(&spinner);
         }
 
-        let remaining: String = command.chars().skip(n.shortcut.len()).
-            collect();
+        // Icons are theme names unless they're a path to an image
+        if let Some(icon) = row.icon {
+            if icon.starts_with('/') {
+                if let Ok(p) = Pixbuf::new_from_file_at_size(icon, ICON_PIXELS,
+                                                             ICON_PIXELS) {
+                    outer.add(&Image::new_from_pixbuf(&p));
+                }
+            } else {
+                outer.add(&Image::new_from_icon_name(icon, ICON_SIZE_MENU));
+            }
+        }
 
-        if remaining.len() == 0 && n.children == None {
-            return Resolved::Complete(n);
+        if let Some(rgba) = row.swatch {
+            outer.add(&Image::new_from_pixbuf(&swatch(rgba)));
         }
 
-        if remaining.len() == 0 {
-            return match n.children {
-                Some(ref c) => Resolved::Partial(borrow_nodes(c)),
-                None        => Resolved::Complete(n),
-            };
+        if let Some(path) = row.preview {
+            if let Ok(p) = Pixbuf::new_from_file_at_size(path, PREVIEW_WIDTH,
+                                                         PREVIEW_HEIGHT) {
+                outer.add(&Image::new_from_pixbuf(&p));
+            }
         }
 
-        return match n.children {
-            Some(ref c) => resolve(borrow_nodes(c), remaining),
-            None        => Resolved::Partial(vec![]),
-        };
-    }
+        let left = Label::new(row.left);
 
-    Resolved::Partial(partial)
-}
+        // A body or bitmap goes under the title, so they share a column
+        if row.body.is_none() && row.bitmap.is_none() {
+            outer.add(&left);
+        } else {
+            let column = Box::new(Orientation::Vertical, 0);
+            left.set_halign(gtk::Align::Start);
+            column.add(&left);
 
-fn clear_output(output: &Box) {
-    let labels = output.get_children();
+            if let Some(text) = row.body {
+                let body = Label::new(text);
+                body.set_halign(gtk::Align::Start);
+                body.set_line_wrap(true);
+                body.set_max_width_chars(BODY_WIDTH_CHARS);
 
-    for label in labels {
-        label.destroy();
-    }
-}
+                if let Some(style) = body.get_style_context() {
+                    style.add_class("body");
+                }
 
-fn set_output_nodes(output: &Box, nodes: Vec<&Node>) {
-    clear_output(output);
+                column.add(&body);
+            }
 
-    for node in nodes {
-        let outer = Box::new(Orientation::Horizontal, 0);
-        output.add(&outer);
+            if let Some(cells) = row.bitmap {
+                let image = Image::new_from_pixbuf(&bitmap(cells));
+                image.set_halign(gtk::Align::Start);
+                column.add(&image);
+            }
+
+            outer.add(&column);
+        }
 
-        let shortcut_text: &str = &node.shortcut;
-        let shortcut = Label::new(shortcut_text);
-        outer.add(&shortcut);
+        if row.section {
+            left.set_markup(&format!("<small>{}</small>",
+                                     escape_markup(row.left)));
+        }
+
+        if row.selected {
+            left.set_markup(&format!("<b>{}</b>", escape_markup(row.left)));
+        }
 
-        let description_text: &str = &node.description;
-        let description = Label::new(description_text);
-        description.set_alignment(1.0, 0.0);
-        description.set_hexpand(true);
-        outer.add(&description);
+        let right = Label::new(row.right);
+        right.set_alignment(1.0, 0.0);
+        right.set_hexpand(true);
+        outer.add(&right);
+
+        // Highlight the row under the pointer
+        event.connect_enter_notify_event(|e, _| {
+            e.set_state_flags(gtk::STATE_FLAG_PRELIGHT, false);
+            Inhibit(false)
+        });
+        event.connect_leave_notify_event(|e, _| {
+            e.unset_state_flags(gtk::STATE_FLAG_PRELIGHT);
+            Inhibit(false)
+        });
+
+        events.push(event);
     }
 
     output.show_all();
+    events
+}
+
+// The results Alt+1 through Alt+9 launch: the first ones that do something.
+fn quick_select(items: &[Item]) -> Vec<usize> {
+    (0..items.len()).filter(|n| items[*n].action.is_some()).
+        take(QUICK_SELECT).collect()
+}
+
+fn set_output_items(output: &Box, items: &[Item], selected: Option<usize>,
+                    numbered: bool) -> Vec<EventBox> {
+    let quick = if numbered { quick_select(items) } else { vec![] };
+
+    set_output_rows(output, items.iter().enumerate().map(|(n, i)| Row {
+        index: quick.iter().position(|q| *q == n).map(|p| p + 1),
+        icon: i.icon.as_deref(),
+        preview: i.preview.as_deref(),
+        left: &i.title,
+        right: &i.description,
+        body: i.body.as_deref(),
+        swatch: i.swatch,
+        bitmap: i.bitmap.as_deref(),
+        classes: &i.classes,
+        loading: i.loading,
+        section: i.section,
+        selected: selected == Some(n),
+    }).collect())
+}
+
+// Listed notes are results like any other, newest first, so the selection
+// says which one Enter copies and delete_note removes. Clicking one copies
+// it too.
+fn show_notes(launcher: &Rc<Launcher>, notes: &[notes::Note]) {
+    if notes.is_empty() {
+        launcher.selected.set(None);
+        launcher.items.borrow_mut().clear();
+        set_output_text(&launcher.output, "No notes found..");
+        return;
+    }
+
+    show_results(launcher, notes.iter().map(|n| {
+        Item::new(&n.text, &n.timestamp, Action::Copy(n.text.clone()))
+    }).collect());
 }
 
 fn set_output_text(output: &Box, text: &str) {
@@ -120,196 +380,1807 @@ fn set_output_text(output: &Box, text: &str) {
     output.show_all();
 }
 
-fn main() {
-    let config_file = File::open("/etc/blaunch.json").
-        expect("Can't open /etc/blaunch.json");
+// The current levels, and what Enter would do to the first setting.
+fn set_output_settings(output: &Box, settings: &[quick::Setting],
+                       change: Option<quick::Change>) {
+    let levels: Vec<String> = settings.iter().enumerate().map(|(n, s)| {
+        let level = match s.level() {
+            Some(l) => format!("{}%", l),
+            None    => "unavailable".to_string(),
+        };
 
-    let config: Config = match serde_json::from_reader(config_file) {
-        Ok(n)  => n,
-        Err(e) => panic!("Can't parse /etc/blaunch.json: {}", e),
-    };
+        match change {
+            Some(c) if n == 0 => format!("{}, Enter to {}", level,
+                                         c.describe()),
+            _                 => level,
+        }
+    }).collect();
 
-    if gtk::init().is_err() {
-        println!("Failed to initialize GTK.");
-        return;
+    set_output_rows(output, settings.iter().zip(levels.iter()).
+        map(|(s, l)| Row::new(s.name(), l)).collect());
+}
+
+// Plain text copy of whatever the output area is showing, one row per line.
+fn output_snapshot(output: &Box) -> String {
+    let mut rows = vec![];
+
+    for child in output.get_children() {
+        let row = child.clone().downcast::<EventBox>().ok().
+            and_then(|e| e.get_child()).
+            and_then(|c| c.downcast::<Box>().ok());
+
+        let widgets = match row {
+            Some(r) => r.get_children(),
+            None    => vec![child],
+        };
+
+        rows.push(widgets.into_iter().
+            filter_map(|w| w.downcast::<Label>().ok()).
+            filter(|l| !l.get_style_context().
+                is_some_and(|s| s.has_class("index"))).
+            filter_map(|l| l.get_text()).collect());
     }
 
-    let window = Window::new(WindowType::Toplevel);
-    window.set_title("blaunch");
-    window.set_default_size(350, 200);
+    snapshot::text(&rows)
+}
+
+// The output area drawn the way it looks on screen, theme and all.
+fn output_image(output: &Box) -> Option<Pixbuf> {
+    let (width, height) = (output.get_allocated_width(),
+                           output.get_allocated_height());
 
-    let vbox = Box::new(Orientation::Vertical, 0);
-    window.add(&vbox);
+    if width <= 0 || height <= 0 {
+        return None;
+    }
 
-    let command = Entry::new();
-    vbox.add(&command);
+    let mut surface = cairo::ImageSurface::create(cairo::Format::ARgb32,
+                                                  width, height);
+    output.draw(&cairo::Context::new(&surface));
 
-    let scrolled = ScrolledWindow::new(None, None);
-    scrolled.set_vexpand(true);
-    vbox.add(&scrolled);
+    let stride = surface.get_stride() as usize;
+    let pixels = snapshot::rgba(&surface.get_data().ok()?,
+                                width as usize, height as usize, stride);
+
+    Some(Pixbuf::new_from_vec(pixels, COLORSPACE_RGB, true, 8, width, height,
+                              width * 4))
+}
 
-    let output_lines = Box::new(Orientation::Vertical, 0);
-    scrolled.add(&output_lines);
+fn copy_to_clipboard(text: &str) {
+    let clipboard = Clipboard::get(&gdk::Atom::intern("CLIPBOARD"));
+    clipboard.set_text(text);
 
-    set_output_nodes(&output_lines, borrow_nodes(&config.menu));
+    // Hand the contents to the clipboard manager so they survive us quitting
+    clipboard.store();
+}
+
+// Records copies for the clipboard history while the daemon runs. GTK 3
+// has no change notification we can use here, so it checks every second.
+fn watch_clipboard(config: &Rc<RefCell<Config>>) {
+    let config = config.clone();
+    let mut recorder = clipboard::Recorder::new();
+
+    gtk::timeout_add(CLIPBOARD_POLL_MS, move || {
+        let history = config.borrow().clipboard.clone();
+
+        if let Some(h) = history {
+            let clipboard = Clipboard::get(&gdk::Atom::intern("CLIPBOARD"));
 
-    command.grab_focus();
+            if let Some(text) = clipboard.wait_for_text() {
+                if let Err(e) = recorder.seen(&h, &text) {
+                    eprintln!("Can't record clipboard history: {}", e);
+                }
+            }
+        }
 
-    window.show_all();
-    window.connect_delete_event(|_, _| {
-        gtk::main_quit();
-        Inhibit(false)
+        Continue(true)
     });
+}
 
-    let c_config = config.clone();
-    command.connect_changed(move |c| {
-        let value = c.get_text().unwrap_or("".to_string());
+// Sends the notifications for timers that are due while the daemon runs.
+// Timers live in a file, so it checks every second like the clipboard.
+fn watch_timers(config: &Rc<RefCell<Config>>) {
+    let config = config.clone();
 
-        // Handle shell prefix
-        if value.starts_with(&c_config.shell_prefix) {
-            set_output_text(&output_lines, "Enter a shell command..");
-            return;
+    gtk::timeout_add(TIMER_POLL_MS, move || {
+        if let Err(e) = timers::fire(config.borrow().timers.as_ref()) {
+            eprintln!("Can't fire timers: {}", e);
         }
 
-        // Handle menu matching
-        match resolve(borrow_nodes(&c_config.menu), value) {
-            Resolved::Complete(n) => {
-                let command = match n.command {
-                    Some(ref c) => c,
-                    None        => panic!("No command for {}", n.shortcut),
-                };
+        Continue(true)
+    });
+}
 
-                match Command::new(command).spawn() {
-                    Ok (_) => {},
-                    Err(e) => panic!("Can't start process: {}", e),
-                };
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Mode {
+    // Launch the chosen entry and quit
+    Normal,
+    // Launch the chosen entry and hide until summoned again
+    Daemon,
+    // Print the chosen entry instead of launching it; Escape exits non-zero
+    Print(print::Field),
+}
 
-                gtk::main_quit();
-            },
-            Resolved::Partial(nodes) => {
-                set_output_nodes(&output_lines, nodes);
-            },
-        };
+// One launcher window: its own entry, results, macro recorder and jobs. A
+// daemon keeps one per output it's been summoned on, so two monitors can
+// each have a launcher open without stepping on each other.
+struct Launcher {
+    window: Window,
+    // The submenus the query has descended into, above the entry
+    breadcrumb: Label,
+    command: Input,
+    output: Box,
+    // What the selected result will run, under the results
+    status: Label,
+    // The monitor this window is placed on, or None for the default window
+    output_name: Option<String>,
+    // On the layer-shell overlay layer, where the compositor places it
+    layered: bool,
+    mode: Mode,
+    config: Rc<RefCell<Config>>,
+    registry: RefCell<Registry>,
+    recorder: RefCell<macros::Recorder>,
+    jobs: RefCell<jobs::Runner>,
+    finder: RefCell<cnf::Finder>,
+    // The provider results on display and which of them Enter launches
+    items: RefCell<Vec<Item>>,
+    selected: Cell<Option<usize>>,
+    // The result whose prompt is being answered in the entry, like a menu
+    // node asking "Search for:"
+    prompting: RefCell<Option<Item>>,
+}
+
+// Shows provider results. The selection stays on the same item across
+// redraws if it's still there, and otherwise starts on the first one that
+// does something.
+fn show_results(launcher: &Rc<Launcher>, items: Vec<Item>) {
+    show_items(launcher, items, true);
+}
+
+// Shows results where nothing is selected until the user moves to a row,
+// for choices Enter mustn't make on its own, like installing a package.
+fn show_choices(launcher: &Rc<Launcher>, items: Vec<Item>) {
+    show_items(launcher, items, false);
+}
+
+fn show_items(launcher: &Rc<Launcher>, items: Vec<Item>, pick_first: bool) {
+    let previous = launcher.selected.get().
+        and_then(|n| launcher.items.borrow().get(n).cloned());
+
+    let same = previous.and_then(|p| items.iter().position(|i| {
+        i.action.is_some() && i.title == p.title && i.action == p.action
+    }));
+    let selected = same.or_else(|| if pick_first {
+        items.iter().position(|i| i.action.is_some())
+    } else {
+        None
     });
 
-    let kp_config = config.clone();
-    command.connect_key_press_event(move |c, e| {
-        if e.get_keyval() == KEY_ESCAPE {
+    launcher.selected.set(selected);
+    *launcher.items.borrow_mut() = items;
+    render_results(launcher);
+}
+
+// Draws the provider results. Clicking one launches it, or fills in its
+// completion to descend into a submenu. While a macro is being recorded a
+// click only fills in the entry, for Enter to add as a step.
+fn render_results(launcher: &Rc<Launcher>) {
+    let rows = set_output_items(&launcher.output, &launcher.items.borrow(),
+                                launcher.selected.get(), true);
+
+    let command = selected_item(launcher).and_then(|i| i.action).
+        map(|a| provider::command_line(&a));
+    launcher.status.set_text(command.as_deref().unwrap_or(""));
+    launcher.status.set_visible(command.is_some());
+
+    for (n, row) in rows.into_iter().enumerate() {
+        let l = launcher.clone();
+        row.connect_button_press_event(move |_, e| {
+            if e.get_button() != 1 {
+                return Inhibit(false);
+            }
+
+            let item = match l.items.borrow().get(n).cloned() {
+                Some(i) => i,
+                None    => return Inhibit(false),
+            };
+
+            let recording = l.recorder.borrow().is_recording();
+
+            match item.completion {
+                Some(ref c) if recording || item.action.is_none() => {
+                    l.command.set_text(c);
+                    l.command.move_to_end();
+                    l.command.grab_focus();
+                },
+                _ if item.action.is_some() && !recording => {
+                    let value = l.command.get_text();
+                    launch(&l, &l.config.borrow(), &item, &value);
+                },
+                _ => {},
+            };
+
+            Inhibit(true)
+        });
+    }
+}
+
+// Moves the selection to the next (or previous) result that does something.
+fn move_selection(launcher: &Rc<Launcher>, forward: bool) {
+    let selected = {
+        let items = launcher.items.borrow();
+        let actionable: Vec<usize> = (0..items.len()).
+            filter(|n| items[*n].action.is_some()).collect();
+
+        let at = launcher.selected.get().
+            and_then(|s| actionable.iter().position(|a| *a == s));
+
+        let next = match at {
+            Some(a) if forward => actionable.get(a + 1).or(actionable.last()),
+            Some(a) if a > 0   => actionable.get(a - 1),
+            _                  => actionable.first(),
+        };
+
+        next.cloned()
+    };
+
+    launcher.selected.set(selected);
+    render_results(launcher);
+}
+
+fn selected_item(launcher: &Launcher) -> Option<Item> {
+    launcher.selected.get().
+        and_then(|n| launcher.items.borrow().get(n).cloned())
+}
+
+// Closes the launcher. A daemon's window is only hidden and starts over
+// with an empty entry once the handler that closed it is done.
+fn dismiss(launcher: &Rc<Launcher>) {
+    match launcher.mode {
+        Mode::Normal   => {
             gtk::main_quit();
-        }
+            return;
+        },
+        // Closing without choosing anything
+        Mode::Print(_) => process::exit(1),
+        Mode::Daemon   => {},
+    };
 
-        if e.get_keyval() == KEY_ENTER {
-            let value = c.get_text().unwrap_or("".to_string());
+    launcher.window.hide();
+    launcher.prompting.borrow_mut().take();
 
-            if value.starts_with(&kp_config.shell_prefix) {
-                let command: String = value.chars().skip(
-                        kp_config.shell_prefix.len()).collect();
+    let launcher = launcher.clone();
+    gtk::idle_add(move || {
+        launcher.jobs.borrow_mut().clear_finished();
+        launcher.command.set_text("");
+        Continue(false)
+    });
+}
 
-                match Command::new("sh").arg("-c").arg(command).spawn() {
-                    Ok (_) => gtk::main_quit(),
-                    Err(e) => panic!("Can't start process: {}", e),
+// Carries out an action. Failures come back as a message for the output
+// area instead of taking the launcher, or a daemon, down with them.
+fn run_action(registry: &mut Registry, action: &Action)
+    -> Result<(), String> {
+    match *action {
+        Action::Spawn(ref c) => Command::new("sh").arg("-c").arg(c).spawn().
+            map(|_| ()).map_err(|e| format!("Can't start process: {}", e)),
+        Action::Exec(ref p) => Command::new(p).spawn().map(|_| ()).
+            map_err(|e| format!("Can't start {}: {}", p, e)),
+        Action::Builtin(ref b) => builtins::run(b).
+            map_err(|e| format!("Can't run {}: {}", b, e)),
+        Action::Copy(ref t) => {
+            copy_to_clipboard(t);
+            Ok(())
+        },
+        Action::Activate(ref w) => windows::activate(w).
+            map_err(|e| format!("Can't switch windows: {}", e)),
+        // Only meaningful when picked by hand; see launch
+        Action::Query(_) => Ok(()),
+        // Replay each step as if it were typed: a complete shortcut if there
+        // is one, otherwise the first result that does something. Macros
+        // don't nest, and a failing step stops the rest.
+        Action::Macro(ref steps) => {
+            for step in steps {
+                let items = registry.query(step);
+                let found = items.iter().find(|i| i.immediate).
+                    or_else(|| items.iter().find(|i| i.action.is_some())).
+                    filter(|i| i.prompt.is_none());
+
+                match found.and_then(|i| i.action.as_ref()) {
+                    Some(&Action::Macro(_)) | None => {},
+                    Some(a) => run_action(registry, a)?,
                 };
             }
+
+            Ok(())
+        },
+    }
+}
+
+// Runs the item and closes the launcher, except for items that wait: those
+// become a job with a progress row and the launcher stays open.
+fn activate(launcher: &Rc<Launcher>, config: &Config, item: &Item,
+            value: &str) {
+    // Ask for the argument first, with the entry starting over for it
+    if item.prompt.is_some() {
+        *launcher.prompting.borrow_mut() = Some(item.clone());
+
+        if value.is_empty() {
+            update(launcher, config, String::new());
+        } else {
+            launcher.command.set_text("");
         }
 
-        Inhibit(false)
-    });
+        return;
+    }
 
-    gtk::main();
+    // Scripts get the selection on stdout and nothing runs
+    if let Mode::Print(field) = launcher.mode {
+        match print::selection(field, item, value) {
+            Some(s) => println!("{}", s),
+            None    => process::exit(1),
+        };
+
+        process::exit(0);
+    }
+
+    // Count the typed characters plus Enter when the item needed it
+    if let Some(ref h) = config.history {
+        let keystrokes = value.chars().count() + !item.immediate as usize;
+
+        if let Err(e) = history::record(&h.file, keystrokes, &item.title,
+                                        item.action.as_ref()) {
+            eprintln!("Can't record history: {}", e);
+        }
+    }
+
+    let result = match item.action {
+        Some(Action::Spawn(ref c)) if item.wait => {
+            launcher.jobs.borrow_mut().start(&item.title, c);
+            return;
+        },
+        // Jobs run through a shell; quoted, the name is still just a program
+        Some(Action::Exec(ref p)) if item.wait => {
+            launcher.jobs.borrow_mut().start(&item.title, &shell_quote(p));
+            return;
+        },
+        Some(ref a) => run_action(&mut launcher.registry.borrow_mut(), a),
+        None        => Err(format!("No command for {}", item.title)),
+    };
+
+    // A failure stays on screen instead of closing
+    match result {
+        Ok (_) => dismiss(launcher),
+        Err(e) => set_output_text(&launcher.output, &e),
+    };
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{Node, Resolved, resolve, borrow_nodes};
-
-    fn test_data() -> Vec<Node> {
-        vec![Node {
-            shortcut: "terminal".to_string(),
-            description: "terminal emulator".to_string(),
-            command: Some("xfce4-terminal".to_string()),
-            children: None,
-        }, Node {
-            shortcut: "web".to_string(),
-            description: "web browsers".to_string(),
-            command: None,
-            children: Some(vec![Node {
-                shortcut: "chrome".to_string(),
-                description: "Google Chrome".to_string(),
-                command: Some("chromium".to_string()),
-                children: None,
-            }, Node {
-                shortcut: "firefox".to_string(),
-                description: "Mozilla FireFox".to_string(),
-                command: Some("firefox".to_string()),
-                children: None,
-            }]),
-        }]
-    }
-
-    fn expect_partial(command: &str, mut expected: Vec<&str>) {
-        let data = test_data();
-        let nodes = match resolve(borrow_nodes(&data), command.to_string()) {
-            Resolved::Complete(_) => panic!("Expected partial match"),
-            Resolved::Partial(n)  => n,
-        };
+// Launches a result picked with Enter, a click or Alt+<digit>. A job's
+// progress row is shown above the menu, so the entry starts over.
+fn launch(launcher: &Rc<Launcher>, config: &Config, item: &Item,
+          value: &str) {
+    if let Some(Action::Query(ref q)) = item.action {
+        launcher.command.set_text(q);
+        launcher.command.move_to_end();
+        return;
+    }
 
-        for node in nodes {
-            let p = match expected.iter().position(|&e| e == node.shortcut) {
-                None    => panic!("Unexpected node {}", node.shortcut),
-                Some(p) => p,
-            };
+    activate(launcher, config, item, value);
+
+    if item.wait {
+        launcher.command.set_text("");
+    }
+}
+
+// Appends a note to the file, or hands it to the configured command as a
+// job, like a command that waits. The launcher then stays open with the
+// job's row, so returns whether it can close.
+fn save_note(launcher: &Launcher, config: &notes::Config, text: &str)
+    -> io::Result<bool> {
+    match notes::pipe_command(config, text) {
+        Some(c) => {
+            launcher.jobs.borrow_mut().start("note", &c);
+            launcher.command.set_text("");
+            Ok(false)
+        },
+        None    => notes::append(config, text).map(|_| true),
+    }
+}
+
+// Runs the configured fallback for a query that nothing matched.
+fn run_fallback(launcher: &Rc<Launcher>, config: &Config, value: &str) {
+    let f = match config.fallback {
+        Some(ref f) => f,
+        None        => return,
+    };
+
+    match fallback::resolve(f, value) {
+        Ok (Fallback::Search(c)) | Ok (Fallback::Shell(c)) => {
+            let result = run_action(&mut launcher.registry.borrow_mut(),
+                                    &Action::Spawn(c));
+
+            if let Err(e) = result {
+                set_output_text(&launcher.output, &e);
+                return;
+            }
+        },
+        Ok (Fallback::Note(text)) => match config.notes {
+            Some(ref n) => match save_note(launcher, n, &text) {
+                Ok (true)  => {},
+                Ok (false) => return,
+                Err(e)     => {
+                    set_output_text(&launcher.output,
+                                    &format!("Can't save note: {}", e));
+                    return;
+                },
+            },
+            None => {
+                set_output_text(&launcher.output,
+                                "The note fallback needs a notes section");
+                return;
+            },
+        },
+        Err(e) => {
+            set_output_text(&launcher.output, &e);
+            return;
+        },
+    };
+
+    dismiss(launcher);
+}
+
+// Whether the input belongs to one of the prefix modes (shell, notes, quick
+// settings) rather than the providers.
+fn in_prefix_mode(config: &Config, value: &str) -> bool {
+    if value.starts_with(&config.shell_prefix) {
+        return true;
+    }
 
-            expected.remove(p);
+    if let Some(ref n) = config.notes {
+        if notes::parse_input(&n.prefix, value).is_some() {
+            return true;
         }
+    }
 
-        if expected.len() != 0 {
-            panic!("Expected nodes missing: {:?}", expected);
+    if let Some(ref q) = config.quick_settings {
+        if quick::parse_input(q, value).is_some() {
+            return true;
         }
     }
 
-    fn expect_complete(command: &str, expected: &str) {
-        let data = test_data();
-        let node = match resolve(borrow_nodes(&data), command.to_string()) {
-            Resolved::Partial(_)  => panic!("Expected complete match"),
-            Resolved::Complete(n) => n,
+    false
+}
+
+// While a macro is being recorded nothing launches on its own; Enter adds
+// the query as a step instead.
+fn update(launcher: &Rc<Launcher>, config: &Config, value: String) {
+    let output = &launcher.output;
+
+    // Answering a prompt: the prompt goes above the entry and the only
+    // result is the prompting one, with the answer filled in. Until the
+    // answer is one the action can take, the row says why and does nothing.
+    let prompting = launcher.prompting.borrow().clone();
+    if let Some(item) = prompting {
+        launcher.breadcrumb.set_text(item.prompt.as_deref().unwrap_or(""));
+        launcher.breadcrumb.set_visible(true);
+
+        let answered = item.action.as_ref().
+            map(|a| provider::checked_answer(a, &value));
+
+        let row = match answered {
+            Some(Err(e)) => Item {
+                description: e,
+                action: None,
+                ..item
+            },
+            answered     => Item {
+                action: answered.and_then(|a| a.ok()),
+                ..item
+            },
+        };
+
+        show_results(launcher, vec![Item {
+            prompt: None,
+            immediate: false,
+            ..row
+        }]);
+        return;
+    }
+
+    // Only provider results have a menu path
+    let path = if in_prefix_mode(config, &value) {
+        vec![]
+    } else {
+        launcher.registry.borrow().path(&value)
+    };
+
+    let crumbs: Vec<String> = path.iter().map(|p| format!("{} \u{203a}", p)).
+        collect();
+    launcher.breadcrumb.set_text(&crumbs.join(" "));
+    launcher.breadcrumb.set_visible(!path.is_empty());
+    launcher.status.set_visible(false);
+
+    // Handle shell prefix. Commands that aren't installed get suggestions
+    // for packages to install, below the rows of any install jobs. One only
+    // installs when it's picked: clicked, or moved to and launched.
+    if value.starts_with(&config.shell_prefix) {
+        let command: String = value.chars().skip(
+                config.shell_prefix.len()).collect();
+        let mut items = launcher.jobs.borrow().items();
+
+        match cnf::missing_binary(&command) {
+            Some(b) => items.extend(launcher.finder.borrow_mut().items(&b)),
+            None if items.is_empty() => {
+                set_output_text(output, "Enter a shell command..");
+                return;
+            },
+            None => items.push(Item::message("Enter a shell command..")),
+        };
+
+        show_choices(launcher, items);
+        return;
+    }
+
+    // Handle notes
+    if let Some(ref n) = config.notes {
+        match notes::parse_input(&n.prefix, &value) {
+            Some(notes::Input::Append(_)) => {
+                set_output_text(output, "Enter a note..");
+                return;
+            },
+            Some(notes::Input::List(filter)) => {
+                show_notes(launcher, &notes::recent(&n.file, filter));
+                return;
+            },
+            None => {},
         };
+    }
+
+    // Handle quick settings
+    if let Some(ref q) = config.quick_settings {
+        if let Some(query) = quick::parse_input(q, &value) {
+            match query.invalid {
+                Some(ref e) => set_output_text(output, e),
+                None        => set_output_settings(output, &query.settings,
+                                                   query.change),
+            };
+            return;
+        }
+    }
+
+    // Handle provider results, below the rows of any jobs
+    let mut items = launcher.jobs.borrow().items();
+    items.extend(launcher.registry.borrow_mut().query(&value));
 
-        assert_eq!(node.shortcut, expected);
+    let recording = launcher.recorder.borrow().is_recording();
+
+    // Say what Enter will do when nothing else would do anything
+    if let Some(ref f) = config.fallback {
+        if !value.is_empty() && !recording &&
+           !items.iter().any(|i| i.action.is_some()) {
+            if let Ok(fallback) = fallback::resolve(f, &value) {
+                items.push(Item::message(
+                    &fallback::describe(&fallback, &value)));
+            }
+        }
     }
 
-    #[test]
-    fn it_resolves_no_match_to_empty() {
-        expect_partial("wrong", vec![]);
+    // Items that wait always need Enter so a long job never starts by
+    // accident
+    match items.iter().find(|i| i.immediate && !i.wait && !recording) {
+        Some(i) => activate(launcher, config, i, &value),
+        None    => show_results(launcher, items),
+    };
+}
+
+// The monitor the config asks for. The focused window's monitor falls back
+// to the pointer's when nothing has focus.
+fn configured_monitor(screen: &gdk::Screen, placement: window::Placement)
+    -> i32 {
+    let pointer = || {
+        let (_, x, y, _) = screen.get_display().get_pointer();
+        screen.get_monitor_at_point(x, y)
+    };
+
+    match placement {
+        window::Placement::Monitor(m) => m,
+        window::Placement::Focus      => match screen.get_active_window() {
+            Some(w) => screen.get_monitor_at_window(&w),
+            None    => pointer(),
+        },
+        window::Placement::Pointer    => pointer(),
     }
+}
+
+// Centers the window on the named output if there is one, or otherwise the
+// monitor picked in the config. Unknown monitors leave it wherever the
+// window manager puts it.
+fn place(window: &Window, output_name: Option<&str>,
+         config: &window::Config) {
+    let screen = match gdk::Screen::get_default() {
+        Some(s) => s,
+        None    => return,
+    };
+
+    let monitor = match output_name {
+        Some(name) => (0..screen.get_n_monitors()).find(|m| {
+            screen.get_monitor_plug_name(*m).as_deref() == Some(name)
+        }).ok_or_else(|| format!("No output named {}", name)),
+        None => window::parse_placement(&config.monitor).
+            map(|p| configured_monitor(&screen, p)),
+    };
+
+    let area = match monitor {
+        Ok (m) if m >= 0 && m < screen.get_n_monitors() => {
+            screen.get_monitor_geometry(m)
+        },
+        Ok (m) => {
+            eprintln!("No monitor {}", m);
+            return;
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        },
+    };
+
+    let (width, height) = window.get_size();
+
+    // Bars span the monitor along its top or bottom edge
+    match window::parse_layout(&config.layout) {
+        Ok (window::Layout::TopBar) => {
+            window.resize(area.width, height);
+            window.move_(area.x, area.y);
+        },
+        Ok (window::Layout::BottomBar) => {
+            window.resize(area.width, height);
+            window.move_(area.x, area.y + area.height - height);
+        },
+        // Window managers fullscreen on the monitor the window is on
+        Ok (window::Layout::Overlay) => {
+            window.move_(area.x, area.y);
+            window.fullscreen();
+        },
+        _ => window.move_(area.x + (area.width - width) / 2 + config.offset_x,
+                          area.y + (area.height - height) / 2 +
+                          config.offset_y),
+    };
+}
 
-    #[test]
-    fn it_resolves_empty_string_to_root_node() {
-        expect_partial("", vec!["web", "terminal"]);
+fn show(launcher: &Launcher, query: Option<String>) {
+    if let Some(q) = query {
+        launcher.command.set_text(&q);
+        launcher.command.move_to_end();
     }
 
-    #[test]
-    fn it_resolves_partial_first_level() {
-        expect_partial("t", vec!["terminal"]);
+    if !launcher.layered {
+        place(&launcher.window, launcher.output_name.as_deref(),
+              &launcher.config.borrow().window);
     }
 
-    #[test]
-    fn it_resolves_second_level() {
-        expect_partial("web", vec!["firefox", "chrome"]);
+    launcher.window.show_all();
+    launcher.window.present();
+    launcher.command.grab_focus();
+}
+
+type Launchers = Rc<RefCell<Vec<Rc<Launcher>>>>;
+
+// The user's stylesheet, applied to every window on the screen
+struct Theme {
+    provider: RefCell<Option<CssProvider>>,
+    watcher: RefCell<theme::Watcher>,
+    // The inline settings from the config
+    settings: RefCell<Option<CssProvider>>,
+}
+
+// Loads the stylesheet again. One that doesn't parse (say, halfway through
+// an edit) leaves the old one applied; one that's gone removes the theme.
+fn apply_theme(theme: &Theme) {
+    let screen = match gdk::Screen::get_default() {
+        Some(s) => s,
+        None    => return,
+    };
+
+    let watcher = theme.watcher.borrow();
+    let path = watcher.path().to_string_lossy().into_owned();
+
+    let provider = if watcher.exists() {
+        let provider = CssProvider::new();
+
+        if let Err(e) = provider.load_from_path(&path) {
+            eprintln!("Can't load theme {}: {}", path, e);
+            return;
+        }
+
+        Some(provider)
+    } else {
+        None
+    };
+
+    if let Some(old) = theme.provider.borrow_mut().take() {
+        StyleContext::remove_provider_for_screen(&screen, &old);
     }
 
-    #[test]
-    fn it_resolves_partial_second_level() {
-        expect_partial("webchr", vec!["chrome"]);
+    if let Some(ref p) = provider {
+        StyleContext::add_provider_for_screen(
+            &screen, p, gtk::STYLE_PROVIDER_PRIORITY_USER);
     }
 
-    #[test]
-    fn it_resolves_complete_first_level() {
-        expect_complete("terminal", "terminal");
+    *theme.provider.borrow_mut() = provider;
+}
+
+// Applies the base styles and the config's inline theme settings below the
+// stylesheet.
+fn apply_settings(theme: &Theme, config: &theme::Config) {
+    let screen = match gdk::Screen::get_default() {
+        Some(s) => s,
+        None    => return,
+    };
+
+    let provider = match theme::write_settings(config) {
+        Ok (path) => {
+            let provider = CssProvider::new();
+
+            if let Err(e) = provider.load_from_path(&path.to_string_lossy()) {
+                eprintln!("Can't load theme settings: {}", e);
+                return;
+            }
+
+            Some(provider)
+        },
+        Err(e) => {
+            eprintln!("Can't write theme settings: {}", e);
+            return;
+        },
+    };
+
+    if let Some(old) = theme.settings.borrow_mut().take() {
+        StyleContext::remove_provider_for_screen(&screen, &old);
     }
 
-    #[test]
-    fn it_resolves_complete_second_level() {
-        expect_complete("webfirefox", "firefox");
+    if let Some(ref p) = provider {
+        StyleContext::add_provider_for_screen(
+            &screen, p, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
     }
+
+    *theme.settings.borrow_mut() = provider;
 }
 
+// Points the theme at another file and applies it.
+fn switch_theme(theme: &Theme, file: &str) {
+    *theme.watcher.borrow_mut() = theme::Watcher::new(file);
+    theme.watcher.borrow_mut().changed();
+    apply_theme(theme);
+}
+
+// The window for an output, created the first time it's asked for.
+fn launcher_for(launchers: &Launchers, config: &Rc<RefCell<Config>>,
+                output_name: Option<String>) -> Rc<Launcher> {
+    let found = launchers.borrow().iter().
+        find(|l| l.output_name == output_name).cloned();
+
+    match found {
+        Some(l) => l,
+        None    => {
+            let l = build_launcher(config, output_name, Mode::Daemon);
+            launchers.borrow_mut().push(l.clone());
+            l
+        },
+    }
+}
+
+// Carries out a request from the socket or D-Bus.
+fn handle_request(launchers: &Launchers, config: &Rc<RefCell<Config>>,
+                  theme: &Theme, request: daemon::Request) {
+    match request {
+        daemon::Request::Toggle { output } => {
+            let l = launcher_for(launchers, config, output);
+
+            if l.window.is_visible() {
+                dismiss(&l);
+            } else {
+                show(&l, None);
+            }
+        },
+        daemon::Request::Show { output, query } => {
+            show(&launcher_for(launchers, config, output), query);
+        },
+        daemon::Request::Hide { output } => {
+            let l = launcher_for(launchers, config, output);
+
+            if l.window.is_visible() {
+                dismiss(&l);
+            }
+        },
+        // A broken config keeps the old one running. The root is copied out
+        // first so the config isn't still borrowed when it's replaced.
+        daemon::Request::Reload => {
+            let root = config.borrow().root.clone();
+
+            match load_config(root) {
+                Ok (c) => {
+                    for l in launchers.borrow().iter() {
+                        *l.registry.borrow_mut() = build_registry(&c, l.mode);
+                    }
+
+                    switch_theme(theme, &c.theme.file);
+                    apply_settings(theme, &c.theme);
+                    *config.borrow_mut() = c;
+                },
+                Err(e) => eprintln!("Can't reload: {}", e),
+            };
+        },
+        daemon::Request::ReloadTheme(Some(file)) => switch_theme(theme, &file),
+        daemon::Request::ReloadTheme(None) => apply_theme(theme),
+    };
+}
+
+#[cfg(feature = "x11-hotkey")]
+fn grab_hotkey(launchers: &Launchers, config: &Rc<RefCell<Config>>,
+               theme: &Rc<Theme>, chord: &str) {
+    let presses = match hotkey::grab(chord) {
+        Ok (r) => r,
+        Err(e) => {
+            eprintln!("Can't grab hotkey {}: {}", chord, e);
+            return;
+        },
+    };
+
+    let launchers = launchers.clone();
+    let config = config.clone();
+    let theme = theme.clone();
+    gtk::timeout_add(50, move || {
+        for _ in presses.try_iter() {
+            handle_request(&launchers, &config, &theme,
+                           daemon::Request::Toggle { output: None });
+        }
+
+        Continue(true)
+    });
+}
+
+#[cfg(not(feature = "x11-hotkey"))]
+fn grab_hotkey(_: &Launchers, _: &Rc<RefCell<Config>>, _: &Rc<Theme>,
+               chord: &str) {
+    eprintln!("Built without the x11-hotkey feature, ignoring hotkey {}",
+              chord);
+}
+
+// Loads the config, with the menu starting at the submenu given with --menu
+// if there was one.
+fn load_config(root: Option<String>) -> Result<Config, String> {
+    let mut config = config::load(CONFIG_PATH)?;
+
+    if let Some(ref r) = root {
+        config.menu = node::submenu(&config.menu, r,
+                                    &dynamic::load_children)?;
+    }
+
+    config.root = root;
+    Ok(config)
+}
+
+// Only a daemon checks for updates; a one-off launcher is gone long before
+// a check would matter.
+fn build_registry(config: &Config, mode: Mode) -> Registry {
+    let mut registry = Registry::new();
+
+    if let Some(ref e) = config.error {
+        registry.add(std::boxed::Box::new(Notice::new(
+            &format!("Safe mode: {}", e))));
+    }
+
+    if let Some(ref u) = config.update {
+        if mode == Mode::Daemon {
+            registry.add(std::boxed::Box::new(Update::new(
+                u.clone(), env!("CARGO_PKG_VERSION"))));
+        }
+    }
+
+    // Arithmetic can't be mistaken for anything else, so its answer leads
+    registry.add(std::boxed::Box::new(Calc));
+    registry.add(std::boxed::Box::new(Color));
+
+    // Favorites first, then recent launches, then the menu
+    registry.add(std::boxed::Box::new(Pinned::new(&config.pins,
+                                                  &config.menu)));
+
+    if let Some(ref h) = config.history {
+        registry.add(std::boxed::Box::new(Recent::new(h)));
+    }
+
+    registry.add(std::boxed::Box::new(Menu::new(config.menu.clone())));
+
+    if let Some(ref a) = config.applications {
+        registry.add(std::boxed::Box::new(Apps::new(a.clone())));
+    }
+
+    if let Some(ref r) = config.run {
+        registry.add(std::boxed::Box::new(Run::new(r.clone())));
+    }
+
+    if let Some(ref h) = config.history {
+        registry.add(std::boxed::Box::new(Stats::new(h)));
+    }
+
+    if let Some(ref m) = config.macros {
+        registry.add(std::boxed::Box::new(Macros::new(m.clone())));
+    }
+
+    if let Some(ref w) = config.windows {
+        registry.add(std::boxed::Box::new(Windows::new(w.clone())));
+    }
+
+    if let Some(ref s) = config.search {
+        registry.add(std::boxed::Box::new(Search::new(s.clone())));
+    }
+
+    if let Some(ref s) = config.ssh {
+        registry.add(std::boxed::Box::new(Ssh::new(s.clone())));
+    }
+
+    if let Some(ref c) = config.clipboard {
+        registry.add(std::boxed::Box::new(Clips::new(c.clone())));
+    }
+
+    if let Some(ref f) = config.files {
+        registry.add(std::boxed::Box::new(Files::new(f.clone())));
+    }
+
+    if let Some(ref p) = config.processes {
+        registry.add(std::boxed::Box::new(Processes::new(p.clone())));
+    }
+
+    if let Some(ref p) = config.power {
+        registry.add(std::boxed::Box::new(Power::new(p.clone())));
+    }
+
+    if let Some(ref u) = config.units {
+        registry.add(std::boxed::Box::new(Units::new(u.clone())));
+    }
+
+    if let Some(ref b) = config.bookmarks {
+        registry.add(std::boxed::Box::new(Bookmarks::new(b.clone())));
+    }
+
+    if let Some(ref p) = config.pass {
+        registry.add(std::boxed::Box::new(Pass::new(p.clone())));
+    }
+
+    if let Some(ref w) = config.wifi {
+        registry.add(std::boxed::Box::new(Wifi::new(w.clone())));
+    }
+
+    if let Some(ref a) = config.audio {
+        registry.add(std::boxed::Box::new(Audio::new(a.clone())));
+    }
+
+    if let Some(ref b) = config.bluetooth {
+        registry.add(std::boxed::Box::new(Bluetooth::new(b.clone())));
+    }
+
+    if let Some(ref t) = config.tmux {
+        registry.add(std::boxed::Box::new(Tmux::new(t.clone())));
+    }
+
+    if let Some(ref r) = config.repos {
+        registry.add(std::boxed::Box::new(Repos::new(r.clone())));
+    }
+
+    if let Some(ref s) = config.steam {
+        registry.add(std::boxed::Box::new(Steam::new(s.clone())));
+    }
+
+    if let Some(ref v) = config.vms {
+        registry.add(std::boxed::Box::new(Vms::new(v.clone())));
+    }
+
+    if let Some(ref c) = config.containers {
+        registry.add(std::boxed::Box::new(Containers::new(c.clone())));
+    }
+
+    if let Some(ref d) = config.drives {
+        registry.add(std::boxed::Box::new(Drives::new(d.clone())));
+    }
+
+    if let Some(ref d) = config.displays {
+        registry.add(std::boxed::Box::new(Displays::new(d.clone())));
+    }
+
+    if let Some(ref k) = config.keyboard {
+        registry.add(std::boxed::Box::new(Keyboard::new(k.clone())));
+    }
+
+    if let Some(ref w) = config.wallpapers {
+        registry.add(std::boxed::Box::new(Wallpapers::new(w.clone())));
+    }
+
+    if let Some(ref c) = config.convert {
+        registry.add(std::boxed::Box::new(Convert::new(c.clone())));
+    }
+
+    if let Some(ref d) = config.dictionary {
+        registry.add(std::boxed::Box::new(Dictionary::new(d.clone())));
+    }
+
+    if let Some(ref t) = config.translate {
+        registry.add(std::boxed::Box::new(Translate::new(t.clone())));
+    }
+
+    if let Some(ref t) = config.todo {
+        registry.add(std::boxed::Box::new(Todo::new(t.clone())));
+    }
+
+    if let Some(ref s) = config.snippets {
+        registry.add(std::boxed::Box::new(Snippets::new(s.clone())));
+    }
+
+    if let Some(ref r) = config.recent_files {
+        registry.add(std::boxed::Box::new(RecentFiles::new(r.clone())));
+    }
+
+    if let Some(ref m) = config.man {
+        registry.add(std::boxed::Box::new(Man::new(m.clone())));
+    }
+
+    if let Some(ref k) = config.kube {
+        registry.add(std::boxed::Box::new(Kube::new(k.clone())));
+    }
+
+    if let Some(ref v) = config.vpn {
+        registry.add(std::boxed::Box::new(Vpns::new(v.clone())));
+    }
+
+    if let Some(ref q) = config.qr {
+        registry.add(std::boxed::Box::new(Qr::new(q.clone())));
+    }
+
+    if let Some(ref t) = config.timers {
+        registry.add(std::boxed::Box::new(Timers::new(t.clone())));
+    }
+
+    if let Some(ref w) = config.weather {
+        registry.add(std::boxed::Box::new(Weather::new(w.clone())));
+    }
+
+    if let Some(ref t) = config.trash {
+        registry.add(std::boxed::Box::new(Trash::new(t.clone())));
+    }
+
+    if let Some(ref e) = config.emoji {
+        registry.add(std::boxed::Box::new(Emoji::new(e.clone())));
+    }
+
+    if !config.projects.is_empty() {
+        registry.add(std::boxed::Box::new(Projects::new(
+            config.projects.clone())));
+    }
+
+    registry
+}
+
+#[cfg(feature = "layer-shell")]
+fn use_layer_shell(window: &Window, config: &window::Config,
+                   layout: window::Layout) -> bool {
+    config.layer_shell && layer_shell::init(window, &config.name, layout)
+}
+
+#[cfg(not(feature = "layer-shell"))]
+fn use_layer_shell(_: &Window, _: &window::Config, _: window::Layout)
+    -> bool {
+    false
+}
+
+// Sets the window up as a translucent overlay, with the entry and results in
+// a column in the middle. Without a compositor the background is opaque.
+fn overlay(window: &Window, container: &Box) {
+    if let Some(screen) = gdk::Screen::get_default() {
+        if screen.is_composited() {
+            window.set_visual(screen.get_rgba_visual().as_ref());
+        }
+    }
+
+    window.set_app_paintable(true);
+
+    if let Some(style) = window.get_style_context() {
+        style.add_class("overlay");
+    }
+
+    container.set_halign(gtk::Align::Center);
+    container.set_size_request(OVERLAY_WIDTH, -1);
+    container.set_margin_top(OVERLAY_MARGIN);
+    container.set_margin_bottom(OVERLAY_MARGIN);
+}
+
+fn build_launcher(config: &Rc<RefCell<Config>>, output_name: Option<String>,
+                  mode: Mode) -> Rc<Launcher> {
+    let window = Window::new(WindowType::Toplevel);
+
+    {
+        let config = config.borrow();
+        window.set_title("blaunch");
+        window.set_wmclass(&config.window.name, &config.window.class);
+        window.set_role(&config.window.role);
+
+        if config.window.floating {
+            window.set_type_hint(gdk::WindowTypeHint::Dialog);
+        }
+
+        window.set_decorated(config.window.decorated);
+        window.set_skip_taskbar_hint(config.window.skip_taskbar);
+        window.set_skip_pager_hint(config.window.skip_pager);
+        window.set_keep_above(config.window.keep_above);
+    }
+
+    // A bar puts the entry and the results side by side in one row, the
+    // others stack them
+    let layout = window::parse_layout(&config.borrow().window.layout).
+        unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            window::Layout::Window
+        });
+    let (orientation, spacing) = match layout {
+        window::Layout::TopBar | window::Layout::BottomBar => {
+            (Orientation::Horizontal, BAR_SPACING)
+        },
+        _ => (Orientation::Vertical, 0),
+    };
+
+    let layered = use_layer_shell(&window, &config.borrow().window, layout);
+
+    let container = Box::new(orientation, 0);
+    window.add(&container);
+
+    let breadcrumb = Label::new(None);
+    breadcrumb.set_halign(gtk::Align::Start);
+    breadcrumb.set_no_show_all(true);
+    container.add(&breadcrumb);
+
+    if let Some(style) = breadcrumb.get_style_context() {
+        style.add_class("breadcrumb");
+    }
+
+    let command = Input::new(config.borrow().multiline_entry);
+    container.add(&command.widget());
+
+    let scrolled = ScrolledWindow::new(None, None);
+    container.add(&scrolled);
+
+    if layout == window::Layout::Window {
+        window.set_default_size(350, 200);
+        scrolled.set_vexpand(true);
+    } else if layout == window::Layout::Overlay {
+        overlay(&window, &container);
+        scrolled.set_vexpand(true);
+    } else {
+        window.set_default_size(-1, BAR_HEIGHT);
+        command.widget().set_size_request(BAR_ENTRY, -1);
+        scrolled.set_policy(PolicyType::Automatic, PolicyType::Never);
+        scrolled.set_hexpand(true);
+    }
+
+    let output = Box::new(orientation, spacing);
+    scrolled.add(&output);
+
+    // A bar has no room for the status line
+    let status = Label::new(None);
+    status.set_halign(gtk::Align::Start);
+    status.set_selectable(true);
+    status.set_no_show_all(true);
+
+    if orientation == Orientation::Vertical {
+        container.add(&status);
+    }
+
+    if let Some(style) = status.get_style_context() {
+        style.add_class("status");
+    }
+
+    let mut registry = build_registry(&config.borrow(), mode);
+    let items = registry.query("");
+
+    let launcher = Rc::new(Launcher {
+        window,
+        breadcrumb,
+        command,
+        output,
+        status,
+        output_name,
+        layered,
+        mode,
+        config: config.clone(),
+        registry: RefCell::new(registry),
+        recorder: RefCell::new(macros::Recorder::default()),
+        jobs: RefCell::new(jobs::Runner::new()),
+        finder: RefCell::new(cnf::Finder::new()),
+        items: RefCell::new(vec![]),
+        selected: Cell::new(None),
+        prompting: RefCell::new(None),
+    });
+
+    show_results(&launcher, items);
+
+    // A daemon's window is only ever hidden, never destroyed
+    let d_launcher = launcher.clone();
+    launcher.window.connect_delete_event(move |_, _| {
+        dismiss(&d_launcher);
+        Inhibit(mode == Mode::Daemon)
+    });
+
+    let c_config = config.clone();
+    let c_launcher = launcher.clone();
+    launcher.command.connect_changed(move || {
+        let value = c_launcher.command.get_text();
+        update(&c_launcher, &c_config.borrow(), value);
+    });
+
+    // Redraw when a provider finishes background work, like a menu node's
+    // children_command, or a job finishes
+    let t_config = config.clone();
+    let t_launcher = launcher.clone();
+    gtk::timeout_add(100, move || {
+        let provided = t_launcher.registry.borrow_mut().poll();
+        let finished = t_launcher.jobs.borrow_mut().poll();
+        let found = t_launcher.finder.borrow_mut().poll();
+
+        if !provided && !finished && !found {
+            return Continue(true);
+        }
+
+        let value = t_launcher.command.get_text();
+        update(&t_launcher, &t_config.borrow(), value);
+
+        Continue(true)
+    });
+
+    let kp_config = config.clone();
+    let l = launcher.clone();
+    launcher.command.connect_key_press_event(move |e| {
+        let kp_config = kp_config.borrow();
+
+        let value = l.command.get_text();
+        let key = pressed(&kp_config, e);
+
+        // Quitting leaves a prompt or clears the entry first, back to the
+        // root listing, and closes the launcher once it's empty
+        if key == Some(Key::Quit) {
+            if l.prompting.borrow_mut().take().is_some() {
+                if value.is_empty() {
+                    update(&l, &kp_config, value);
+                } else {
+                    l.command.set_text("");
+                }
+            } else if value.is_empty() || kp_config.escape_quits {
+                dismiss(&l);
+            } else {
+                l.command.set_text("");
+            }
+
+            return Inhibit(true);
+        }
+
+        if key == Some(Key::Clear) {
+            l.command.set_text("");
+            return Inhibit(true);
+        }
+
+        // While a prompt is answered Enter runs its result, whatever the
+        // answer looks like
+        if key == Some(Key::Launch) && l.prompting.borrow().is_some() {
+            if let Some(i) = selected_item(&l) {
+                launch(&l, &kp_config, &i, &value);
+            }
+
+            return Inhibit(true);
+        }
+
+        // Readline-style editing
+        let edit = match key {
+            Some(Key::KillLine) => Some(editing::Edit::KillLine),
+            Some(Key::KillWord) => Some(editing::Edit::KillWord),
+            Some(Key::Home)     => Some(editing::Edit::Home),
+            Some(Key::End)      => Some(editing::Edit::End),
+            _                   => None,
+        };
+        if let Some(edit) = edit {
+            let at = l.command.cursor();
+            let (text, cursor) = editing::apply(edit, &value, at);
+
+            if text != value {
+                l.command.set_text(&text);
+            }
+
+            l.command.set_cursor(cursor);
+            return Inhibit(true);
+        }
+
+        // A new line in a multi-line entry, for shell commands
+        if key == Some(Key::Newline) && l.command.is_multiline() {
+            if value.starts_with(&kp_config.shell_prefix) {
+                let at = l.command.cursor();
+                let mut chars: Vec<char> = value.chars().collect();
+                chars.insert(at.min(chars.len()), '\n');
+
+                l.command.set_text(&chars.into_iter().collect::<String>());
+                l.command.set_cursor(at + 1);
+            }
+
+            return Inhibit(true);
+        }
+
+        // Copy the current results as text or as an image, e.g. to share the
+        // output of a capture-mode command.
+        if key == Some(Key::CopyResults) {
+            copy_to_clipboard(&output_snapshot(&l.output));
+            return Inhibit(true);
+        }
+
+        if key == Some(Key::CopyResultsImage) {
+            if let Some(image) = output_image(&l.output) {
+                let clipboard = Clipboard::get(&gdk::Atom::intern(
+                    "CLIPBOARD"));
+                clipboard.set_image(&image);
+                clipboard.store();
+            }
+
+            return Inhibit(true);
+        }
+
+        // Start recording a macro. Pressing it again saves the recorded
+        // steps under whatever shortcut is typed in the entry.
+        if key == Some(Key::Record) {
+            if let Some(ref m) = kp_config.macros {
+                let steps = {
+                    let mut recorder = l.recorder.borrow_mut();
+
+                    if !recorder.is_recording() {
+                        recorder.start();
+                        l.window.set_title("blaunch (recording)");
+                        return Inhibit(true);
+                    }
+
+                    recorder.stop()
+                };
+
+                l.window.set_title("blaunch");
+
+                if let Some(steps) = steps {
+                    match macros::save(&m.file, value.trim(), steps) {
+                        Ok (s) => {
+                            let saved = format!("Saved macro {}", s);
+                            set_output_text(&l.output, &saved);
+                        },
+                        Err(e) => set_output_text(&l.output, &e),
+                    };
+                }
+
+                return Inhibit(true);
+            }
+        }
+
+        // Move the selection through the results
+        // Listed notes and package suggestions for a missing shell command
+        // can be picked too
+        let listing_notes = kp_config.notes.as_ref().is_some_and(|n| {
+            matches!(notes::parse_input(&n.prefix, &value),
+                     Some(notes::Input::List(_)))
+        });
+        let choosing = listing_notes ||
+            (value.starts_with(&kp_config.shell_prefix) &&
+             l.items.borrow().iter().any(|i| i.action.is_some()));
+
+        if (key == Some(Key::Next) || key == Some(Key::Previous)) &&
+           (!in_prefix_mode(&kp_config, &value) || choosing) {
+            move_selection(&l, key == Some(Key::Next));
+            return Inhibit(true);
+        }
+
+        // Fill in the selected result's completion, to descend into a
+        // submenu without typing the rest of its shortcut
+        if key == Some(Key::Complete) {
+            let completion = selected_item(&l).and_then(|i| i.completion);
+
+            if let Some(c) = completion.filter(|c| *c != value) {
+                l.command.set_text(&c);
+                l.command.move_to_end();
+            }
+
+            return Inhibit(true);
+        }
+
+        // Alt+<digit> launches the result with that number
+        let digit = e.get_keyval();
+        if (KEY_1..=KEY_9).contains(&digit) &&
+           e.get_state().contains(gdk::MOD1_MASK) &&
+           !in_prefix_mode(&kp_config, &value) &&
+           !l.recorder.borrow().is_recording() {
+            let item = {
+                let items = l.items.borrow();
+                quick_select(&items).get((digit - KEY_1) as usize).
+                    map(|n| items[*n].clone())
+            };
+
+            if let Some(i) = item {
+                launch(&l, &kp_config, &i, &value);
+            }
+
+            return Inhibit(true);
+        }
+
+        // Pin the selected result so it's listed first when nothing is
+        // typed, or unpin it if it already is.
+        if key == Some(Key::Pin) && !in_prefix_mode(&kp_config, &value) {
+            if let Some(Item { title, action: Some(action), .. }) =
+                    selected_item(&l) {
+                let message = match pins::toggle(&kp_config.pins.file, &title,
+                                                 &action) {
+                    Ok (true)  => format!("Pinned {}", title),
+                    Ok (false) => format!("Unpinned {}", title),
+                    Err(e)     => format!("Can't pin {}: {}", title, e),
+                };
+
+                set_output_text(&l.output, &message);
+            }
+
+            return Inhibit(true);
+        }
+
+        // Notes: launching saves a new note or copies the selected listed
+        // one and delete_note removes the selected one. The listed notes are
+        // the results, in the same order, so the selection indexes them.
+        if let Some(ref n) = kp_config.notes {
+            match notes::parse_input(&n.prefix, &value) {
+                Some(notes::Input::Append(text))
+                    if key == Some(Key::Launch) && !text.is_empty() => {
+                    match save_note(&l, n, text) {
+                        Ok (true)  => dismiss(&l),
+                        Ok (false) => {},
+                        Err(e)     => set_output_text(
+                            &l.output, &format!("Can't save note: {}", e)),
+                    };
+                },
+                Some(notes::Input::List(filter)) => {
+                    let found = notes::recent(&n.file, filter);
+                    let selected = l.selected.get().and_then(|s| found.get(s));
+
+                    if let (Some(Key::Launch), Some(note)) = (key, selected) {
+                        copy_to_clipboard(&note.text);
+                        dismiss(&l);
+                        return Inhibit(true);
+                    }
+
+                    if let (Some(Key::DeleteNote), Some(note)) =
+                        (key, selected) {
+                        match notes::delete(&n.file, note) {
+                            Ok (_) => show_notes(&l, &notes::recent(&n.file,
+                                                                    filter)),
+                            Err(e) => set_output_text(&l.output, &format!(
+                                "Can't delete note: {}", e)),
+                        };
+
+                        return Inhibit(true);
+                    }
+                },
+                _ => {},
+            };
+        }
+
+        // Quick settings: decrease/increase nudge the first listed setting
+        // and launching applies a typed amount to it. Either way the
+        // launcher stays open showing the resulting level.
+        if let Some(ref q) = kp_config.quick_settings {
+            if let Some(query) = quick::parse_input(q, &value) {
+                let setting = match query.settings.first() {
+                    Some(s) => *s,
+                    None    => return Inhibit(false),
+                };
+
+                let step = q.step as i32;
+                let result = match key {
+                    Some(Key::Decrease) => Some(setting.adjust(-step)),
+                    Some(Key::Increase) => Some(setting.adjust(step)),
+                    Some(Key::Launch)   =>
+                        query.change.map(|c| setting.change(c)),
+                    _                   => None,
+                };
+
+                match result {
+                    Some(Ok (_)) => {
+                        set_output_settings(&l.output, &query.settings, None);
+                        return Inhibit(true);
+                    },
+                    Some(Err(e)) => {
+                        set_output_text(&l.output, &e.to_string());
+                    },
+                    None         => {},
+                };
+            }
+        }
+
+        if key == Some(Key::Launch) &&
+           value.starts_with(&kp_config.shell_prefix) {
+            let command: String = value.chars().skip(
+                    kp_config.shell_prefix.len()).collect();
+
+            // Rather than close and fail quietly, stay open with the
+            // package suggestions. Only one the user moved to installs,
+            // and the command can be run once it's there.
+            if cnf::missing_binary(&command).is_some() {
+                if let Some(i) = selected_item(&l) {
+                    activate(&l, &kp_config, &i, &value);
+                    update(&l, &kp_config, value.clone());
+                }
+
+                return Inhibit(true);
+            }
+
+            match Command::new("sh").arg("-c").arg(command).spawn() {
+                Ok (_) => dismiss(&l),
+                Err(e) => set_output_text(
+                    &l.output, &format!("Can't start process: {}", e)),
+            };
+        }
+
+        if key == Some(Key::LaunchAlternate) && !in_prefix_mode(&kp_config,
+                                                                &value) {
+            let item = selected_item(&l).and_then(|i| {
+                let alternate = i.alternate.clone()?;
+                Some(Item { action: Some(alternate), ..i })
+            });
+
+            if let Some(i) = item {
+                launch(&l, &kp_config, &i, &value);
+            }
+
+            return Inhibit(true);
+        }
+
+        // Launch the selected result, or add the query to the macro being
+        // recorded.
+        if key == Some(Key::Launch) && !in_prefix_mode(&kp_config, &value) {
+            if l.recorder.borrow().is_recording() {
+                let steps = {
+                    let mut recorder = l.recorder.borrow_mut();
+                    recorder.record(&value);
+                    recorder.step_count()
+                };
+
+                l.window.set_title(&format!("blaunch (recording, {} steps)",
+                                            steps));
+                l.command.set_text("");
+                return Inhibit(true);
+            }
+
+            match selected_item(&l) {
+                Some(i) => {
+                    launch(&l, &kp_config, &i, &value);
+                    return Inhibit(true);
+                },
+                None if !value.is_empty() => {
+                    run_fallback(&l, &kp_config, &value);
+                },
+                None => {},
+            };
+        }
+
+        // Lines are only added through the newline key
+        Inhibit(l.command.is_multiline() &&
+                keys::normalize(e.get_keyval()) == keys::RETURN)
+    });
+
+    launcher.command.grab_focus();
+
+    launcher
+}
+
+// What the keymap says a key press does.
+fn pressed(config: &Config, e: &gdk::EventKey) -> Option<Key> {
+    let state = e.get_state();
+    let modifiers: Vec<Modifier> = [
+        (gdk::SHIFT_MASK, Modifier::Shift),
+        (gdk::CONTROL_MASK, Modifier::Control),
+        (gdk::MOD1_MASK, Modifier::Alt),
+        (gdk::SUPER_MASK, Modifier::Super),
+    ].iter().filter(|&&(mask, _)| state.contains(mask)).
+        map(|&(_, m)| m).collect();
+
+    keys::lookup(&config.keymap, e.get_keyval(), &modifiers)
+}
+
+// `blaunch doctor` prints what's wrong with the environment instead of
+// launching, exiting non-zero if anything is outright broken.
+fn run_doctor() {
+    let mut checks = doctor::run(CONFIG_PATH);
+
+    checks.push(match gtk::init() {
+        Ok (_) => doctor::Check::new("gtk", doctor::Status::Ok,
+                                     "GTK initialized"),
+        Err(_) => doctor::Check::new("gtk", doctor::Status::Failure,
+            "Can't initialize GTK; check the display check above"),
+    });
+
+    print!("{}", doctor::report(&checks));
+
+    if doctor::failed(&checks) {
+        process::exit(1);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut request = None;
+
+    match args.get(1).map(|a| a.as_str()) {
+        Some("doctor") => {
+            run_doctor();
+            return;
+        },
+        // Send any socket command to the daemon, e.g.
+        // `blaunch ctl reload-theme ~/.config/blaunch/dark.css`
+        Some("ctl") => {
+            match daemon::send(&daemon::socket_path(), &args[2..].join(" ")) {
+                Ok (_) => return,
+                Err(e) => {
+                    eprintln!("Can't reach the daemon: {}", e);
+                    process::exit(1);
+                },
+            };
+        },
+        // Print how a query resolves as JSON, without touching the display.
+        // Exits non-zero when nothing matches.
+        Some("resolve") => {
+            let config = match config::load(CONFIG_PATH) {
+                Ok (c) => c,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(2);
+                },
+            };
+
+            let query = args[2..].join(" ");
+            let report = headless::resolve_query(&config.menu, &query);
+            println!("{}", headless::to_json(&report));
+
+            if report.matches.is_empty() {
+                process::exit(1);
+            }
+            return;
+        },
+        _ => {},
+    };
+
+    let args = match args::parse(&args[1..]) {
+        Ok (a) => a,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        },
+    };
+
+    let checked = args.layout.as_ref().map(|l| window::parse_layout(l));
+
+    if let Some(Err(e)) = checked {
+        eprintln!("{}", e);
+        process::exit(2);
+    }
+
+    // Pass --toggle, --show or --hide to a running daemon, or start
+    // normally if there isn't one
+    if let Some(ref line) = args.remote {
+        match daemon::send(&daemon::socket_path(), line) {
+            Ok (_) => return,
+            // Nothing to hide
+            Err(_) if line.starts_with("hide") => return,
+            Err(e) => eprintln!("No daemon running ({}), starting", e),
+        };
+
+        request = daemon::parse_request(line);
+    }
+
+    let mode = match args.print {
+        Some(f) => Mode::Print(f),
+        // Stay resident with the window hidden until toggled
+        None if args.daemon => Mode::Daemon,
+        None => Mode::Normal,
+    };
+
+    let initial = args.query;
+    let layout = args.layout;
+
+    let mut config = match load_config(args.menu) {
+        Ok (c) => c,
+        Err(e) => {
+            eprintln!("{}", e);
+            config::fallback(CONFIG_PATH, &e)
+        },
+    };
+
+    if let Some(l) = layout {
+        config.window.layout = l;
+    }
+
+    // GTK takes the Wayland app_id from the program name, so it has to be
+    // set before anything is created
+    glib::utils::set_prgname(Some(config.window.name.as_str()));
+
+    if gtk::init().is_err() {
+        println!("Failed to initialize GTK.");
+        return;
+    }
+
+    let theme = Rc::new(Theme {
+        provider: RefCell::new(None),
+        watcher: RefCell::new(theme::Watcher::new(&config.theme.file)),
+        settings: RefCell::new(None),
+    });
+
+    theme.watcher.borrow_mut().changed();
+    apply_theme(&theme);
+    apply_settings(&theme, &config.theme);
+
+    // Re-apply the stylesheet whenever it's saved
+    let w_theme = theme.clone();
+    gtk::timeout_add(500, move || {
+        let changed = w_theme.watcher.borrow_mut().changed();
+
+        if changed {
+            apply_theme(&w_theme);
+        }
+
+        Continue(true)
+    });
+
+    let config = Rc::new(RefCell::new(config));
+
+    if mode != Mode::Daemon {
+        // Without a daemon there's just the one window, on the requested
+        // output if there was one
+        let (output_name, query) = match request {
+            Some(daemon::Request::Show { output, query }) => (output, query),
+            Some(daemon::Request::Toggle { output }) => (output, None),
+            _ => (None, None),
+        };
+
+        show(&build_launcher(&config, output_name, mode), query.or(initial));
+        gtk::main();
+        return;
+    }
+
+    let listener = match daemon::Listener::bind(&daemon::socket_path()) {
+        Ok (l) => l,
+        Err(e) => {
+            eprintln!("Can't start daemon: {}", e);
+            process::exit(1);
+        },
+    };
+
+    // The default window always exists; the ones for specific outputs are
+    // made the first time they're summoned
+    let launchers: Launchers = Rc::new(RefCell::new(vec![]));
+    launcher_for(&launchers, &config, None);
+
+    let d_launchers = launchers.clone();
+    let d_config = config.clone();
+    let d_theme = theme.clone();
+    gtk::timeout_add(100, move || {
+        for r in listener.poll() {
+            handle_request(&d_launchers, &d_config, &d_theme, r);
+        }
+
+        Continue(true)
+    });
+
+    let b_launchers = launchers.clone();
+    let b_config = config.clone();
+    let b_theme = theme.clone();
+    dbus::serve(move |r| handle_request(&b_launchers, &b_config, &b_theme, r));
+
+    let chord = config.borrow().hotkey.clone();
+    if let Some(chord) = chord {
+        grab_hotkey(&launchers, &config, &theme, &chord);
+    }
+
+    watch_clipboard(&config);
+    watch_timers(&config);
+
+    gtk::main();
+}